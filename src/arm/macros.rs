@@ -1,4 +1,84 @@
 #[macro_export]
+macro_rules! system_register {
+    ($name: ident, $register: literal, $value_struct: ident) => {
+        paste::paste! {
+            #[doc = concat!("This function reads the value of the `", $register, "` register.")]
+            #[inline]
+            #[must_use]
+            pub fn [<read_ $name>]() -> $value_struct {
+                $value_struct([<read_ $name _raw>]())
+            }
+
+            #[doc = concat!("This function writes the value into the `", $register, "` register.")]
+            #[inline]
+            pub fn [<write_ $name>](value: $value_struct) {
+                [<write_ $name _raw>](value.0);
+            }
+
+            $crate::system_register!([<$name _raw>], $register);
+        }
+    };
+    ($name: ident, $register: literal) => {
+        paste::paste! {
+            #[doc = concat!("This function reads the raw value of the `", $register, "` register.")]
+            #[inline]
+            #[must_use]
+            pub fn [<read_ $name>]() -> u64 {
+                let value: u64;
+                unsafe {
+                    core::arch::asm!(
+                        concat!("mrs {}, ", $register),
+                        out(reg) value,
+                        options(nomem, nostack, preserves_flags)
+                    );
+                }
+                value
+            }
+
+            #[doc = concat!(
+                "This function writes the raw value into the `", $register, "` register."
+            )]
+            #[inline]
+            pub fn [<write_ $name>](value: u64) {
+                unsafe {
+                    core::arch::asm!(
+                        concat!("msr ", $register, ", {}"),
+                        in(reg) value,
+                        options(nostack, preserves_flags)
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// This macro generates the feature enum with the detection of the features. Every feature is
+/// read from the bits `start_bit..end_bit` of the specified ID register. The bit range is checked
+/// at compile time, so an invalid range is rejected with a compile error instead of a panic during
+/// the detection.
+///
+/// ```rust
+/// use libcpu::cpu_features;
+///
+/// cpu_features! {
+///     #[derive(Clone, Copy, Debug)]
+///     pub enum Feature {
+///         AES("ID_AA64ISAR0_EL1", "AES", 4, 8) = 0b0001
+///     }
+/// }
+/// ```
+///
+/// ```rust,compile_fail
+/// use libcpu::cpu_features;
+///
+/// cpu_features! {
+///     #[derive(Clone, Copy, Debug)]
+///     pub enum Feature {
+///         AES("ID_AA64ISAR0_EL1", "AES", 8, 4) = 0b0001
+///     }
+/// }
+/// ```
+#[macro_export]
 macro_rules! cpu_features {
     ($(#[$attr:meta])* $vis: vis enum $name: ident {
         $($(#[$feat_attr:meta])* $feat_ident: ident ($register: literal, $feat_name: literal, $start_bit: expr, $end_bit: expr) = $value: expr),*
@@ -11,8 +91,17 @@ macro_rules! cpu_features {
             )*
         }
 
-        impl alloc::fmt::Display for $name {
-            fn fmt(&self, formatter: &mut alloc::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        const _: () = {
+            $(
+            assert!(
+                $start_bit < $end_bit && $end_bit <= 64,
+                concat!("The bit range of ", stringify!($feat_ident), " is invalid")
+            );
+            )*
+        };
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
                 write!(formatter, "{}", match self {
                     $(
                     Self::$feat_ident => $feat_name,
@@ -23,37 +112,114 @@ macro_rules! cpu_features {
 
         impl $name {
 
-            pub fn enabled_features() -> alloc::vec::Vec<Self> {
-                let mut data = alloc::vec::Vec::new();
-                {
-                    let mut register: crate::Register = 0;
-                    unsafe {
-                        asm!(
-                            "mrs {0}, ID_AA64ISAR0_EL1",
-                            out(reg) register,
-                            options(pure, nomem, preserves_flags, nostack)
-                        );
-                    }
-                    Self::enabled_features_of("ID_AA64ISAR0_EL1", register, &mut data);
+            /// This constant lists all features, that are known by this library. Unlike
+            /// `all_features`, this list is available without an allocator.
+            pub const ALL: &'static [Self] = &[
+                $(
+                Self::$feat_ident,
+                )*
+            ];
+
+            /// This function returns the short identifier of the feature (like `"AVX2"`). Unlike the
+            /// [Display](core::fmt::Display) implementation, that returns the long description, the
+            /// identifier is the name of the variant, so it's useful for compact logging.
+            #[inline]
+            #[must_use]
+            pub const fn name(self) -> &'static str {
+                const NAMES: &[&str] = &[
+                    $(
+                    stringify!($feat_ident),
+                    )*
+                ];
+                NAMES[self as usize]
+            }
+
+            /// This function returns whether the feature is supported by the current processor.
+            /// This function doesn't allocate.
+            #[inline]
+            #[must_use]
+            pub fn is_supported(self) -> bool {
+                match self {
+                    $(
+                    Self::$feat_ident => Self::read_id_register($register).map_or(false, |data| {
+                        let bits = $crate::RegisterExt::bits(data, $start_bit..$end_bit);
+                        (bits & $value) == $value
+                    }),
+                    )*
                 }
-                data
             }
 
+            /// This function writes the features, that are supported by the current processor, into
+            /// the buffer and returns the number of written features. If the buffer is too small,
+            /// the remaining features are skipped. This function doesn't allocate.
+            #[inline]
+            pub fn enabled_features_into(buffer: &mut [Self]) -> usize {
+                let features = Self::ALL.iter().copied().filter(|feature| feature.is_supported());
+                buffer.iter_mut().zip(features).map(|(slot, feature)| *slot = feature).count()
+            }
+
+            /// This function returns all features, that are supported by the current processor, as
+            /// a list. This function is only available with the `alloc` feature.
+            #[cfg(feature = "alloc")]
+            pub fn enabled_features() -> alloc::vec::Vec<Self> {
+                Self::ALL.iter().copied().filter(|feature| feature.is_supported()).collect()
+            }
+
+            /// This function returns all features, that are known by this library, as a list. This
+            /// function is only available with the `alloc` feature.
+            #[cfg(feature = "alloc")]
             #[inline]
             pub fn all_features() -> alloc::vec::Vec<Self> {
-                alloc::vec![
+                Self::ALL.to_vec()
+            }
+
+            /// This function returns an iterator over all features, that are known by this library,
+            /// with their descriptions. Like on x86, the description is the value returned by the
+            /// [Display](core::fmt::Display) implementation. This function doesn't allocate.
+            ///
+            /// ```rust
+            /// use libcpu::CPUFeature;
+            ///
+            /// let (feature, description) = CPUFeature::all_with_descriptions()
+            ///     .find(|(feature, _)| *feature == CPUFeature::CRC32)
+            ///     .unwrap();
+            /// assert_eq!(description, "CRC32");
+            /// assert_eq!(description, feature.to_string());
+            /// assert_eq!(feature.name(), "CRC32");
+            /// assert_eq!(CPUFeature::all_with_descriptions().count(), CPUFeature::ALL.len());
+            /// ```
+            #[cfg_attr(feature = "alloc", doc = r#"
+```rust
+use libcpu::CPUFeature;
+
+assert_eq!(CPUFeature::all_with_descriptions().count(), CPUFeature::all_features().len());
+```
+"#)]
+            #[inline]
+            pub fn all_with_descriptions() -> impl Iterator<Item = (Self, &'static str)> {
+                const DESCRIPTIONS: &[($name, &str)] = &[
                     $(
-                    Self::$feat_ident,
+                    ($name::$feat_ident, $feat_name),
                     )*
-                ]
+                ];
+                DESCRIPTIONS.iter().copied()
             }
 
-            fn enabled_features_of(register: &str, data: crate::Register, features: &mut alloc::vec::Vec<Self>) {
-                $(
-                if register == $register && (data.get_bits($start_bit..$end_bit) & $value) == $value {
-                    features.push(Self::$feat_ident);
+            // Only the ID registers, that are used by the feature list, are read
+            fn read_id_register(register: &str) -> Option<$crate::Register> {
+                if register != "ID_AA64ISAR0_EL1" {
+                    return None;
                 }
-                )*
+
+                let value: $crate::Register;
+                unsafe {
+                    core::arch::asm!(
+                        "mrs {0}, ID_AA64ISAR0_EL1",
+                        out(reg) value,
+                        options(pure, nomem, preserves_flags, nostack)
+                    );
+                }
+                Some(value)
             }
         }
     }
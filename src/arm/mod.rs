@@ -1,8 +1,12 @@
 pub(crate) mod macros;
+pub(crate) mod state;
+
+pub use state::CpuState;
 
 use core::arch::asm;
+use core::fmt::{Display, Formatter};
 use bit_field::BitField;
-use crate::cpu_features;
+use crate::{cpu_features, system_register};
 
 cpu_features! {
     #[allow(non_camel_case_types)]
@@ -12,8 +16,501 @@ cpu_features! {
     }
 }
 
-pub fn wait_for_interrupts() {
+/// This function executes a full memory barrier with the `dmb ish` instruction. All loads and
+/// stores before the barrier are observed by the inner shareable domain before any load or store
+/// after the barrier.
+///
+/// This barrier is the strongest ordering and also covers the acquire (loads after the barrier
+/// are not moved before it) and release (stores before the barrier are not moved after it)
+/// orderings.
+///
+/// ```rust
+/// let mut buffer = [0_u8; 4];
+/// buffer[0] = 0xAB;
+/// libcpu::memory_fence();
+/// assert_eq!(buffer[0], 0xAB);
+/// ```
+///
+/// # See also
+/// - [DMB](https://developer.arm.com/documentation/dui0802/b/A64-General-Instructions/DMB) by
+/// [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn memory_fence() {
+    unsafe {
+        asm!("dmb ish", options(nostack, preserves_flags));
+    }
+}
+
+/// This function suspends the execution with the `wfi` instruction until an interrupt, a debug
+/// event or a reset occurs. The CPU is also woken up by interrupts, that are masked in the `DAIF`
+/// register, but the interrupt is not taken in that case. Executing this instruction at EL0 can
+/// be trapped to EL1 by the kernel.
+///
+/// # See also
+/// - [WFI](https://developer.arm.com/documentation/dui0802/b/A64-General-Instructions/WFI) by
+/// [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn wait_for_interrupt() {
+    unsafe {
+        asm!("wfi", options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// This function suspends the execution with the `wfe` instruction until an event is signaled
+/// (e.g. by [send_event] on another core), an interrupt occurs or the event register is already
+/// set. This is used in spin-wait loops to save power while waiting for a lock.
+///
+/// # See also
+/// - [WFE](https://developer.arm.com/documentation/dui0802/b/A64-General-Instructions/WFE) by
+/// [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn wait_for_event() {
+    unsafe {
+        asm!("wfe", options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// This function signals an event to all cores in the system with the `sev` instruction. This
+/// wakes up the cores, that are waiting with [wait_for_event].
+///
+/// # See also
+/// - [SEV](https://developer.arm.com/documentation/dui0802/b/A64-General-Instructions/SEV) by
+/// [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn send_event() {
+    unsafe {
+        asm!("sev", options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// This function waits with the `dsb ish` instruction until all memory accesses, cache and TLB
+/// maintenance operations before the barrier are completed in the inner shareable domain. This
+/// barrier is required after changing the translation tables or invalidating the TLB.
+///
+/// # See also
+/// - [DSB](https://developer.arm.com/documentation/dui0802/b/A64-General-Instructions/DSB) by
+/// [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn data_synchronization_barrier() {
+    unsafe {
+        asm!("dsb ish", options(nostack, preserves_flags));
+    }
+}
+
+/// This function flushes the pipeline with the `isb` instruction, so all instructions after the
+/// barrier are fetched again and are observing the changes of the system registers (like the
+/// translation table base registers) before the barrier.
+///
+/// # See also
+/// - [ISB](https://developer.arm.com/documentation/dui0802/b/A64-General-Instructions/ISB) by
+/// [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn instruction_synchronization_barrier() {
+    unsafe {
+        asm!("isb", options(nostack, preserves_flags));
+    }
+}
+
+/// This function invalidates all stage 1 translations for EL1 in the TLB with the `tlbi vmalle1`
+/// instruction. The invalidation must be completed with [data_synchronization_barrier] and
+/// [instruction_synchronization_barrier].
+///
+/// # See also
+/// - [TLBI](https://developer.arm.com/documentation/ddi0596/2021-12/Base-Instructions/TLBI--TLB-Invalidate-operation--an-alias-of-SYS-)
+/// by [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn tlbi_vmalle1() {
+    unsafe {
+        asm!("tlbi vmalle1", options(nostack, preserves_flags));
+    }
+}
+
+/// This function invalidates all stage 1 translations for EL1 in the TLBs of all cores in the
+/// inner shareable domain with the `tlbi vmalle1is` instruction. This is the broadcast variant of
+/// [tlbi_vmalle1], which is needed after changing translation tables, that are shared between the
+/// cores. The invalidation must be completed with [data_synchronization_barrier] (`dsb ish`
+/// waits for the invalidation on all cores) and [instruction_synchronization_barrier]:
+///
+/// ```rust,no_run
+/// use libcpu::{data_synchronization_barrier, instruction_synchronization_barrier, tlbi_vmalle1is};
+///
+/// // The stores to the translation table must be visible before the invalidation
+/// data_synchronization_barrier();
+/// tlbi_vmalle1is();
+/// data_synchronization_barrier();
+/// instruction_synchronization_barrier();
+/// ```
+///
+/// # See also
+/// - [TLBI](https://developer.arm.com/documentation/ddi0596/2021-12/Base-Instructions/TLBI--TLB-Invalidate-operation--an-alias-of-SYS-)
+/// by [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn tlbi_vmalle1is() {
+    unsafe {
+        asm!("tlbi vmalle1is", options(nostack, preserves_flags));
+    }
+}
+
+/// This function cleans and invalidates the data cache line, that contains the virtual address,
+/// to the Point of Coherency with the `dc civac` instruction. Dirty data is written back to the
+/// memory before the line is invalidated, so no data is lost. This is needed before a device
+/// without cache coherency reads the memory (e.g. a DMA buffer) or after it has written the
+/// memory. The address must be mapped, otherwise the instruction leads into a data abort.
+///
+/// The operation is only completed after [data_synchronization_barrier]:
+///
+/// ```rust,no_run
+/// use libcpu::{data_synchronization_barrier, dc_civac};
+///
+/// let buffer = [0_u8; 64];
+/// dc_civac(buffer.as_ptr() as u64);
+/// data_synchronization_barrier();
+/// ```
+///
+/// # See also
+/// - [DC CIVAC](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Instructions/DC-CIVAC--Data-or-unified-Cache-line-Clean-and-Invalidate-by-VA-to-PoC)
+/// by [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn dc_civac(address: u64) {
+    unsafe {
+        asm!("dc civac, {}", in(reg) address, options(nostack, preserves_flags));
+    }
+}
+
+/// This function invalidates all instruction caches of the current core to the Point of
+/// Unification with the `ic iallu` instruction. This is needed after loading or modifying code,
+/// because the instruction cache isn't coherent with the data cache. The new code must be cleaned
+/// from the data cache before, and the invalidation must be completed with
+/// [data_synchronization_barrier] and [instruction_synchronization_barrier] before the code is
+/// executed:
+///
+/// ```rust,no_run
+/// use libcpu::{
+///     data_synchronization_barrier, dc_civac, ic_iallu, instruction_synchronization_barrier,
+/// };
+///
+/// let code = [0xD65F03C0_u32]; // ret
+/// dc_civac(code.as_ptr() as u64);
+/// data_synchronization_barrier();
+/// ic_iallu();
+/// data_synchronization_barrier();
+/// instruction_synchronization_barrier();
+/// ```
+///
+/// # See also
+/// - [IC IALLU](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Instructions/IC-IALLU--Instruction-Cache-Invalidate-All-to-PoU)
+/// by [Arm Limited](https://www.arm.com)
+#[inline]
+pub fn ic_iallu() {
+    unsafe {
+        asm!("ic iallu", options(nostack, preserves_flags));
+    }
+}
+
+/// This structure represents the value of a Translation Table Base Register (TTBR0_EL1 or
+/// TTBR1_EL1). The register contains the physical base address of the translation table and the
+/// Address Space Identifier (ASID).
+///
+/// The following structure shows the layout of the register:
+/// - Bit 0 - Common not Private (CnP)
+/// - Bit 1 to 47 - Base address of the translation table
+/// - Bit 48 to 63 - Address Space Identifier (ASID)
+///
+/// ```rust
+/// use libcpu::TranslationTableBase;
+///
+/// let value = TranslationTableBase(0x002A_0000_4008_1000);
+/// assert_eq!(value.base_address(), 0x4008_1000);
+/// assert_eq!(value.asid(), 0x2A);
+/// assert_eq!(TranslationTableBase::new(0x4008_1000, 0x2A), value);
+/// ```
+///
+/// # See also
+/// - [TTBR0_EL1](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/TTBR0-EL1--Translation-Table-Base-Register-0--EL1-)
+/// by [Arm Limited](https://www.arm.com)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+#[repr(transparent)]
+pub struct TranslationTableBase(pub u64);
+
+impl TranslationTableBase {
+    /// This function creates a new value for the translation table base register with the
+    /// specified base address and ASID.
+    #[inline]
+    #[must_use]
+    pub fn new(base_address: u64, asid: u16) -> Self {
+        let mut value = 0u64;
+        value.set_bits(1..48, base_address.get_bits(1..48));
+        value.set_bits(48..64, u64::from(asid));
+        Self(value)
+    }
+
+    /// This function returns the physical base address of the translation table.
+    #[inline]
+    #[must_use]
+    pub fn base_address(&self) -> u64 {
+        self.0.get_bits(1..48) << 1
+    }
+
+    /// This function returns the Address Space Identifier (ASID) of the translation table.
+    #[inline]
+    #[must_use]
+    pub fn asid(&self) -> u16 {
+        self.0.get_bits(48..64) as u16
+    }
+}
+
+system_register!(ttbr0_el1, "ttbr0_el1", TranslationTableBase);
+system_register!(ttbr1_el1, "ttbr1_el1", TranslationTableBase);
+
+/// This structure represents the value of the Architectural Feature Access Control Register
+/// (CPACR_EL1). The FPEN field (bit 20 and 21) controls, whether the floating-point and Advanced
+/// SIMD (NEON) instructions are trapped at EL0 and EL1:
+/// - `0b00` or `0b10` - The instructions are trapped at EL0 and EL1
+/// - `0b01` - The instructions are only trapped at EL0
+/// - `0b11` - The instructions are not trapped
+///
+/// ```rust
+/// use libcpu::CoprocessorAccessControl;
+///
+/// let mut value = CoprocessorAccessControl(0);
+/// assert!(!value.fp_simd_enabled());
+///
+/// value.set_fpen(CoprocessorAccessControl::FPEN_NO_TRAP);
+/// assert_eq!(value.0, 0b11 << 20);
+/// assert!(value.fp_simd_enabled());
+/// ```
+///
+/// # See also
+/// - [CPACR_EL1](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/CPACR-EL1--Architectural-Feature-Access-Control-Register)
+/// by [Arm Limited](https://www.arm.com)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+#[repr(transparent)]
+pub struct CoprocessorAccessControl(pub u64);
+
+impl CoprocessorAccessControl {
+    /// This constant represents the value of the FPEN field, with that the floating-point and
+    /// Advanced SIMD instructions are not trapped at EL0 and EL1.
+    pub const FPEN_NO_TRAP: u8 = 0b11;
+
+    /// This function returns the value of the FPEN field.
+    #[inline]
+    #[must_use]
+    pub fn fpen(&self) -> u8 {
+        self.0.get_bits(20..22) as u8
+    }
+
+    /// This function replaces the value of the FPEN field.
+    #[inline]
+    pub fn set_fpen(&mut self, fpen: u8) {
+        self.0.set_bits(20..22, u64::from(fpen));
+    }
+
+    /// This function returns whether the floating-point and Advanced SIMD instructions are allowed
+    /// at EL0 and EL1 without a trap.
+    #[inline]
+    #[must_use]
+    pub fn fp_simd_enabled(&self) -> bool {
+        self.fpen() == Self::FPEN_NO_TRAP
+    }
+}
+
+system_register!(cpacr_el1, "cpacr_el1", CoprocessorAccessControl);
+
+/// This function allows the floating-point and Advanced SIMD (NEON) instructions at EL0 and EL1 by
+/// setting the FPEN field of the CPACR_EL1 register to the "no trap" value. The other fields of
+/// the register are kept. This is the ARM equivalent of enabling the SSE state on x86, so it must
+/// be called in EL1 before the compiler emits floating-point or SIMD instructions.
+///
+/// # See also
+/// - [CoprocessorAccessControl] (Source Code)
+#[inline]
+pub fn enable_fp_simd() {
+    let mut value = read_cpacr_el1();
+    value.set_fpen(CoprocessorAccessControl::FPEN_NO_TRAP);
+    write_cpacr_el1(value);
+    instruction_synchronization_barrier();
+}
+
+/// This function returns whether the floating-point and Advanced SIMD (NEON) instructions are
+/// allowed at EL0 and EL1, which is indicated by the FPEN field of the CPACR_EL1 register.
+#[inline]
+#[must_use]
+pub fn fp_simd_enabled() -> bool {
+    read_cpacr_el1().fp_simd_enabled()
+}
+
+/// This enum represents the implementer of an ARM processor, which is encoded in the highest byte
+/// of the Main ID Register (MIDR_EL1). This is the ARM equivalent of the x86 CPU vendor.
+///
+/// # See also
+/// - [MIDR_EL1](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/MIDR-EL1--Main-ID-Register)
+/// by [Arm Limited](https://www.arm.com)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum ArmImplementer {
+    Arm,
+    Broadcom,
+    Cavium,
+    Fujitsu,
+    Nvidia,
+    Qualcomm,
+    Samsung,
+    Apple,
+    Ampere,
+    Unknown(u8),
+}
+
+impl From<u8> for ArmImplementer {
+    #[must_use]
+    fn from(value: u8) -> Self {
+        match value {
+            0x41 => Self::Arm,
+            0x42 => Self::Broadcom,
+            0x43 => Self::Cavium,
+            0x46 => Self::Fujitsu,
+            0x4E => Self::Nvidia,
+            0x51 => Self::Qualcomm,
+            0x53 => Self::Samsung,
+            0x61 => Self::Apple,
+            0xC0 => Self::Ampere,
+            value => Self::Unknown(value),
+        }
+    }
+}
+
+impl Display for ArmImplementer {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Arm => write!(formatter, "ARM Limited"),
+            Self::Broadcom => write!(formatter, "Broadcom Corporation"),
+            Self::Cavium => write!(formatter, "Cavium Inc."),
+            Self::Fujitsu => write!(formatter, "Fujitsu Ltd."),
+            Self::Nvidia => write!(formatter, "NVIDIA Corporation"),
+            Self::Qualcomm => write!(formatter, "Qualcomm Inc."),
+            Self::Samsung => write!(formatter, "Samsung Electronics Co., Ltd."),
+            Self::Apple => write!(formatter, "Apple Inc."),
+            Self::Ampere => write!(formatter, "Ampere Computing"),
+            Self::Unknown(value) => write!(formatter, "Unknown Implementer (0x{:x})", value),
+        }
+    }
+}
+
+/// This structure represents the value of the Main ID Register (MIDR_EL1), which identifies the
+/// processor.
+///
+/// The following structure shows the layout of the register:
+/// - Bit 0 to 3 - Revision
+/// - Bit 4 to 15 - Part number
+/// - Bit 16 to 19 - Architecture
+/// - Bit 20 to 23 - Variant
+/// - Bit 24 to 31 - Implementer
+///
+/// ```rust
+/// use libcpu::{ArmImplementer, MainIdRegister};
+///
+/// // Cortex-A72 r0p3
+/// let midr = MainIdRegister(0x410F_D083);
+/// assert_eq!(midr.implementer(), ArmImplementer::Arm);
+/// assert_eq!(midr.part_number(), 0xD08);
+/// assert_eq!(midr.revision(), 3);
+///
+/// // Apple M1 (Firestorm)
+/// let midr = MainIdRegister(0x611F_0231);
+/// assert_eq!(midr.implementer(), ArmImplementer::Apple);
+/// assert_eq!(midr.part_number(), 0x023);
+/// assert_eq!(midr.variant(), 1);
+/// ```
+///
+/// # See also
+/// - [MIDR_EL1](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/MIDR-EL1--Main-ID-Register)
+/// by [Arm Limited](https://www.arm.com)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+#[repr(transparent)]
+pub struct MainIdRegister(pub u64);
+
+impl MainIdRegister {
+    /// This function returns the implementer of the processor.
+    #[inline]
+    #[must_use]
+    pub fn implementer(&self) -> ArmImplementer {
+        ArmImplementer::from(self.0.get_bits(24..32) as u8)
+    }
+
+    /// This function returns the implementer-specific variant (the major revision) of the
+    /// processor.
+    #[inline]
+    #[must_use]
+    pub fn variant(&self) -> u8 {
+        self.0.get_bits(20..24) as u8
+    }
+
+    /// This function returns the architecture code of the processor. The value `0xF` indicates,
+    /// that the architecture is defined by the ID registers.
+    #[inline]
+    #[must_use]
+    pub fn architecture(&self) -> u8 {
+        self.0.get_bits(16..20) as u8
+    }
+
+    /// This function returns the implementer-specific part number of the processor.
+    #[inline]
+    #[must_use]
+    pub fn part_number(&self) -> u16 {
+        self.0.get_bits(4..16) as u16
+    }
+
+    /// This function returns the implementer-specific revision (the minor revision) of the
+    /// processor.
+    #[inline]
+    #[must_use]
+    pub fn revision(&self) -> u8 {
+        self.0.get_bits(0..4) as u8
+    }
+}
+
+/// This function reads the value of the `midr_el1` register.
+#[inline]
+#[must_use]
+pub fn read_midr_el1() -> MainIdRegister {
+    let value: u64;
+    unsafe {
+        asm!("mrs {}, midr_el1", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+    MainIdRegister(value)
+}
+
+/// This function returns the implementer of the current processor from the MIDR_EL1 register. The
+/// part number is available over [read_midr_el1].
+#[inline]
+#[must_use]
+pub fn arm_implementer() -> ArmImplementer {
+    read_midr_el1().implementer()
+}
+
+/// This function returns a hint for the count of logical processors, which is inferred from the
+/// MPIDR_EL1 register. The affinity fields of the register are only identifying the current
+/// processor, so the count is only known if the uniprocessor bit (bit 30) is set. Otherwise, this
+/// function returns [None] and the count must be read from the firmware (like the device tree or
+/// the ACPI MADT), which is also the authoritative source on multiprocessor systems.
+///
+/// # See also
+/// - [MPIDR_EL1](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/MPIDR-EL1--Multiprocessor-Affinity-Register)
+/// by [Arm Limited](https://www.arm.com)
+#[must_use]
+pub fn logical_cpu_count_hint() -> Option<u32> {
+    let value: u64;
     unsafe {
-        asm!("wfi");
+        asm!("mrs {}, mpidr_el1", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+
+    if value.get_bit(30) {
+        Some(1)
+    } else {
+        None
     }
 }
+
+/// This function is the cross-architecture alias of [wait_for_interrupt].
+#[inline]
+pub fn wait_for_interrupts() {
+    wait_for_interrupt();
+}
@@ -0,0 +1,81 @@
+use crate::{read_ttbr0_el1, read_ttbr1_el1, TranslationTableBase};
+use core::arch::asm;
+use core::fmt::{Display, Formatter};
+
+/// This structure represents a snapshot of the system control register, the translation table base
+/// registers, the interrupt masks and the current exception level. The snapshot is formatted as a
+/// readable dump with [Display], so it can be printed in a panic or exception handler. The capture
+/// only reads registers and doesn't allocate.
+///
+/// ```rust,no_run
+/// use libcpu::CpuState;
+///
+/// let state = CpuState::capture();
+/// assert_eq!(state.exception_level, 1);
+/// ```
+///
+/// # See also
+/// - [SCTLR_EL1](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/SCTLR-EL1--System-Control-Register--EL1-)
+/// by [Arm Limited](https://www.arm.com)
+/// - [DAIF](https://developer.arm.com/documentation/ddi0595/2021-12/AArch64-Registers/DAIF--Interrupt-Mask-Bits)
+/// by [Arm Limited](https://www.arm.com)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct CpuState {
+    /// The raw value of the SCTLR_EL1 register
+    pub sctlr: u64,
+
+    /// The value of the TTBR0_EL1 register (the translation table of the lower address range)
+    pub ttbr0: TranslationTableBase,
+
+    /// The value of the TTBR1_EL1 register (the translation table of the upper address range)
+    pub ttbr1: TranslationTableBase,
+
+    /// The raw value of the DAIF register (the masks are stored in the bits 6 to 9)
+    pub daif: u64,
+
+    /// The current exception level (0 to 3)
+    pub exception_level: u8,
+}
+
+impl CpuState {
+    /// This function captures the current values of the system control register, the translation
+    /// table base registers, the interrupt masks and the current exception level.
+    #[must_use]
+    pub fn capture() -> Self {
+        let (sctlr, daif, current_el): (u64, u64, u64);
+        unsafe {
+            asm!("mrs {}, sctlr_el1", out(reg) sctlr, options(nomem, nostack, preserves_flags));
+            asm!("mrs {}, daif", out(reg) daif, options(nomem, nostack, preserves_flags));
+            asm!(
+                "mrs {}, CurrentEL",
+                out(reg) current_el,
+                options(nomem, nostack, preserves_flags)
+            );
+        }
+
+        Self {
+            sctlr,
+            ttbr0: read_ttbr0_el1(),
+            ttbr1: read_ttbr1_el1(),
+            daif,
+            exception_level: ((current_el >> 2) & 0b11) as u8,
+        }
+    }
+}
+
+impl Display for CpuState {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        const MASKS: [(u64, &str); 4] =
+            [(1 << 9, "D"), (1 << 8, "A"), (1 << 7, "I"), (1 << 6, "F")];
+
+        writeln!(formatter, "SCTLR_EL1: {:#018x}", self.sctlr)?;
+        writeln!(formatter, "TTBR0_EL1: {:#018x}", self.ttbr0.0)?;
+        writeln!(formatter, "TTBR1_EL1: {:#018x}", self.ttbr1.0)?;
+        write!(formatter, "DAIF:      {:#018x} (", self.daif)?;
+        for (mask, name) in MASKS {
+            write!(formatter, "{}", if self.daif & mask != 0 { name } else { "-" })?;
+        }
+        writeln!(formatter, ")")?;
+        write!(formatter, "EL:        {}", self.exception_level)
+    }
+}
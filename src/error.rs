@@ -0,0 +1,108 @@
+use crate::CPUFeature;
+use core::fmt::{Display, Formatter};
+
+/// This enum represents all errors, that can be returned by the functions of this library. The
+/// functions are returning these errors instead of executing an instruction, that would lead into
+/// an exception (like a General Protection Fault).
+///
+/// Here is a list of all errors with description:
+/// - [CpuError::UnsupportedFeature] - The operation requires a CPU feature, that is not supported
+/// by the processor of the current system.
+/// - [CpuError::InvalidPrivilegeLevel] - The value is not a valid privilege level (0 to 3).
+/// - [CpuError::InvalidSelector] - The segment selector doesn't reference a valid descriptor.
+/// - [CpuError::InvalidTssIndex] - The index doesn't reference a valid TSS descriptor.
+/// - [CpuError::NotInLongMode] - The operation is only available in the 64-bit long mode.
+/// - [CpuError::UnknownTscFrequency] - The frequency of the Time Stamp Counter isn't reported by
+/// the processor.
+/// - [CpuError::InvalidPcid] - The value is not a valid Process Context Identifier (0 to 4095).
+/// - [CpuError::ReservedBitsSet] - The value sets reserved bits of a register.
+/// - [CpuError::InvalidControlRegister] - The number doesn't identify a control register.
+///
+/// ```rust
+/// use libcpu::{CpuError, CPUFeature};
+///
+/// let messages = [
+///     CpuError::UnsupportedFeature(CPUFeature::SSE3).to_string(),
+///     CpuError::InvalidPrivilegeLevel(4).to_string(),
+///     CpuError::InvalidSelector(0x1B).to_string(),
+///     CpuError::InvalidTssIndex(2).to_string(),
+///     CpuError::NotInLongMode.to_string(),
+///     CpuError::UnknownTscFrequency.to_string(),
+///     CpuError::InvalidPcid(4096).to_string(),
+///     CpuError::ReservedBitsSet(1 << 15).to_string(),
+///     CpuError::InvalidControlRegister(5).to_string(),
+/// ];
+///
+/// for (index, message) in messages.iter().enumerate() {
+///     assert!(!message.is_empty());
+///     assert!(!messages[index + 1..].contains(message));
+/// }
+/// ```
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum CpuError {
+    /// The operation requires a CPU feature, that is not supported by the processor of the current
+    /// system. The value is the feature that is missing.
+    UnsupportedFeature(CPUFeature),
+
+    /// The value is not a valid privilege level. Only the privilege levels 0 to 3 are valid.
+    InvalidPrivilegeLevel(u8),
+
+    /// The segment selector doesn't reference a valid descriptor in the descriptor table.
+    InvalidSelector(u16),
+
+    /// The index doesn't reference a valid Task State Segment descriptor.
+    InvalidTssIndex(usize),
+
+    /// The operation is only available when the processor is running in the 64-bit long mode.
+    NotInLongMode,
+
+    /// The frequency of the Time Stamp Counter isn't reported by the processor (CPUID leaf 0x15
+    /// and 0x16), so the counter can't be converted into a time.
+    UnknownTscFrequency,
+
+    /// The value is not a valid Process Context Identifier. The PCID is stored in the low 12 bits
+    /// of the CR3 register, so only the values 0 to 4095 are valid.
+    InvalidPcid(u16),
+
+    /// The value sets bits of a register, that are reserved. Writing these bits leads into a
+    /// General Protection Fault. The value contains the reserved bits, that are set.
+    ReservedBitsSet(u64),
+
+    /// The number doesn't identify a control register, that can be accessed with the `mov`
+    /// instruction. The value is the number of the control register.
+    InvalidControlRegister(u8),
+}
+
+impl Display for CpuError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedFeature(feature) => {
+                write!(formatter, "The CPU feature '{}' is not supported by the processor", feature)
+            }
+            Self::InvalidPrivilegeLevel(level) => {
+                write!(formatter, "Invalid privilege level {} (expected 0 to 3)", level)
+            }
+            Self::InvalidSelector(selector) => {
+                write!(formatter, "Invalid segment selector 0x{:x}", selector)
+            }
+            Self::InvalidTssIndex(index) => write!(formatter, "Invalid TSS index {}", index),
+            Self::NotInLongMode => {
+                write!(formatter, "The processor is not running in the long mode")
+            }
+            Self::UnknownTscFrequency => {
+                write!(formatter, "The frequency of the Time Stamp Counter is unknown")
+            }
+            Self::InvalidPcid(pcid) => {
+                write!(formatter, "Invalid PCID {} (expected 0 to 4095)", pcid)
+            }
+            Self::ReservedBitsSet(bits) => {
+                write!(formatter, "The reserved bits 0x{:x} must not be set", bits)
+            }
+            Self::InvalidControlRegister(register) => {
+                write!(formatter, "Invalid control register CR{}", register)
+            }
+        }
+    }
+}
+
+impl core::error::Error for CpuError {}
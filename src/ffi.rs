@@ -0,0 +1,81 @@
+//! This module exports C wrappers of the key primitives, so they can be called from the C part of
+//! a mixed C/Rust kernel. It's only available with the `ffi` feature. Every wrapper is exported
+//! with `#[no_mangle]` and the name of the wrapped Rust function with a `libcpu_` prefix, so the
+//! functions are declared in C like this:
+//!
+//! ```c
+//! #include <stdint.h>
+//!
+//! void libcpu_halt_cpu(void) __attribute__((noreturn));
+//! uint64_t libcpu_read_msr(uint32_t msr);
+//! void libcpu_write_msr(uint32_t msr, uint64_t value);
+//! ```
+//!
+//! The Rust API is unchanged by this feature. The exported names are checked by linking against
+//! the symbols:
+//!
+//! ```rust
+//! extern crate libcpu;
+//!
+//! extern "C" {
+//!     fn libcpu_halt_cpu() -> !;
+//!     fn libcpu_wait_for_interrupts();
+//!     fn libcpu_memory_fence();
+//!     #[cfg(target_arch = "x86_64")]
+//!     fn libcpu_read_msr(msr: u32) -> u64;
+//!     #[cfg(target_arch = "x86_64")]
+//!     fn libcpu_write_msr(msr: u32, value: u64);
+//!     #[cfg(target_arch = "x86_64")]
+//!     fn libcpu_read_tsc() -> u64;
+//! }
+//!
+//! let _: [unsafe extern "C" fn(); 2] = [libcpu_wait_for_interrupts, libcpu_memory_fence];
+//! let _: unsafe extern "C" fn() -> ! = libcpu_halt_cpu;
+//!
+//! #[cfg(target_arch = "x86_64")]
+//! {
+//!     let _: unsafe extern "C" fn(u32) -> u64 = libcpu_read_msr;
+//!     let _: unsafe extern "C" fn(u32, u64) = libcpu_write_msr;
+//!     let first = unsafe { libcpu_read_tsc() };
+//!     assert!(unsafe { libcpu_read_tsc() } > first);
+//! }
+//! ```
+
+/// This function is the C wrapper of [crate::halt_cpu].
+#[no_mangle]
+pub extern "C" fn libcpu_halt_cpu() -> ! {
+    crate::halt_cpu()
+}
+
+/// This function is the C wrapper of [crate::wait_for_interrupts].
+#[no_mangle]
+pub extern "C" fn libcpu_wait_for_interrupts() {
+    crate::wait_for_interrupts();
+}
+
+/// This function is the C wrapper of [crate::memory_fence].
+#[no_mangle]
+pub extern "C" fn libcpu_memory_fence() {
+    crate::memory_fence();
+}
+
+/// This function is the C wrapper of [crate::msr::read_msr].
+#[no_mangle]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub extern "C" fn libcpu_read_msr(msr: u32) -> u64 {
+    crate::msr::read_msr(msr)
+}
+
+/// This function is the C wrapper of [crate::msr::write_msr].
+#[no_mangle]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub extern "C" fn libcpu_write_msr(msr: u32, value: u64) {
+    crate::msr::write_msr(msr, value);
+}
+
+/// This function is the C wrapper of [crate::tsc::read_tsc].
+#[no_mangle]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub extern "C" fn libcpu_read_tsc() -> u64 {
+    crate::tsc::read_tsc()
+}
@@ -1,6 +1,10 @@
 #![feature(abi_x86_interrupt)]
+#![feature(error_in_core)]
+#![feature(naked_functions)]
+#![feature(offset_of)]
 #![no_std]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
 // Register
@@ -16,6 +20,26 @@ pub type Register = u64;
 #[cfg(target_pointer_width = "64")]
 pub type MemoryAddress = u64;
 
+// The registers are passed to the inline assembly with the `reg` class, which selects the register
+// name (e.g. `eax` or `rax`) by the width of the value, so the width must match the general-purpose
+// registers of the target.
+const _: () = assert!(core::mem::size_of::<Register>() == core::mem::size_of::<usize>());
+
+// Errors
+pub(crate) mod error;
+
+pub use error::*;
+
+// Register helpers
+pub(crate) mod register;
+
+pub use register::*;
+
+// Synchronization primitives
+pub(crate) mod sync;
+
+pub use sync::{SpinLock, SpinLockGuard};
+
 // x86 and x86_64 API
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub(crate) mod x86;
@@ -30,8 +54,35 @@ pub(crate) mod arm;
 #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
 pub use arm::*;
 
+// C wrappers for mixed C/Rust kernels
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 pub fn halt_cpu() -> ! {
     loop {
         wait_for_interrupts();
     }
 }
+
+/// This function executes the spin loop hint (`pause` on x86, `yield` on ARM) the specified count
+/// of times, so the processor is delayed by roughly `n` hints. This is a crude delay for the
+/// earliest boot stages, in which no timer is calibrated yet (like a minimum settling time after a
+/// register write).
+///
+/// The delay is imprecise by design: The duration of a single hint differs between the
+/// microarchitectures (a few to more than 100 cycles for `pause`) and the processor can be
+/// interrupted while waiting. So `n` is only a lower bound for the count of hints and not a count
+/// of cycles. Use `tsc::spin_delay_ns` on x86, if the Time Stamp Counter is available.
+///
+/// ```rust
+/// use libcpu::spin_cycles;
+///
+/// spin_cycles(0);
+/// spin_cycles(10_000);
+/// ```
+#[inline]
+pub fn spin_cycles(n: u64) {
+    for _ in 0..n {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,71 @@
+use bit_field::BitField;
+use core::ops::Range;
+
+/// This trait implements convenience functions for raw register values and memory addresses, so
+/// the code manipulating these values doesn't depend on `bit_field` directly. It's implemented for
+/// the 32-bit and 64-bit types, so [crate::Register] and [crate::MemoryAddress] are providing the
+/// same API on both widths.
+///
+/// ```rust
+/// use libcpu::RegisterExt;
+///
+/// let mut value: u64 = 0b1010;
+/// assert!(value.bit(1));
+/// value.set_bit(0, true);
+/// assert_eq!(value.bits(0..4), 0b1011);
+/// assert_eq!(0x1001_u64.align_up(0x1000), 0x2000);
+///
+/// let mut value: u32 = 0;
+/// value.set_bit(31, true);
+/// assert!(value.bit(31));
+/// assert_eq!(value.bits(28..32), 0b1000);
+/// assert_eq!(0x1000_u32.align_up(0x1000), 0x1000);
+/// ```
+pub trait RegisterExt: Sized {
+    /// This function returns whether the bit at the specified index is set.
+    #[must_use]
+    fn bit(self, index: usize) -> bool;
+
+    /// This function sets or clears the bit at the specified index.
+    fn set_bit(&mut self, index: usize, value: bool);
+
+    /// This function returns the bits in the specified range, shifted to the lowest bit.
+    #[must_use]
+    fn bits(self, range: Range<usize>) -> Self;
+
+    /// This function rounds the value up to the next multiple of the specified alignment. The
+    /// alignment must be a power of two.
+    #[must_use]
+    fn align_up(self, alignment: Self) -> Self;
+}
+
+macro_rules! register_ext {
+    ($($ty: ty),*) => {
+        $(
+        impl RegisterExt for $ty {
+            #[inline]
+            fn bit(self, index: usize) -> bool {
+                BitField::get_bit(&self, index)
+            }
+
+            #[inline]
+            fn set_bit(&mut self, index: usize, value: bool) {
+                BitField::set_bit(self, index, value);
+            }
+
+            #[inline]
+            fn bits(self, range: Range<usize>) -> Self {
+                BitField::get_bits(&self, range)
+            }
+
+            #[inline]
+            fn align_up(self, alignment: Self) -> Self {
+                assert!(alignment.is_power_of_two(), "The alignment must be a power of two");
+                (self + alignment - 1) & !(alignment - 1)
+            }
+        }
+        )*
+    };
+}
+
+register_ext!(u32, u64);
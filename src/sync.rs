@@ -0,0 +1,159 @@
+use core::{
+    cell::UnsafeCell,
+    fmt::{Debug, Formatter},
+    hint::spin_loop,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// This structure represents a minimal spinlock, that protects the value with an atomic flag. The
+/// lock is acquired with a compare-exchange with the acquire ordering and released with a store
+/// with the release ordering, so all accesses to the value in the critical section are visible to
+/// the next owner of the lock. While the lock is held by another core, the waiting core only reads
+/// the flag and executes [spin_loop] (`pause` on x86, `yield` on ARM), so the cache line isn't
+/// bouncing between the cores.
+///
+/// The lock doesn't disable the interrupts. If the lock is also acquired by an interrupt handler,
+/// the interrupts must be disabled while the lock is held, otherwise the handler deadlocks when it
+/// interrupts the owner of the lock on the same core.
+///
+/// ```rust
+/// use libcpu::SpinLock;
+///
+/// let lock = SpinLock::new(0);
+/// {
+///     let mut value = lock.lock();
+///     *value += 1;
+///     assert!(lock.is_locked());
+///     assert!(lock.try_lock().is_none());
+/// }
+///
+/// assert!(!lock.is_locked());
+/// assert_eq!(*lock.try_lock().unwrap(), 1);
+/// assert_eq!(lock.into_inner(), 1);
+/// ```
+///
+/// The lock serializes the increments of multiple threads:
+/// ```rust
+/// use libcpu::SpinLock;
+///
+/// static COUNTER: SpinLock<u64> = SpinLock::new(0);
+///
+/// let threads: Vec<_> = (0..4)
+///     .map(|_| std::thread::spawn(|| (0..1000).for_each(|_| *COUNTER.lock() += 1)))
+///     .collect();
+/// threads.into_iter().for_each(|thread| thread.join().unwrap());
+/// assert_eq!(*COUNTER.lock(), 4000);
+/// ```
+///
+/// # See also
+/// - [Spinlock](https://wiki.osdev.org/Spinlock) by [OSDev.org](https://wiki.osdev.org/)
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// The value is only accessed by the owner of the lock, so the lock can be shared between the cores
+// if the value can be sent to another core.
+unsafe impl<T: Send> Send for SpinLock<T> {}
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    /// This function creates a new unlocked spinlock, that protects the specified value.
+    #[inline]
+    #[must_use]
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// This function acquires the lock and returns a guard, that releases the lock when it's
+    /// dropped. If the lock is held by another core, this function spins until the lock is
+    /// released.
+    #[inline]
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+
+            while self.is_locked() {
+                spin_loop();
+            }
+        }
+    }
+
+    /// This function tries to acquire the lock without spinning. If the lock is held by another
+    /// core, this function returns [None].
+    #[inline]
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| SpinLockGuard { lock: self })
+    }
+
+    /// This function returns whether the lock is currently held. The result is only a snapshot,
+    /// the lock can be acquired or released by another core right after the check.
+    #[inline]
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// This function returns a mutable reference to the value. No locking is needed, because the
+    /// mutable borrow guarantees that no guard exists.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// This function consumes the lock and returns the protected value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Default> Default for SpinLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Debug> Debug for SpinLock<T> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.try_lock() {
+            Some(guard) => formatter.debug_struct("SpinLock").field("value", &*guard).finish(),
+            None => formatter.debug_struct("SpinLock").field("value", &"<locked>").finish(),
+        }
+    }
+}
+
+/// This structure represents the ownership of a [SpinLock]. The value is accessed over the guard
+/// and the lock is released, when the guard is dropped.
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
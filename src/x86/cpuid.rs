@@ -1,47 +1,377 @@
+use crate::x86::leaves;
+use bit_field::BitField;
+use core::arch::x86_64::CpuidResult;
+#[cfg(not(feature = "mock"))]
 use core::arch::x86_64::{
-    CpuidResult,
     __cpuid,
     __cpuid_count,
 };
 
+/// This function returns whether the processor supports the `cpuid` instruction. The support is
+/// detected by toggling the ID flag (bit 21) in the EFLAGS register. If the flag can be toggled,
+/// the instruction is supported. Every x86_64 processor supports the instruction, so this function
+/// always returns true on x86_64.
+///
+/// ```rust
+/// use libcpu::cpuid_available;
+///
+/// #[cfg(target_arch = "x86_64")]
+/// assert!(cpuid_available());
+/// ```
+///
+/// # See also
+/// - [CPUID](https://wiki.osdev.org/CPUID#Checking_CPUID_availability) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+#[cfg(target_arch = "x86_64")]
+pub fn cpuid_available() -> bool {
+    true
+}
+
+/// This function returns whether the processor supports the `cpuid` instruction. The support is
+/// detected by toggling the ID flag (bit 21) in the EFLAGS register. If the flag can be toggled,
+/// the instruction is supported. Some old or emulated 32-bit processors don't support it.
+///
+/// # See also
+/// - [CPUID](https://wiki.osdev.org/CPUID#Checking_CPUID_availability) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[must_use]
+#[cfg(target_arch = "x86")]
+pub fn cpuid_available() -> bool {
+    let (original, toggled): (u32, u32);
+    unsafe {
+        core::arch::asm!(
+            "pushfd",
+            "pop {original:e}",
+            "mov {toggled:e}, {original:e}",
+            "xor {toggled:e}, 0x200000",
+            "push {toggled:e}",
+            "popfd",
+            "pushfd",
+            "pop {toggled:e}",
+            "push {original:e}",
+            "popfd",
+            original = out(reg) original,
+            toggled = out(reg) toggled,
+        );
+    }
+    ((original ^ toggled) & (1 << 21)) != 0
+}
+
+/// This function returns the highest basic leaf, that is supported by the processor (the eax
+/// register of CPUID leaf 0x0). Leaves above this value are returning the data of the highest
+/// basic leaf on Intel processors, so the helpers are checking this value before they request a
+/// basic leaf.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{max_basic_leaf, mock::register_file};
+
+// Captured on an Intel Core i7-8700 processor
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0x16, ebx: 0x756E_6547, ecx: 0x6C65_746E, edx: 0x4965_6E69 });
+assert_eq!(max_basic_leaf(), 0x16);
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn max_basic_leaf() -> u32 {
+    CPUIDRequest::Vendor.cpuid().eax
+}
+
+/// This function returns the highest extended leaf, that is supported by the processor (the eax
+/// register of CPUID leaf 0x8000_0000). The extended leaves (like the memory encryption leaf of
+/// AMD processors) are only valid if they are not above this value.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{leaves, max_extended_leaf, mock::register_file};
+
+// Captured on an AMD EPYC 9004 series processor
+register_file().set_cpuid(0x8000_0000, 0, CpuidResult { eax: 0x8000_0028, ebx: 0, ecx: 0, edx: 0 });
+assert_eq!(max_extended_leaf(), 0x8000_0028);
+assert!(max_extended_leaf() >= leaves::MEMORY_ENCRYPTION);
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn max_extended_leaf() -> u32 {
+    CPUIDRequest::ExtendedBase.cpuid().eax
+}
+
+/// This structure represents the processor signature, that is reported in the eax register of
+/// CPUID leaf 0x1. The family and the model are split into a base and an extended field, which are
+/// combined by [CpuSignature::family] and [CpuSignature::model]. The signature identifies the
+/// microarchitecture of the processor, so it's used to apply microarchitecture-specific errata.
+///
+/// ```rust
+/// use libcpu::CpuSignature;
+///
+/// // Intel Core i7-6700K (Skylake)
+/// let signature = CpuSignature(0x0005_06E3);
+/// assert_eq!(signature.family(), 0x6);
+/// assert_eq!(signature.model(), 0x5E);
+/// assert_eq!(signature.stepping(), 3);
+///
+/// // AMD Ryzen 7 3700X (Zen 2)
+/// let signature = CpuSignature(0x0087_0F10);
+/// assert_eq!(signature.base_family(), 0xF);
+/// assert_eq!(signature.extended_family(), 0x8);
+/// assert_eq!(signature.family(), 0x17);
+/// assert_eq!(signature.model(), 0x71);
+/// assert_eq!(signature.stepping(), 0);
+/// ```
+///
+/// # See also
+/// - [CPUID](https://en.wikipedia.org/wiki/CPUID#EAX=1:_Processor_Info_and_Feature_Bits) by
+/// [Wikipedia](https://wikipedia.org)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+#[repr(transparent)]
+pub struct CpuSignature(pub u32);
+
+impl CpuSignature {
+    /// This function returns the stepping (the revision) of the processor.
+    #[inline]
+    #[must_use]
+    pub fn stepping(&self) -> u8 {
+        self.0.get_bits(0..4) as u8
+    }
+
+    /// This function returns the base model of the processor without the extended model.
+    #[inline]
+    #[must_use]
+    pub fn base_model(&self) -> u8 {
+        self.0.get_bits(4..8) as u8
+    }
+
+    /// This function returns the base family of the processor without the extended family.
+    #[inline]
+    #[must_use]
+    pub fn base_family(&self) -> u8 {
+        self.0.get_bits(8..12) as u8
+    }
+
+    /// This function returns the extended model of the processor, which is only used for the
+    /// families 0x6 and 0xF.
+    #[inline]
+    #[must_use]
+    pub fn extended_model(&self) -> u8 {
+        self.0.get_bits(16..20) as u8
+    }
+
+    /// This function returns the extended family of the processor, which is only used for the
+    /// family 0xF.
+    #[inline]
+    #[must_use]
+    pub fn extended_family(&self) -> u8 {
+        self.0.get_bits(20..28) as u8
+    }
+
+    /// This function returns the family of the processor. If the base family is 0xF, the extended
+    /// family is added to the base family.
+    #[inline]
+    #[must_use]
+    pub fn family(&self) -> u16 {
+        match self.base_family() {
+            0xF => 0xF + u16::from(self.extended_family()),
+            family => u16::from(family),
+        }
+    }
+
+    /// This function returns the model of the processor. If the base family is 0x6 or 0xF, the
+    /// extended model is used as the upper nibble of the model.
+    #[inline]
+    #[must_use]
+    pub fn model(&self) -> u8 {
+        match self.base_family() {
+            0x6 | 0xF => (self.extended_model() << 4) | self.base_model(),
+            _ => self.base_model(),
+        }
+    }
+}
+
+/// This function returns the processor signature, that is reported by CPUID leaf 0x1.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{cpu_signature, mock::register_file};
+
+// Captured on an Intel Core i7-8700 processor (Coffee Lake)
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0x906EA, ebx: 0x0C10_0800, ecx: 0x7FFA_FBBF, edx: 0xBFEB_FBFF });
+let signature = cpu_signature();
+assert_eq!((signature.family(), signature.model(), signature.stepping()), (0x6, 0x9E, 0xA));
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn cpu_signature() -> CpuSignature {
+    CpuSignature(CPUIDRequest::Features.cpuid().eax)
+}
+
+/// This function returns the legacy descriptor bytes of the TLBs, caches and the prefetching, that
+/// are reported by CPUID leaf 0x2. The meaning of the bytes is defined by the table in the
+/// documentation of the `cpuid` instruction, so the bytes are returned without decoding. The low
+/// byte of the eax register (always 0x01), the registers with the bit 31 set (no valid
+/// descriptors) and the null descriptors are skipped.
+///
+/// The descriptor 0xFF indicates, that the caches must be read from the structured CPUID leaf 0x4.
+/// If the processor doesn't report leaf 0x2, no descriptor is returned.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{legacy_cache_descriptors, mock::register_file};
+
+// The result of an Intel Sandy Bridge processor
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0xD, ebx: 0x756E_6547, ecx: 0x6C65_746E, edx: 0x4965_6E69 });
+register_file().set_cpuid(2, 0, CpuidResult { eax: 0x7603_5A01, ebx: 0x00F0_B2FF, ecx: 0, edx: 0x00CA_0000 });
+assert!(legacy_cache_descriptors().eq([0x5A, 0x03, 0x76, 0xFF, 0xB2, 0xF0, 0xCA]));
+
+register_file().set_cpuid(2, 0, CpuidResult { eax: 0x0000_5A01, ebx: 0x80F0_B2FF, ecx: 0, edx: 0 });
+assert!(legacy_cache_descriptors().eq([0x5A]));
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn legacy_cache_descriptors() -> impl Iterator<Item = u8> {
+    let registers = if max_basic_leaf() >= leaves::CACHE_DESCRIPTORS {
+        let result = CPUIDRequest::CacheDescriptors.cpuid();
+        [result.eax & !0xFF, result.ebx, result.ecx, result.edx]
+    } else {
+        [0; 4]
+    };
+
+    registers
+        .into_iter()
+        .filter(|register| !register.get_bit(31))
+        .flat_map(u32::to_le_bytes)
+        .filter(|descriptor| *descriptor != 0)
+}
+
+/// This enum lists the CPUID requests (the leaf and sub leaf), that are executed by this library.
+/// Every [crate::CPUFeature] is read from the result of one of these requests.
+///
+/// ```rust
+/// use libcpu::{leaves, CPUIDRequest};
+///
+/// assert_eq!(CPUIDRequest::ExtendedFeatures1.leaf(), leaves::EXTENDED_FEATURES);
+/// assert_eq!(CPUIDRequest::ExtendedFeatures1.sub_leaf(), Some(0));
+/// ```
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+/// - [leaves] (Source Code)
 #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
 pub enum CPUIDRequest {
+    /// The highest basic leaf and the vendor string (leaf 0x0)
     Vendor,
+
+    /// The processor signature and the basic feature flags (leaf 0x1)
     Features,
+
+    /// The legacy descriptors of the TLBs, caches and the prefetching (leaf 0x2)
+    CacheDescriptors,
+
+    /// The structured extended feature flags (leaf 0x7, sub leaf 0)
     ExtendedFeatures1,
+
+    /// The structured extended feature flags (leaf 0x7, sub leaf 1)
     ExtendedFeatures2,
+
+    /// The structured extended feature flags (leaf 0x7, sub leaf 2)
     ExtendedFeatures3,
+
+    /// The extended processor info and feature flags (leaf 0x8000_0001)
     ExtendedFeatures4,
+
+    /// The supported state components and the size of the XSAVE area (leaf 0xD, sub leaf 0)
+    ExtendedState,
+
+    /// The ratio of the Time Stamp Counter to the crystal clock (leaf 0x15)
+    TimeStampCounter,
+
+    /// The base, maximum and bus frequency of the processor (leaf 0x16)
+    ProcessorFrequency,
+
+    /// The highest extended leaf (leaf 0x8000_0000)
+    ExtendedBase,
+
+    /// The memory encryption capabilities of AMD processors (leaf 0x8000_001F)
+    MemoryEncryption,
 }
 
 impl CPUIDRequest {
-    pub(crate) fn cpuid(&self) -> CpuidResult {
-        let leaf = self.leaf();
-        unsafe {
-            match self.sub_leaf() {
-                None => __cpuid(leaf),
-                Some(sub_leaf) => __cpuid_count(leaf, sub_leaf),
-            }
-        }
+    /// This function executes the `cpuid` instruction with the leaf and sub leaf of this request
+    /// and returns the result.
+    #[must_use]
+    pub fn cpuid(&self) -> CpuidResult {
+        cpuid(self.leaf(), self.sub_leaf())
     }
 
-    fn leaf(&self) -> u32 {
+    /// This function returns the leaf (the value of the eax register), that is requested.
+    #[inline]
+    #[must_use]
+    pub const fn leaf(&self) -> u32 {
         match self {
-            CPUIDRequest::Vendor => 0,
-            CPUIDRequest::Features => 1,
-            CPUIDRequest::ExtendedFeatures1 => 7,
-            CPUIDRequest::ExtendedFeatures2 => 7,
-            CPUIDRequest::ExtendedFeatures3 => 7,
-            CPUIDRequest::ExtendedFeatures4 => 0x80000001,
+            CPUIDRequest::Vendor => leaves::VENDOR,
+            CPUIDRequest::Features => leaves::BASIC_FEATURES,
+            CPUIDRequest::CacheDescriptors => leaves::CACHE_DESCRIPTORS,
+            CPUIDRequest::ExtendedFeatures1 => leaves::EXTENDED_FEATURES,
+            CPUIDRequest::ExtendedFeatures2 => leaves::EXTENDED_FEATURES,
+            CPUIDRequest::ExtendedFeatures3 => leaves::EXTENDED_FEATURES,
+            CPUIDRequest::ExtendedFeatures4 => leaves::EXTENDED_PROCESSOR_INFO,
+            CPUIDRequest::ExtendedState => leaves::EXTENDED_STATE,
+            CPUIDRequest::TimeStampCounter => leaves::TIME_STAMP_COUNTER,
+            CPUIDRequest::ProcessorFrequency => leaves::PROCESSOR_FREQUENCY,
+            CPUIDRequest::ExtendedBase => leaves::EXTENDED_BASE,
+            CPUIDRequest::MemoryEncryption => leaves::MEMORY_ENCRYPTION,
         }
     }
 
-    fn sub_leaf(&self) -> Option<u32> {
+    /// This function returns the sub leaf (the value of the ecx register), that is requested. If
+    /// the leaf has no sub leaves, this function returns [None].
+    #[inline]
+    #[must_use]
+    pub const fn sub_leaf(&self) -> Option<u32> {
         match self {
             CPUIDRequest::ExtendedFeatures1 => Some(0),
             CPUIDRequest::ExtendedFeatures2 => Some(1),
             CPUIDRequest::ExtendedFeatures3 => Some(2),
+            CPUIDRequest::ExtendedState => Some(0),
             _ => None,
         }
     }
 }
+
+// The requests must stay in sync with the leaf numbers, which are used by the helpers directly
+const _: () = assert!(CPUIDRequest::Features.leaf() == leaves::BASIC_FEATURES);
+
+// This function executes the `cpuid` instruction for leaves with a variable sub leaf (like the
+// state components of the XSAVE leaf), that can't be represented by a request
+pub(crate) fn cpuid(leaf: u32, sub_leaf: Option<u32>) -> CpuidResult {
+    #[cfg(feature = "mock")]
+    let result = crate::mock::backend().cpuid(leaf, sub_leaf.unwrap_or(0));
+
+    #[cfg(not(feature = "mock"))]
+    let result = unsafe {
+        match sub_leaf {
+            None => __cpuid(leaf),
+            Some(sub_leaf) => __cpuid_count(leaf, sub_leaf),
+        }
+    };
+    result
+}
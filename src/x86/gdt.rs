@@ -30,8 +30,8 @@
 //!     PrivilegeLevel,
 //! };
 //! let mut global_descriptor_table = GlobalDescriptorTable::default();
-//! global_descriptor_table.insert(1, GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace));
-//! global_descriptor_table.insert(2, GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace));
+//! global_descriptor_table.push(GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace));
+//! global_descriptor_table.push(GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace));
 //! ```
 //!
 //! # See also
@@ -41,7 +41,13 @@
 //! by [OSDev.org](https://wiki.osdev.org)
 
 use crate::{
+    interrupts::InterruptDescriptorTable,
+    set_cs,
+    set_ds,
+    set_es,
+    set_ss,
     x86::DescriptorTablePointer,
+    CpuError,
     DescriptorTable,
     MemoryAddress,
     PrivilegeLevel,
@@ -49,9 +55,21 @@ use crate::{
 };
 use bit_field::BitField;
 use bitflags::bitflags;
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
 use core::{
-    arch::asm,
+    cmp::Ordering,
+    fmt::{
+        Display,
+        Formatter,
+    },
+    hash::{
+        Hash,
+        Hasher,
+    },
     mem::size_of,
+    ptr::NonNull,
+    slice,
 };
 
 bitflags! {
@@ -113,6 +131,8 @@ bitflags! {
     /// not set, this is a 16-bit protected mode segment.
     /// - [Flags::LONG_MODE] - If this flag iet set, this is a 64-bit code segment. If
     /// this is set, you shouldn't set the size flag.
+    /// - [Flags::AVAILABLE] - This flag is ignored by the CPU and available for the use by the
+    /// operating system.
     ///
     /// # See also
     /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
@@ -130,9 +150,122 @@ bitflags! {
         /// If this flag iet set, this is a 64-bit code segment. If this is set, you shouldn't set
         /// the size flag.
         const LONG_MODE   = 0b0010_0000;
+
+        /// This flag is ignored by the CPU and available for the use by the operating system.
+        /// Some kernels are tagging descriptors with this flag.
+        const AVAILABLE   = 0b0001_0000;
+    }
+}
+
+/// This enum represents the type of a system segment descriptor. For system descriptors (the
+/// [Access::USER_SEGMENT] bit is not set), the lower 4 bits of the access byte are encoding this
+/// type instead of the [Access] flags.
+///
+/// Here is a list of all system segment types with description:
+/// - [SystemSegmentType::LDT] - The descriptor describes a Local Descriptor Table
+/// - [SystemSegmentType::AvailableTSS] - The descriptor describes an available 32-bit or 64-bit
+/// Task State Segment
+/// - [SystemSegmentType::BusyTSS] - The descriptor describes a busy 32-bit or 64-bit Task State
+/// Segment. The CPU sets this type when the TSS is loaded with the `ltr` instruction.
+/// - [SystemSegmentType::CallGate] - The descriptor describes a 32-bit or 64-bit Call Gate
+/// - [SystemSegmentType::InterruptGate] - The descriptor describes a 32-bit or 64-bit Interrupt
+/// Gate
+/// - [SystemSegmentType::TrapGate] - The descriptor describes a 32-bit or 64-bit Trap Gate
+///
+/// # See also
+/// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+/// by [OSDev.org](https://wiki.osdev.org)
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 3.5 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[repr(u8)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum SystemSegmentType {
+    /// The descriptor describes a Local Descriptor Table
+    LDT = 0x2,
+
+    /// The descriptor describes an available 32-bit or 64-bit Task State Segment
+    AvailableTSS = 0x9,
+
+    /// The descriptor describes a busy 32-bit or 64-bit Task State Segment. The CPU sets this type
+    /// when the TSS is loaded with the `ltr` instruction.
+    BusyTSS = 0xB,
+
+    /// The descriptor describes a 32-bit or 64-bit Call Gate
+    CallGate = 0xC,
+
+    /// The descriptor describes a 32-bit or 64-bit Interrupt Gate
+    InterruptGate = 0xE,
+
+    /// The descriptor describes a 32-bit or 64-bit Trap Gate
+    TrapGate = 0xF,
+}
+
+/// This implementation is used to convert the type nibble of the access byte into the system
+/// segment type. This function is used in [GDTDescriptor::system_segment_type]
+impl TryFrom<u8> for SystemSegmentType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x2 => Ok(Self::LDT),
+            0x9 => Ok(Self::AvailableTSS),
+            0xB => Ok(Self::BusyTSS),
+            0xC => Ok(Self::CallGate),
+            0xE => Ok(Self::InterruptGate),
+            0xF => Ok(Self::TrapGate),
+            _ => Err(value),
+        }
+    }
+}
+
+/// This enum represents the invariants of a [GDTDescriptor], that are checked by
+/// [GDTDescriptor::is_valid]. Loading a descriptor, that violates one of these invariants, leads
+/// into an undefined behavior of the segment or a General Protection Fault.
+///
+/// Here is a list of all errors with description:
+/// - [DescriptorError::ConflictingSizeFlags] - The descriptor has [Flags::SIZE] and
+/// [Flags::LONG_MODE] set.
+/// - [DescriptorError::NotExecutable] - The descriptor is a 64-bit code segment, but
+/// [Access::EXECUTABLE] isn't set.
+/// - [DescriptorError::InvalidSystemType] - The descriptor is a present system segment, but the type
+/// nibble doesn't encode a [SystemSegmentType].
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 3.4.5 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum DescriptorError {
+    /// The descriptor has [Flags::SIZE] (32-bit segment) and [Flags::LONG_MODE] (64-bit segment)
+    /// set. This combination is reserved by the processor.
+    ConflictingSizeFlags,
+
+    /// The descriptor has [Flags::LONG_MODE] set, so it's a 64-bit code segment, but the
+    /// [Access::EXECUTABLE] bit isn't set.
+    NotExecutable,
+
+    /// The descriptor is a present system segment, but the type nibble of the access byte doesn't
+    /// encode a [SystemSegmentType]. The value is the type nibble.
+    InvalidSystemType(u8),
+}
+
+impl Display for DescriptorError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ConflictingSizeFlags => {
+                write!(formatter, "The SIZE and LONG_MODE flags must not be set together")
+            }
+            Self::NotExecutable => {
+                write!(formatter, "The 64-bit code segment is not executable")
+            }
+            Self::InvalidSystemType(kind) => {
+                write!(formatter, "Invalid system segment type 0x{:x}", kind)
+            }
+        }
     }
 }
 
+impl core::error::Error for DescriptorError {}
+
 /// This structure represents a single descriptor in the GDT (Global Descriptor Table). This
 /// structure is compatible with the raw memory representation of a descriptor. Use the function
 /// [`GDTDescriptor::NUL`] to use the Null descriptor. The implementation of the GDT is only needed
@@ -187,29 +320,109 @@ pub struct GDTDescriptor {
     higher_base_address: u8,
 }
 
+// The limit of the GDTR in GlobalDescriptorTable::as_ptr is calculated with the descriptor size
+const _: () = assert!(size_of::<GDTDescriptor>() == 8, "A GDT descriptor must be 8 bytes");
+
 impl GDTDescriptor {
+    /// This constant represents the maximum limit of a descriptor. The limit is stored in 20 bits,
+    /// so without [Flags::GRANULARITY] the largest segment is 1 MiB. With [Flags::GRANULARITY], the
+    /// limit is in 4 KiB blocks and the maximum covers the full 4 GiB address space.
+    pub const MAX_LIMIT: u32 = 0xFFFFF;
+
     /// This function creates a new GDT descriptor with the specified values. The function parameters
     /// `privilege`, `kind` and `access` are merged to the access byte for the descriptor.
     ///
     /// Here is a list with the parameters:
+    /// - `limit_address` - This parameter defines the 20-bit limit of the descriptor. The limit is
+    /// in bytes or, if [Flags::GRANULARITY] is set, in 4 KiB blocks. Use
+    /// [GDTDescriptor::with_byte_limit] to select the granularity automatically
     /// - `privilege` - This parameter defines the privilege level of the descriptor
     /// - `access` - This parameter defines the access flags of the descriptor
     /// - `flag` - This parameter defines the flags of the descriptor
     ///
     /// TODO: Validate x86 implementation and set data
     ///
+    /// ```rust
+    /// use libcpu::{gdt::{Access, Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let limit = GDTDescriptor::MAX_LIMIT;
+    /// let descriptor = GDTDescriptor::new(0, limit, PrivilegeLevel::KernelSpace, access, Flags::empty());
+    /// assert_eq!(descriptor.limit(), 0xFFFFF);
+    /// assert_eq!(descriptor.byte_limit(), 0xFFFFF);
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// use libcpu::{gdt::{Access, Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let _ = GDTDescriptor::new(0, 0x100000, PrivilegeLevel::KernelSpace, access, Flags::empty());
+    /// ```
+    ///
+    /// # Panics
+    /// This function panics, if the limit exceeds [GDTDescriptor::MAX_LIMIT]. Otherwise, the upper
+    /// bits of the limit would be silently truncated.
+    ///
     /// # See also
     /// - [GDT Tutorial](https://wiki.osdev.org/GDT_Tutorial#What_to_Put_In_a_GDT)
     /// by [OSDev.org](https://wiki.osdev.org)
     #[must_use]
     pub fn new(base_address: u32, limit_address: u32, privilege: PrivilegeLevel, access: Access, flags: Flags) -> Self {
+        assert!(limit_address <= Self::MAX_LIMIT, "The limit must fit into 20 bits");
+        let mut access = access.bits();
+        access.set_bits(5..7, privilege.as_ring());
         GDTDescriptor {
             lower_limit_address: limit_address as u16,
             lower_base_address: base_address as u16,
             middle_base_address: (base_address >> 16) as u8,
-            access: (limit_address.get_bits(0..3) as u8) | access.bits() | (privilege as u8),
-            flags: flags.bits(),
-            higher_base_address: (base_address >> 16) as u8,
+            access,
+            flags: (limit_address.get_bits(16..20) as u8) | flags.bits(),
+            higher_base_address: (base_address >> 24) as u8,
+        }
+    }
+
+    /// This function creates a new GDT descriptor with the specified limit in bytes. Unlike
+    /// [GDTDescriptor::new], the granularity is selected automatically: If the limit exceeds 1 MiB,
+    /// the limit is rounded up to 4 KiB blocks and [Flags::GRANULARITY] is set. Otherwise, the limit
+    /// is stored with byte granularity. Like [GDTDescriptor::byte_limit], the limit is the offset
+    /// of the last byte in the segment.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{Access, Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let descriptor = GDTDescriptor::with_byte_limit(0, 100, PrivilegeLevel::KernelSpace, access);
+    /// assert!(!descriptor.flags().contains(Flags::GRANULARITY));
+    /// assert_eq!(descriptor.byte_limit(), 100);
+    ///
+    /// // 0xFFFFF is the largest limit with byte granularity, larger limits are rounded up
+    /// let descriptor = GDTDescriptor::with_byte_limit(0, 0xFFFFF, PrivilegeLevel::KernelSpace, access);
+    /// assert!(!descriptor.flags().contains(Flags::GRANULARITY));
+    /// let descriptor = GDTDescriptor::with_byte_limit(0, 0x100000, PrivilegeLevel::KernelSpace, access);
+    /// assert!(descriptor.flags().contains(Flags::GRANULARITY));
+    /// assert_eq!(descriptor.limit(), 0x100);
+    /// assert!(descriptor.byte_limit() >= 0x100000);
+    ///
+    /// let descriptor =
+    ///     GDTDescriptor::with_byte_limit(0, 0xFFFF_FFFF, PrivilegeLevel::KernelSpace, access);
+    /// assert!(descriptor.flags().contains(Flags::GRANULARITY));
+    /// assert_eq!(descriptor.limit(), 0xFFFFF);
+    /// assert_eq!(descriptor.byte_limit(), 0xFFFF_FFFF);
+    /// ```
+    ///
+    /// # Panics
+    /// This function panics, if the limit exceeds the 4 GiB address space.
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[must_use]
+    pub fn with_byte_limit(base_address: u32, byte_limit: u64, privilege: PrivilegeLevel, access: Access) -> Self {
+        assert!(byte_limit <= 0xFFFF_FFFF, "The limit must be in the 4 GiB address space");
+        if byte_limit > u64::from(Self::MAX_LIMIT) {
+            Self::new(base_address, (byte_limit >> 12) as u32, privilege, access, Flags::GRANULARITY)
+        } else {
+            Self::new(base_address, byte_limit as u32, privilege, access, Flags::empty())
         }
     }
 
@@ -228,6 +441,16 @@ impl GDTDescriptor {
     /// This function creates a new GDT descriptor with the default settings for a executable Code
     /// segment
     ///
+    /// The encoding of the kernel code segment is the flat 64-bit code segment
+    /// (`0x00AF9B000000FFFF`), that is also used by Linux:
+    /// ```rust
+    /// use libcpu::{gdt::GDTDescriptor, PrivilegeLevel};
+    ///
+    /// let descriptor = GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace);
+    /// let bytes: [u8; 8] = unsafe { core::mem::transmute(descriptor) };
+    /// assert_eq!(bytes, [0xFF, 0xFF, 0x00, 0x00, 0x00, 0x9B, 0xAF, 0x00]);
+    /// ```
+    ///
     /// # See also
     /// - [GDT Tutorial](https://wiki.osdev.org/GDT_Tutorial#What_to_Put_In_a_GDT)
     /// by [OSDev.org](https://wiki.osdev.org)
@@ -247,7 +470,19 @@ impl GDTDescriptor {
         )
     }
 
-    /// This function creates a new GDT descriptor with the default settings for a Data segment
+    /// This function creates a new GDT descriptor with the default settings for a Data segment. The
+    /// long mode flag is only defined for code segments, so the data segment is a flat 32-bit
+    /// segment, which is also used in the long mode.
+    ///
+    /// The encoding of the kernel data segment is the flat data segment (`0x00CF93000000FFFF`),
+    /// that is also used by Linux:
+    /// ```rust
+    /// use libcpu::{gdt::GDTDescriptor, PrivilegeLevel};
+    ///
+    /// let descriptor = GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace);
+    /// let bytes: [u8; 8] = unsafe { core::mem::transmute(descriptor) };
+    /// assert_eq!(bytes, [0xFF, 0xFF, 0x00, 0x00, 0x00, 0x93, 0xCF, 0x00]);
+    /// ```
     ///
     /// # See also
     /// - [GDT Tutorial](https://wiki.osdev.org/GDT_Tutorial#What_to_Put_In_a_GDT)
@@ -260,10 +495,293 @@ impl GDTDescriptor {
             0xFFFFF,
             level,
             Access::PRESENT | Access::ACCESSED | Access::USER_SEGMENT | Access::WRITABLE,
-            Flags::GRANULARITY | Flags::LONG_MODE,
+            Flags::GRANULARITY | Flags::SIZE,
         )
     }
 
+    /// This function creates a new GDT descriptor for a Task State Segment (TSS) with the
+    /// specified base address and limit. The type of the descriptor is
+    /// [SystemSegmentType::AvailableTSS].
+    ///
+    /// On x86_64, system descriptors are extended to 16 bytes. This function creates the lower 8
+    /// bytes of the descriptor.
+    ///
+    /// # See also
+    /// - [Task State Segment](https://wiki.osdev.org/Task_State_Segment) by
+    /// [OSDev.org](https://wiki.osdev.org)
+    #[inline]
+    #[must_use]
+    pub fn tss(base_address: u32, limit_address: u32) -> Self {
+        Self::system_segment(
+            MemoryAddress::from(base_address),
+            limit_address,
+            SystemSegmentType::AvailableTSS,
+            PrivilegeLevel::KernelSpace,
+        ).descriptor()
+    }
+
+    /// This function creates a new GDT descriptor for a Local Descriptor Table (LDT) with the
+    /// specified base address and limit. The type of the descriptor is [SystemSegmentType::LDT].
+    ///
+    /// On x86_64, system descriptors are extended to 16 bytes. This function creates the lower 8
+    /// bytes of the descriptor.
+    ///
+    /// # See also
+    /// - [Local Descriptor Table](https://wiki.osdev.org/Local_Descriptor_Table) by
+    /// [OSDev.org](https://wiki.osdev.org)
+    #[inline]
+    #[must_use]
+    pub fn ldt(base_address: u32, limit_address: u32) -> Self {
+        Self::system_segment(
+            MemoryAddress::from(base_address),
+            limit_address,
+            SystemSegmentType::LDT,
+            PrivilegeLevel::KernelSpace,
+        ).descriptor()
+    }
+
+    /// This function creates a new system segment descriptor (like a TSS or LDT) with the
+    /// specified values. Unlike code and data segments, the S bit of the access byte is cleared and
+    /// the type nibble contains the [SystemSegmentType]. On x86_64, system descriptors are extended
+    /// to 16 bytes to store the full 64-bit base address, so the returned descriptor occupies two
+    /// slots in the GDT.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, SystemSegmentType}, PrivilegeLevel};
+    ///
+    /// let tss = GDTDescriptor::system_segment(
+    ///     0xFFFF_8000_1234_5678,
+    ///     0x67,
+    ///     SystemSegmentType::AvailableTSS,
+    ///     PrivilegeLevel::KernelSpace,
+    /// );
+    /// let descriptor = tss.descriptor();
+    /// assert_eq!(descriptor.raw_access_byte() & (1 << 4), 0);
+    /// assert_eq!(descriptor.system_segment_type(), Some(SystemSegmentType::AvailableTSS));
+    /// assert_eq!(descriptor.base(), 0x1234_5678);
+    /// assert_eq!(descriptor.limit(), 0x67);
+    /// assert_eq!(tss.base(), 0xFFFF_8000_1234_5678);
+    ///
+    /// #[cfg(target_arch = "x86_64")]
+    /// {
+    ///     let bytes: [u8; 16] = unsafe { core::mem::transmute(tss) };
+    ///     assert_eq!(bytes, [
+    ///         0x67, 0x00, 0x78, 0x56, 0x34, 0x89, 0x00, 0x12,
+    ///         0x00, 0x80, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00,
+    ///     ]);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Long_Mode_System_Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[must_use]
+    pub fn system_segment(
+        base_address: MemoryAddress, limit_address: u32, kind: SystemSegmentType,
+        privilege: PrivilegeLevel
+    ) -> SystemSegmentDescriptor {
+        SystemSegmentDescriptor {
+            descriptor: Self::new(
+                base_address as u32,
+                limit_address,
+                privilege,
+                Access::PRESENT | Access::from_bits_retain(kind as u8),
+                Flags::empty(),
+            ),
+            #[cfg(target_arch = "x86_64")]
+            highest_base_address: (base_address >> 32) as u32,
+            #[cfg(target_arch = "x86_64")]
+            reserved: 0,
+        }
+    }
+
+    /// This function returns the descriptor's system segment type. If the descriptor is a code or
+    /// data segment or the type nibble doesn't encode a known type, this function returns [None].
+    ///
+    /// # See also
+    /// - [SystemSegmentType] (Source Code)
+    #[inline]
+    #[must_use]
+    pub fn system_segment_type(&self) -> Option<SystemSegmentType> {
+        if self.access.get_bit(4) {
+            return None;
+        }
+
+        SystemSegmentType::try_from(self.access.get_bits(0..4)).ok()
+    }
+
+    /// This function compares this descriptor with the other descriptor, but ignores the
+    /// [Access::ACCESSED] bit. The CPU sets this bit when the descriptor is accessed, so two
+    /// descriptors describing the same segment can be unequal after the CPU touched one of them.
+    /// Use the [PartialEq] implementation for an exact comparison.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{Access, Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let a = GDTDescriptor::new(0, 0xFFFFF, PrivilegeLevel::KernelSpace, access, Flags::GRANULARITY);
+    /// let b = GDTDescriptor::new(
+    ///     0,
+    ///     0xFFFFF,
+    ///     PrivilegeLevel::KernelSpace,
+    ///     access | Access::ACCESSED,
+    ///     Flags::GRANULARITY,
+    /// );
+    /// assert!(a.semantically_eq(&b));
+    /// assert_ne!(a, b);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn semantically_eq(&self, other: &GDTDescriptor) -> bool {
+        let mut this = *self;
+        let mut other = *other;
+        this.access &= !Access::ACCESSED.bits();
+        other.access &= !Access::ACCESSED.bits();
+        this == other
+    }
+
+    /// This function returns the 8-byte encoding of the descriptor, like it's stored in the GDT.
+    #[inline]
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let [lower_limit_0, lower_limit_1] = { self.lower_limit_address }.to_le_bytes();
+        let [lower_base_0, lower_base_1] = { self.lower_base_address }.to_le_bytes();
+        [
+            lower_limit_0,
+            lower_limit_1,
+            lower_base_0,
+            lower_base_1,
+            self.middle_base_address,
+            self.access,
+            self.flags,
+            self.higher_base_address,
+        ]
+    }
+
+    /// This function creates a descriptor from the 8-byte encoding, like it's stored in the GDT.
+    /// This is the inverse of [GDTDescriptor::to_bytes] and can be used to read descriptors from
+    /// a hand-built or externally-managed table.
+    #[inline]
+    #[must_use]
+    pub fn from_bytes(bytes: [u8; 8]) -> Self {
+        GDTDescriptor {
+            lower_limit_address: u16::from_le_bytes([bytes[0], bytes[1]]),
+            lower_base_address: u16::from_le_bytes([bytes[2], bytes[3]]),
+            middle_base_address: bytes[4],
+            access: bytes[5],
+            flags: bytes[6],
+            higher_base_address: bytes[7],
+        }
+    }
+
+    /// This function validates the invariants of the descriptor (see [DescriptorError]). Unlike
+    /// [GDTDescriptor::new], that accepts all combinations of flags, this function rejects the
+    /// combinations, whose behavior is undefined.
+    ///
+    /// ```rust
+    /// use libcpu::{
+    ///     gdt::{Access, DescriptorError, Flags, GDTDescriptor},
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// let descriptor = GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace);
+    /// assert_eq!(descriptor.is_valid(), Ok(()));
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::EXECUTABLE;
+    /// let flags = Flags::SIZE | Flags::LONG_MODE;
+    /// let descriptor = GDTDescriptor::new(0, 0xFFFFF, PrivilegeLevel::KernelSpace, access, flags);
+    /// assert_eq!(descriptor.is_valid(), Err(DescriptorError::ConflictingSizeFlags));
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let flags = Flags::LONG_MODE;
+    /// let descriptor = GDTDescriptor::new(0, 0xFFFFF, PrivilegeLevel::KernelSpace, access, flags);
+    /// assert_eq!(descriptor.is_valid(), Err(DescriptorError::NotExecutable));
+    ///
+    /// let access = Access::PRESENT | Access::from_bits_retain(0x5);
+    /// let descriptor = GDTDescriptor::new(0, 0x67, PrivilegeLevel::KernelSpace, access, Flags::empty());
+    /// assert_eq!(descriptor.is_valid(), Err(DescriptorError::InvalidSystemType(0x5)));
+    /// ```
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    pub fn is_valid(&self) -> Result<(), DescriptorError> {
+        let access = self.access_flags();
+        let flags = self.flags();
+        if flags.contains(Flags::SIZE | Flags::LONG_MODE) {
+            return Err(DescriptorError::ConflictingSizeFlags);
+        }
+
+        if access.contains(Access::USER_SEGMENT) {
+            if flags.contains(Flags::LONG_MODE) && !access.contains(Access::EXECUTABLE) {
+                return Err(DescriptorError::NotExecutable);
+            }
+        } else if access.contains(Access::PRESENT) && self.system_segment_type().is_none() {
+            return Err(DescriptorError::InvalidSystemType(self.access.get_bits(0..4)));
+        }
+        Ok(())
+    }
+
+    /// This function returns the descriptor's 20-bit limit, set by the descriptor creator. The
+    /// limit is split into the lower 16 bits and the lower 4 bits of the flags byte. If the
+    /// [Flags::GRANULARITY] flag is set, the limit is in 4 KiB blocks.
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[inline]
+    #[must_use]
+    pub fn limit(&self) -> u32 {
+        u32::from(self.lower_limit_address) | (u32::from(self.flags.get_bits(0..4)) << 16)
+    }
+
+    /// This function returns the descriptor's limit in bytes. Unlike [GDTDescriptor::limit], the
+    /// limit is scaled to 4 KiB blocks, if the [Flags::GRANULARITY] flag is set. So the limit
+    /// `0xFFFFF` with granularity covers the full 4 GiB address space.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::GDTDescriptor, PrivilegeLevel};
+    ///
+    /// let descriptor = GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace);
+    /// assert_eq!(descriptor.byte_limit(), 0xFFFF_FFFF);
+    /// ```
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[inline]
+    #[must_use]
+    pub fn byte_limit(&self) -> u64 {
+        let limit = u64::from(self.limit());
+        if self.flags().contains(Flags::GRANULARITY) {
+            (limit << 12) | 0xFFF
+        } else {
+            limit
+        }
+    }
+
+    /// This function returns the descriptor's 32-bit base address, set by the descriptor creator.
+    /// The base address is split into the lower 16 bits, the middle 8 bits and the higher 8 bits.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{Access, Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let descriptor = GDTDescriptor::new(0xDEAD_BEEF, 0xFFF, PrivilegeLevel::KernelSpace, access, Flags::empty());
+    /// assert_eq!(descriptor.base(), 0xDEAD_BEEF);
+    /// ```
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[inline]
+    #[must_use]
+    pub fn base(&self) -> u32 {
+        u32::from(self.lower_base_address)
+            | (u32::from(self.middle_base_address) << 16)
+            | (u32::from(self.higher_base_address) << 24)
+    }
+
     /// This function returns the descriptor's privilege level, set by the descriptor creator.
     ///
     /// # See also
@@ -276,10 +794,30 @@ impl GDTDescriptor {
     #[inline]
     #[must_use]
     pub fn privilege_level(&self) -> PrivilegeLevel {
-        PrivilegeLevel::from(self.access.get_bits(5..7) as u16)
+        PrivilegeLevel::from_ring_bits(self.access.get_bits(5..7))
     }
 
-    /// This function returns the descriptor's access flags, set by the descriptor creator.
+    /// This function returns the descriptor's access flags, set by the descriptor creator. The
+    /// access byte also contains the privilege level and (for system descriptors) the system
+    /// segment type, so the value is masked to the bits named by [Access]. The unmasked byte is
+    /// returned by [GDTDescriptor::raw_access_byte].
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{Access, Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let descriptor = GDTDescriptor::code_segment(PrivilegeLevel::UserSpace);
+    /// assert_eq!(descriptor.raw_access_byte(), 0xFB);
+    /// assert_eq!(
+    ///     descriptor.access_flags(),
+    ///     Access::PRESENT | Access::USER_SEGMENT | Access::EXECUTABLE | Access::READABLE
+    ///         | Access::ACCESSED
+    /// );
+    /// assert_eq!(descriptor.access_flags().bits(), descriptor.raw_access_byte() & !0x60);
+    ///
+    /// assert_eq!(descriptor.raw_flags_byte(), 0xAF);
+    /// assert_eq!(descriptor.flags(), Flags::GRANULARITY | Flags::LONG_MODE);
+    /// assert_eq!(descriptor.flags().bits(), descriptor.raw_flags_byte() & 0xF0);
+    /// ```
     ///
     /// # See also
     /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
@@ -288,10 +826,12 @@ impl GDTDescriptor {
     #[inline]
     #[must_use]
     pub fn access_flags(&self) -> Access {
-        Access::from_bits_retain(self.access)
+        Access::from_bits_truncate(self.access)
     }
 
-    /// This function returns the descriptor's flags, set by the descriptor creator.
+    /// This function returns the descriptor's flags, set by the descriptor creator. The flags byte
+    /// also contains the bits 16 to 19 of the limit, so the value is masked to the bits named by
+    /// [Flags]. The unmasked byte is returned by [GDTDescriptor::raw_flags_byte].
     ///
     /// # See also
     /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
@@ -300,14 +840,396 @@ impl GDTDescriptor {
     #[inline]
     #[must_use]
     pub fn flags(&self) -> Flags {
-        Flags::from_bits_retain(self.flags)
+        Flags::from_bits_truncate(self.flags)
+    }
+
+    /// This function returns the raw access byte of the descriptor (byte 5) with the access flags,
+    /// the privilege level in the bits 5 and 6 and (for system descriptors) the system segment type
+    /// in the bits 0 to 3.
+    #[inline]
+    #[must_use]
+    pub fn raw_access_byte(&self) -> u8 {
+        self.access
+    }
+
+    /// This function returns the raw flags byte of the descriptor (byte 6) with the flags in the
+    /// bits 4 to 7 and the bits 16 to 19 of the limit in the bits 0 to 3.
+    #[inline]
+    #[must_use]
+    pub fn raw_flags_byte(&self) -> u8 {
+        self.flags
+    }
+
+    /// This function returns whether the available (AVL) flag is set. This flag is ignored by the
+    /// CPU and can be used by the operating system.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{Flags, GDTDescriptor}, PrivilegeLevel};
+    ///
+    /// let mut descriptor = GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace);
+    /// assert!(!descriptor.available());
+    ///
+    /// descriptor.set_available(true);
+    /// assert!(descriptor.available());
+    /// assert!(descriptor.flags().contains(Flags::GRANULARITY | Flags::LONG_MODE));
+    /// assert!(!descriptor.flags().contains(Flags::SIZE));
+    /// assert_eq!(descriptor.limit(), 0xFFFFF);
+    /// ```
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[inline]
+    #[must_use]
+    pub fn available(&self) -> bool {
+        self.flags().contains(Flags::AVAILABLE)
+    }
+
+    /// This function sets or clears the available (AVL) flag without modifying the other flags.
+    #[inline]
+    pub fn set_available(&mut self, available: bool) {
+        if available {
+            self.flags |= Flags::AVAILABLE.bits();
+        } else {
+            self.flags &= !Flags::AVAILABLE.bits();
+        }
     }
 }
 
-/// This structure represents the Global Descriptor Table with the maximum of 8192 entries. In this
-/// structure, we store the descriptors in a slice.
+/// This structure represents a call gate descriptor in the GDT (Global Descriptor Table). Call
+/// gates are allowing a far call into a code segment with a different privilege level at a fixed
+/// entry point. This structure is compatible with the raw memory representation of a call gate.
+/// On x86_64, a call gate has a size of 16 bytes and occupies two slots in the GDT.
+///
+/// - `lower_offset` - This field represents the first 16 bits of the entry point's offset
+/// - `segment_selector` - This field represents the segment selector of the target code segment
+/// - `parameter_count` - This field represents the count of parameters, that are copied to the
+/// new stack (32bit only)
+/// - `flags` - This field represents the present bit, the privilege level and the gate type
+/// - `middle_offset` - This field represents the bits 16 to 31 of the entry point's offset
+/// - `higher_offset` - This field represents the last 32 bits of the entry point's offset (64bit
+/// only)
+/// - `reserved` - This field is always zero and should not be set by the user (64bit only)
 ///
-/// - `descriptors` - This field is a slice that can store 8192 [GDTDescriptor]s
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 5.8.3 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+/// - [SystemSegmentType::CallGate] (Source Code)
+#[repr(C, packed)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct CallGateDescriptor {
+    lower_offset: u16,
+    segment_selector: SegmentSelector,
+    parameter_count: u8,
+    flags: u8,
+    middle_offset: u16,
+    #[cfg(target_arch = "x86_64")]
+    higher_offset: u32,
+    #[cfg(target_arch = "x86_64")]
+    reserved: u32,
+}
+
+impl CallGateDescriptor {
+    /// This function creates a new call gate with the specified values. The gate is present and
+    /// has the type [SystemSegmentType::CallGate].
+    ///
+    /// Here is a list with the parameters:
+    /// - `selector` - This parameter defines the segment selector of the target code segment
+    /// - `offset` - This parameter defines the offset of the entry point in the target segment
+    /// - `privilege` - This parameter defines the least privileged level, that can use this gate
+    ///
+    /// ```rust
+    /// use libcpu::{
+    ///     gdt::{CallGateDescriptor, GlobalDescriptorTable},
+    ///     PrivilegeLevel, SegmentSelector,
+    /// };
+    ///
+    /// let gate = CallGateDescriptor::new(
+    ///     SegmentSelector(0x08),
+    ///     0x1234_5678_9ABC_DEF0,
+    ///     PrivilegeLevel::UserSpace,
+    /// );
+    /// assert_eq!(gate.offset(), 0x1234_5678_9ABC_DEF0);
+    /// assert_eq!(gate.segment_selector(), SegmentSelector(0x08));
+    /// assert_eq!(gate.privilege_level(), PrivilegeLevel::UserSpace);
+    ///
+    /// // The fields are checked in the slots of the GDT, in which the gate is stored
+    /// let mut global_descriptor_table = GlobalDescriptorTable::new();
+    /// global_descriptor_table.push_call_gate(gate).unwrap();
+    /// let lower = global_descriptor_table.get(1).unwrap().to_bytes();
+    /// let higher = global_descriptor_table.get(2).unwrap().to_bytes();
+    ///
+    /// assert_eq!(u16::from_le_bytes([lower[0], lower[1]]), 0xDEF0); // Lower offset
+    /// assert_eq!(u16::from_le_bytes([lower[2], lower[3]]), 0x08); // Segment selector
+    /// assert_eq!(u16::from_le_bytes([lower[6], lower[7]]), 0x9ABC); // Middle offset
+    /// assert_eq!(u32::from_le_bytes([higher[0], higher[1], higher[2], higher[3]]), 0x1234_5678);
+    /// assert_eq!(lower[5] & 0x0F, 0xC); // Type
+    /// assert_eq!((lower[5] >> 5) & 0b11, 3); // DPL
+    /// assert_eq!(lower[5] >> 7, 1); // Present
+    /// ```
+    #[must_use]
+    pub fn new(selector: SegmentSelector, offset: MemoryAddress, privilege: PrivilegeLevel) -> Self {
+        let mut flags = Access::PRESENT.bits() | (SystemSegmentType::CallGate as u8);
+        flags.set_bits(5..7, privilege.as_ring());
+        Self {
+            lower_offset: offset as u16,
+            segment_selector: selector,
+            parameter_count: 0,
+            flags,
+            middle_offset: (offset >> 16) as u16,
+            #[cfg(target_arch = "x86_64")]
+            higher_offset: (offset >> 32) as u32,
+            #[cfg(target_arch = "x86_64")]
+            reserved: 0,
+        }
+    }
+
+    /// This function returns the offset of the entry point, set by the gate creator.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> MemoryAddress {
+        let offset = MemoryAddress::from(self.lower_offset)
+            | (MemoryAddress::from(self.middle_offset) << 16);
+        #[cfg(target_arch = "x86_64")]
+        let offset = offset | (MemoryAddress::from(self.higher_offset) << 32);
+        offset
+    }
+
+    /// This function returns the segment selector of the target code segment, set by the gate
+    /// creator.
+    #[inline]
+    #[must_use]
+    pub fn segment_selector(&self) -> SegmentSelector {
+        self.segment_selector
+    }
+
+    /// This function returns the gate's privilege level, set by the gate creator.
+    #[inline]
+    #[must_use]
+    pub fn privilege_level(&self) -> PrivilegeLevel {
+        PrivilegeLevel::from_ring_bits(self.flags.get_bits(5..7))
+    }
+}
+
+/// This structure represents a system segment descriptor (like a TSS or LDT descriptor), that is
+/// created with [GDTDescriptor::system_segment]. On x86, the descriptor has the size of a
+/// [GDTDescriptor]. On x86_64, the descriptor is extended with the higher 32 bits of the base
+/// address to 16 bytes and occupies two slots in the GDT.
+///
+/// # See also
+/// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Long_Mode_System_Segment_Descriptor)
+/// by [OSDev.org](https://wiki.osdev.org)
+/// - [SystemSegmentType] (Source Code)
+#[repr(C, packed)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct SystemSegmentDescriptor {
+    descriptor: GDTDescriptor,
+    #[cfg(target_arch = "x86_64")]
+    highest_base_address: u32,
+    #[cfg(target_arch = "x86_64")]
+    reserved: u32,
+}
+
+// The count of slots in GlobalDescriptorTable::push_system is calculated with the descriptor size
+#[cfg(target_arch = "x86_64")]
+const _: () = assert!(size_of::<SystemSegmentDescriptor>() == 16, "A system descriptor must be 16 bytes");
+
+impl SystemSegmentDescriptor {
+    /// This function returns the lower 8 bytes of the descriptor, which have the same layout as a
+    /// code or data segment descriptor.
+    #[inline]
+    #[must_use]
+    pub fn descriptor(&self) -> GDTDescriptor {
+        self.descriptor
+    }
+
+    /// This function returns the full base address of the system segment, set by the descriptor
+    /// creator.
+    #[inline]
+    #[must_use]
+    pub fn base(&self) -> MemoryAddress {
+        let base = MemoryAddress::from(self.descriptor.base());
+        #[cfg(target_arch = "x86_64")]
+        let base = base | (MemoryAddress::from(self.highest_base_address) << 32);
+        base
+    }
+
+    /// This function returns the 16-byte encoding of the descriptor, like it's stored in the two
+    /// slots of the GDT. The first 8 bytes are the lower descriptor and the last 8 bytes are the
+    /// higher 32 bits of the base address followed by the reserved bits. This can be used to place
+    /// the descriptor into a GDT, that isn't managed by a [GlobalDescriptorTable] (like a table
+    /// handed to the firmware).
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, SystemSegmentType}, PrivilegeLevel};
+    ///
+    /// let tss = GDTDescriptor::system_segment(
+    ///     0xFFFF_8000_1234_5678,
+    ///     0x67,
+    ///     SystemSegmentType::AvailableTSS,
+    ///     PrivilegeLevel::KernelSpace,
+    /// );
+    /// let bytes = tss.system_descriptor_bytes();
+    ///
+    /// let low = GDTDescriptor::from_bytes(bytes[..8].try_into().unwrap());
+    /// let high = GDTDescriptor::from_bytes(bytes[8..].try_into().unwrap());
+    /// assert_eq!(low, tss.descriptor());
+    /// assert_eq!(low.to_bytes(), bytes[..8]);
+    /// assert_eq!(high.to_bytes(), [0x00, 0x80, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00]);
+    /// ```
+    ///
+    /// # See also
+    /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Long_Mode_System_Segment_Descriptor)
+    /// by [OSDev.org](https://wiki.osdev.org)
+    #[cfg(target_arch = "x86_64")]
+    #[must_use]
+    pub fn system_descriptor_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.descriptor.to_bytes());
+        bytes[8..12].copy_from_slice(&{ self.highest_base_address }.to_le_bytes());
+        bytes[12..].copy_from_slice(&{ self.reserved }.to_le_bytes());
+        bytes
+    }
+}
+
+/// This trait represents an entry, that can be pushed into a [GlobalDescriptorTable] with
+/// [GlobalDescriptorTable::push]. Code and data segment descriptors are occupying one 8-byte slot
+/// of the GDT, but system descriptors and call gates are extended to two slots on x86_64. With this
+/// trait, the table advances the count of used slots by the size of the entry.
+///
+/// # See also
+/// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#Long_Mode_System_Segment_Descriptor)
+/// by [OSDev.org](https://wiki.osdev.org)
+pub trait GdtEntry {
+    /// This function returns the 8-byte words of the entry, like they're stored in the slots of
+    /// the GDT. The words are represented as [GDTDescriptor], because the GDT is a table of 8-byte
+    /// descriptors and the entries are packed, so they can't be borrowed as aligned integers.
+    fn as_words(&self) -> &[GDTDescriptor];
+
+    /// This function returns the entry's privilege level, that is used as requested privilege
+    /// level of the selector returned by [GlobalDescriptorTable::push].
+    fn privilege_level(&self) -> PrivilegeLevel;
+}
+
+impl GdtEntry for GDTDescriptor {
+    #[inline]
+    fn as_words(&self) -> &[GDTDescriptor] {
+        slice::from_ref(self)
+    }
+
+    #[inline]
+    fn privilege_level(&self) -> PrivilegeLevel {
+        GDTDescriptor::privilege_level(self)
+    }
+}
+
+impl GdtEntry for SystemSegmentDescriptor {
+    #[inline]
+    fn as_words(&self) -> &[GDTDescriptor] {
+        // The descriptor is packed and only contains integers, so the slots are valid descriptors
+        let slots = size_of::<Self>() / size_of::<GDTDescriptor>();
+        unsafe { slice::from_raw_parts((self as *const Self).cast::<GDTDescriptor>(), slots) }
+    }
+
+    #[inline]
+    fn privilege_level(&self) -> PrivilegeLevel {
+        self.descriptor.privilege_level()
+    }
+}
+
+impl GdtEntry for CallGateDescriptor {
+    #[inline]
+    fn as_words(&self) -> &[GDTDescriptor] {
+        // The gate is packed and only contains integers, so the slots are valid descriptors
+        let slots = size_of::<Self>() / size_of::<GDTDescriptor>();
+        unsafe { slice::from_raw_parts((self as *const Self).cast::<GDTDescriptor>(), slots) }
+    }
+
+    #[inline]
+    fn privilege_level(&self) -> PrivilegeLevel {
+        CallGateDescriptor::privilege_level(self)
+    }
+}
+
+/// This constant represents the count of descriptors, that can be stored in a
+/// [GlobalDescriptorTable] created with [GlobalDescriptorTable::new]. By default, this is the
+/// hardware limit of 8192 descriptors, so the table needs 64 KiB of memory.
+///
+/// With the `small_gdt` feature, the capacity is lowered to 16 descriptors and the table only needs
+/// 128 bytes. This is enough for the common kernel layouts with a few code, data and TSS
+/// descriptors, but pushing more descriptors fails. Tables created with
+/// [GlobalDescriptorTable::from_raw] are not affected by this feature.
+#[cfg_attr(feature = "small_gdt", doc = r#"
+```rust
+use core::mem::size_of;
+use libcpu::{
+    gdt::{GDTDescriptor, GlobalDescriptorTable, GDT_CAPACITY},
+    PrivilegeLevel,
+};
+
+assert_eq!(GDT_CAPACITY, 16);
+assert!(size_of::<GlobalDescriptorTable>() >= GDT_CAPACITY * size_of::<GDTDescriptor>());
+assert!(size_of::<GlobalDescriptorTable>() < 2 * GDT_CAPACITY * size_of::<GDTDescriptor>());
+
+// The Null descriptor occupies the first slot, so 15 descriptors fill the table
+let mut global_descriptor_table = GlobalDescriptorTable::new();
+for index in 1..GDT_CAPACITY {
+    let descriptor = GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace);
+    assert_eq!(global_descriptor_table.push(descriptor).unwrap().index(), index as u16);
+}
+assert!(global_descriptor_table.push(GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace)).is_none());
+assert_eq!({ global_descriptor_table.as_ptr().size }, (GDT_CAPACITY * 8 - 1) as u16);
+```
+"#)]
+pub const GDT_CAPACITY: usize = if cfg!(feature = "small_gdt") { 16 } else { 8192 };
+
+/// This enum represents the memory, in that the descriptors of a [GlobalDescriptorTable] are
+/// stored.
+///
+/// - `Owned` - The descriptors are stored in an array, that is owned by the table itself
+/// - `External` - The descriptors are stored in caller-provided memory, that is described by the
+/// base pointer and the count of descriptors that fit into the memory
+#[allow(clippy::large_enum_variant)] // Boxing the owned table needs a heap, early boot has none
+#[derive(Debug)]
+enum DescriptorStorage {
+    Owned([GDTDescriptor; GDT_CAPACITY]),
+    External {
+        base: NonNull<GDTDescriptor>,
+        capacity: usize,
+    },
+}
+
+/// This structure represents the selectors of the descriptors in the standard kernel layout,
+/// created by [GlobalDescriptorTable::standard_kernel]. The selectors of the user segments are
+/// carrying the requested privilege level 3. The selector of the TSS is only set, if the TSS was
+/// installed with [install_descriptor_tables].
+///
+/// # See also
+/// - [GlobalDescriptorTable::standard_kernel] (Source Code)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct Selectors {
+    /// This field is the selector of the kernel code segment (index 1)
+    pub kernel_code: SegmentSelector,
+
+    /// This field is the selector of the kernel data segment (index 2)
+    pub kernel_data: SegmentSelector,
+
+    /// This field is the selector of the user data segment (index 3)
+    pub user_data: SegmentSelector,
+
+    /// This field is the selector of the user code segment (index 4)
+    pub user_code: SegmentSelector,
+
+    /// This field is the selector of the TSS descriptor (index 5), if the TSS was installed
+    pub tss: Option<SegmentSelector>,
+}
+
+/// This structure represents the Global Descriptor Table with the maximum of [GDT_CAPACITY]
+/// entries. In this structure, we store the descriptors in a slice. This slice is owned by the
+/// table or provided by the caller with [GlobalDescriptorTable::from_raw].
+///
+/// - `descriptors` - This field is the storage that can store up to [GDT_CAPACITY]
+/// [GDTDescriptor]s
 /// - `count` This field holds the max index that is used to insert a descriptor for the
 /// [DescriptorTablePointer]
 ///
@@ -316,53 +1238,576 @@ impl GDTDescriptor {
 /// by [OSDev.org](https://wiki.osdev.org)
 /// - [GDT Tutorial](https://wiki.osdev.org/GDT_Tutorial) by [OSDev.org](https://wiki.osdev.org)
 /// - [GDTDescriptor] (Source Code)
-#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+#[derive(Debug)]
 pub struct GlobalDescriptorTable {
-    /// This field is a slice that can store 8192 [GDTDescriptor]
-    descriptors: [GDTDescriptor; 8192],
+    /// This field is the storage that can store up to [GDT_CAPACITY] [GDTDescriptor]
+    descriptors: DescriptorStorage,
 
     /// This field holds the max index that is used to insert a descriptor for the
     /// [DescriptorTablePointer]
     count: usize,
 }
 
+// The caller of `from_raw` guarantees exclusive access to the external memory while the table
+// exists. The table can't be copied or cloned, so it's the only owner of the memory like a `Box`
+// and can be sent and shared like the owned variant.
+unsafe impl Send for GlobalDescriptorTable {}
+unsafe impl Sync for GlobalDescriptorTable {}
+
+// The tables are compared by the used descriptors, so a table in external memory is equal to an
+// owned table with the same descriptors.
+impl PartialEq for GlobalDescriptorTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.used_descriptors() == other.used_descriptors()
+    }
+}
+
+impl Eq for GlobalDescriptorTable {}
+
+impl PartialOrd for GlobalDescriptorTable {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GlobalDescriptorTable {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.used_descriptors().cmp(other.used_descriptors())
+    }
+}
+
+impl Hash for GlobalDescriptorTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.used_descriptors().hash(state);
+    }
+}
+
+impl Default for GlobalDescriptorTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GlobalDescriptorTable {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            descriptors: [GDTDescriptor::null(); 8192],
+            descriptors: DescriptorStorage::Owned([GDTDescriptor::null(); GDT_CAPACITY]),
             count: 1,
         }
     }
 
+    /// This function creates a GDT with the conventional layout of 64-bit kernels, that is
+    /// compatible with the `syscall` and `sysret` instructions:
+    ///
+    /// | Index | Descriptor  |
+    /// |-------|-------------|
+    /// | 0     | Null        |
+    /// | 1     | Kernel Code |
+    /// | 2     | Kernel Data |
+    /// | 3     | User Data   |
+    /// | 4     | User Code   |
+    ///
+    /// The `sysret` instruction loads the stack segment from the selector in the STAR MSR plus 8
+    /// and the code segment from the selector plus 16, so the user data segment must be placed
+    /// before the user code segment. Swapping these descriptors silently breaks `sysret`.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::GlobalDescriptorTable, DescriptorTable};
+    ///
+    /// let (_, selectors) = GlobalDescriptorTable::standard_kernel();
+    /// assert_eq!(selectors.kernel_code.index(), 1);
+    /// assert_eq!(selectors.kernel_data.index(), 2);
+    /// assert_eq!(selectors.user_data.index(), 3);
+    /// assert_eq!(selectors.user_code.index(), 4);
+    ///
+    /// assert_eq!(selectors.kernel_code.0 & 0b11, 0);
+    /// assert_eq!(selectors.user_data.0 & 0b11, 3);
+    /// assert_eq!(selectors.user_code.0 & 0b11, 3);
+    /// assert_eq!(selectors.user_code.table(), DescriptorTable::GDT);
+    /// ```
+    ///
+    /// # See also
+    /// - [SYSCALL](https://www.felixcloutier.com/x86/syscall) by
+    /// [Felix Clountier](https://www.felixcloutier.com)
+    /// - [SYSRET](https://www.felixcloutier.com/x86/sysret) by
+    /// [Felix Clountier](https://www.felixcloutier.com)
+    #[must_use]
+    pub fn standard_kernel() -> (Self, Selectors) {
+        const MESSAGE: &str = "The GDT has no capacity for the standard layout";
+
+        let mut table = Self::new();
+        let selectors = Selectors {
+            kernel_code: table
+                .push(GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace))
+                .expect(MESSAGE),
+            kernel_data: table
+                .push(GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace))
+                .expect(MESSAGE),
+            user_data: table
+                .push(GDTDescriptor::data_segment(PrivilegeLevel::UserSpace))
+                .expect(MESSAGE),
+            user_code: table
+                .push(GDTDescriptor::code_segment(PrivilegeLevel::UserSpace))
+                .expect(MESSAGE),
+            tss: None,
+        };
+        (table, selectors)
+    }
+
+    /// This function creates a GDT, that stores the descriptors in caller-provided memory instead
+    /// of an array owned by the table. This is useful for kernels, that place the GDT in a reserved
+    /// region or at a fixed address. The first descriptor in the memory is overwritten with the
+    /// Null descriptor. The capacity is limited to 8192 descriptors.
+    ///
+    /// - `base` - The pointer to the first descriptor of the memory
+    /// - `capacity` - The count of descriptors, that fit into the memory
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, GlobalDescriptorTable}, PrivilegeLevel};
+    ///
+    /// let mut memory = [GDTDescriptor::code_segment(PrivilegeLevel::UserSpace); 4];
+    /// let code_segment = GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace);
+    ///
+    /// let mut global_descriptor_table = unsafe {
+    ///     GlobalDescriptorTable::from_raw(memory.as_mut_ptr(), memory.len())
+    /// };
+    /// let selector = global_descriptor_table.push(code_segment).unwrap();
+    /// assert_eq!(selector.index(), 1);
+    /// drop(global_descriptor_table);
+    ///
+    /// assert_eq!(memory[0].to_bytes(), [0; 8]);
+    /// assert_eq!(memory[1], code_segment);
+    /// ```
+    ///
+    /// # Safety
+    /// The caller has to ensure, that `base` is a non-null pointer, that is valid for reads and
+    /// writes of `capacity` descriptors as long as the table is used (and loaded into the CPU). The
+    /// memory must not be accessed through other pointers while the table exists. The capacity must
+    /// be at least 1.
+    #[must_use]
+    pub unsafe fn from_raw(base: *mut GDTDescriptor, capacity: usize) -> Self {
+        let base = NonNull::new(base).expect("The base of the GDT must not be null");
+        base.as_ptr().write(GDTDescriptor::null());
+        Self {
+            descriptors: DescriptorStorage::External {
+                base,
+                capacity: capacity.min(8192),
+            },
+            count: 1,
+        }
+    }
+
+    /// This function returns the descriptors of the table as slice, regardless of the memory they
+    /// are stored in.
+    #[inline]
+    fn descriptors(&self) -> &[GDTDescriptor] {
+        match &self.descriptors {
+            DescriptorStorage::Owned(descriptors) => descriptors,
+            DescriptorStorage::External { base, capacity } => unsafe {
+                slice::from_raw_parts(base.as_ptr(), *capacity)
+            },
+        }
+    }
+
+    /// This function returns the descriptors up to the next free index (including the Null
+    /// descriptor).
+    #[inline]
+    fn used_descriptors(&self) -> &[GDTDescriptor] {
+        &self.descriptors()[..self.count]
+    }
+
+    /// This function returns the descriptors of the table as mutable slice, regardless of the
+    /// memory they are stored in.
+    #[inline]
+    fn descriptors_mut(&mut self) -> &mut [GDTDescriptor] {
+        match &mut self.descriptors {
+            DescriptorStorage::Owned(descriptors) => descriptors,
+            DescriptorStorage::External { base, capacity } => unsafe {
+                slice::from_raw_parts_mut(base.as_ptr(), *capacity)
+            },
+        }
+    }
+
     /// This function generates a pointer to the GDT with the [GlobalDescriptorTable::as_ptr]
     /// function and loads it with the `lgdt` instruction.
+    ///
+    /// The CPU keeps using the table after it was loaded, so the table must live for the rest of
+    /// the program. This is enforced by the `'static` lifetime, so a table on the stack can't be
+    /// loaded. Reloading the table, that is already installed on the current CPU, is allowed (e.g.
+    /// after the busy bit of the TSS descriptor was cleared with
+    /// [GlobalDescriptorTable::clear_tss_busy]).
+    ///
+    /// ```compile_fail
+    /// use libcpu::gdt::GlobalDescriptorTable;
+    /// let global_descriptor_table = GlobalDescriptorTable::new();
+    /// global_descriptor_table.load(); // The table doesn't live long enough
+    /// ```
+    #[cfg_attr(feature = "mock", doc = r#"
+The table can be reloaded, so both loads are reaching the CPU:
+```rust
+use libcpu::{gdt::GlobalDescriptorTable, mock::{set_backend, MockBackend}, SpinLock};
+
+struct RecordingProcessor {
+    loads: SpinLock<Vec<u64>>,
+}
+
+impl MockBackend for RecordingProcessor {
+    fn execute(&self, _instruction: &'static str, operand: u64) {
+        self.loads.lock().push(operand);
+    }
+}
+
+static PROCESSOR: RecordingProcessor = RecordingProcessor { loads: SpinLock::new(Vec::new()) };
+unsafe { set_backend(&PROCESSOR) };
+
+let global_descriptor_table = Box::leak(Box::new(GlobalDescriptorTable::standard_kernel().0));
+global_descriptor_table.load();
+global_descriptor_table.load();
+
+let base = global_descriptor_table.as_ptr().base;
+assert_eq!(*PROCESSOR.loads.lock(), [base, base]);
+```
+"#)]
     ///
     /// # See also
     /// - [LGDT/LIDT](https://www.felixcloutier.com/x86/lgdt:lidt) by
     /// [Felix Clountier](https://www.felixcloutier.com)
-    pub fn load(&self) {
+    pub fn load(&'static self) {
+        #[cfg(feature = "mock")]
+        crate::mock::backend().execute("lgdt", self.as_ptr().base);
+
+        #[cfg(not(feature = "mock"))]
         unsafe {
             asm!("lgdt [{}]", in(reg) &self.as_ptr(), options(readonly, nostack, preserves_flags));
         }
     }
 
-    /// This function inserts a [GDTDescriptor] at the specified index in the GDT. After the
-    /// insertion, the function updates the count variable if necessary.
-    pub fn push(&mut self, descriptor: GDTDescriptor) -> Option<SegmentSelector> {
-        if self.count + 1 >= 8192 {
+    /// This function inserts an entry (like a [GDTDescriptor] or a [SystemSegmentDescriptor]) at
+    /// the next free index in the GDT. After the insertion, the count is advanced by the count of
+    /// slots, that are occupied by the entry (see [GdtEntry]). The returned selector has the entry's
+    /// privilege level as requested privilege level.
+    ///
+    /// ```rust
+    /// use libcpu::{
+    ///     gdt::{GDTDescriptor, GlobalDescriptorTable, SystemSegmentType},
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// let tss = GDTDescriptor::system_segment(
+    ///     0x1000,
+    ///     0x67,
+    ///     SystemSegmentType::AvailableTSS,
+    ///     PrivilegeLevel::KernelSpace,
+    /// );
+    /// let slots = if cfg!(target_arch = "x86_64") { 2 } else { 1 };
+    ///
+    /// let mut global_descriptor_table = GlobalDescriptorTable::new();
+    /// let code = global_descriptor_table
+    ///     .push(GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace))
+    ///     .unwrap();
+    /// let tss = global_descriptor_table.push(tss).unwrap();
+    /// let data = global_descriptor_table
+    ///     .push(GDTDescriptor::data_segment(PrivilegeLevel::UserSpace))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(code.index(), 1);
+    /// assert_eq!(tss.index(), 2);
+    /// assert_eq!(data.index(), 2 + slots);
+    /// assert_eq!(data.privilege_level(), PrivilegeLevel::UserSpace);
+    /// assert_eq!({ global_descriptor_table.as_ptr().size }, ((3 + slots) * 8 - 1) as u16);
+    /// ```
+    ///
+    /// If the entry doesn't fit into the remaining slots, this function returns [None] and the
+    /// table is not modified. Every slot of the table can be used:
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, GlobalDescriptorTable}, PrivilegeLevel};
+    ///
+    /// let mut memory = [GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace); 4];
+    /// let mut global_descriptor_table = unsafe {
+    ///     GlobalDescriptorTable::from_raw(memory.as_mut_ptr(), memory.len())
+    /// };
+    /// for index in 1..4 {
+    ///     let descriptor = GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace);
+    ///     assert_eq!(global_descriptor_table.push(descriptor).unwrap().index(), index);
+    /// }
+    /// assert!(global_descriptor_table.push(GDTDescriptor::code_segment(PrivilegeLevel::UserSpace)).is_none());
+    /// assert_eq!({ global_descriptor_table.as_ptr().size }, 4 * 8 - 1);
+    /// ```
+    pub fn push<E: GdtEntry>(&mut self, entry: E) -> Option<SegmentSelector> {
+        let words = entry.as_words();
+        if self.count + words.len() > self.descriptors().len() {
             return None;
         }
 
-        self.descriptors[self.count] = descriptor;
-        self.count += 1;
+        let index = self.count;
+        self.descriptors_mut()[index..index + words.len()].copy_from_slice(words);
+        self.count += words.len();
         Some(SegmentSelector::new(
-            (self.count - 1) as u16,
+            index as u16,
             DescriptorTable::GDT,
-            descriptor.privilege_level(),
+            entry.privilege_level(),
         ))
     }
 
+    /// This function inserts a [CallGateDescriptor] at the next free index in the GDT. On x86_64,
+    /// the call gate occupies two slots. The returned selector has the gate's privilege level as
+    /// requested privilege level.
+    ///
+    /// ```rust
+    /// use libcpu::{
+    ///     gdt::{CallGateDescriptor, GDTDescriptor, GlobalDescriptorTable},
+    ///     PrivilegeLevel, SegmentSelector,
+    /// };
+    ///
+    /// let gate = CallGateDescriptor::new(SegmentSelector(0x08), 0x1000, PrivilegeLevel::UserSpace);
+    ///
+    /// let mut global_descriptor_table = GlobalDescriptorTable::new();
+    /// let selector = global_descriptor_table.push_call_gate(gate).unwrap();
+    /// assert_eq!(selector.index(), 1);
+    /// assert_eq!(selector.0 & 0b11, 3);
+    /// assert_eq!({ global_descriptor_table.as_ptr().size }, 3 * 8 - 1);
+    ///
+    /// let next = global_descriptor_table.push(GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace));
+    /// assert_eq!(next.unwrap().index(), 3);
+    /// ```
+    #[inline]
+    pub fn push_call_gate(&mut self, gate: CallGateDescriptor) -> Option<SegmentSelector> {
+        self.push(gate)
+    }
+
+    /// This function inserts a [SystemSegmentDescriptor] (like a TSS or LDT descriptor) at the next
+    /// free index in the GDT. On x86_64, the descriptor occupies two slots.
+    #[inline]
+    pub fn push_system(&mut self, descriptor: SystemSegmentDescriptor) -> Option<SegmentSelector> {
+        self.push(descriptor)
+    }
+
+    /// This function returns the descriptor at the specified index in the GDT. If the index is not
+    /// used, this function returns [None]. On x86_64, the upper half of a 16-byte descriptor is
+    /// returned as separate descriptor at the following index.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, index: u16) -> Option<&GDTDescriptor> {
+        self.descriptors()[..self.count].get(usize::from(index))
+    }
+
+    /// This function returns the selector of the first descriptor in the GDT, that is semantically
+    /// equal to the specified descriptor (see [GDTDescriptor::semantically_eq]). The requested
+    /// privilege level of the selector is the privilege level of the descriptor. If the descriptor
+    /// isn't stored in the GDT, this function returns [None]. The Null descriptor is never matched.
+    ///
+    /// ```rust
+    /// use libcpu::{
+    ///     gdt::{Access, Flags, GDTDescriptor, GlobalDescriptorTable},
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// let (mut global_descriptor_table, selectors) = GlobalDescriptorTable::standard_kernel();
+    /// let user_code = GDTDescriptor::code_segment(PrivilegeLevel::UserSpace);
+    /// assert_eq!(global_descriptor_table.selector_of(&user_code), Some(selectors.user_code));
+    ///
+    /// let access = Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE;
+    /// let segment = GDTDescriptor::new(0x1000, 0xFFF, PrivilegeLevel::Ring1, access, Flags::SIZE);
+    /// assert_eq!(global_descriptor_table.selector_of(&segment), None);
+    ///
+    /// let selector = global_descriptor_table.push(segment).unwrap();
+    /// assert_eq!(global_descriptor_table.selector_of(&segment), Some(selector));
+    /// assert_eq!(selector.0, 0x29);
+    /// ```
+    #[must_use]
+    pub fn selector_of(&self, descriptor: &GDTDescriptor) -> Option<SegmentSelector> {
+        let descriptors = &self.descriptors()[..self.count];
+        descriptors
+            .iter()
+            .enumerate()
+            .skip(1)
+            .find(|(_, stored)| stored.semantically_eq(descriptor))
+            .map(|(index, stored)| {
+                SegmentSelector::new(index as u16, DescriptorTable::GDT, stored.privilege_level())
+            })
+    }
+
+    /// This function resets the type of the TSS descriptor, that is identified by the selector, from
+    /// [SystemSegmentType::BusyTSS] to [SystemSegmentType::AvailableTSS]. The CPU marks the TSS as
+    /// busy when it's loaded with the `ltr` instruction, and loading a busy TSS again raises a
+    /// General Protection Fault. So the busy bit must be cleared before the TSS is loaded again,
+    /// e.g. when a CPU is re-initialized. The type of an available TSS isn't modified.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, GlobalDescriptorTable, SystemSegmentType}, PrivilegeLevel};
+    ///
+    /// let mut global_descriptor_table = GlobalDescriptorTable::new();
+    /// let selector = global_descriptor_table
+    ///     .push_system(GDTDescriptor::system_segment(
+    ///         0x1000,
+    ///         0x67,
+    ///         SystemSegmentType::BusyTSS,
+    ///         PrivilegeLevel::KernelSpace,
+    ///     ))
+    ///     .unwrap();
+    /// let descriptor = global_descriptor_table.get(selector.index()).unwrap();
+    /// assert_eq!(descriptor.system_segment_type(), Some(SystemSegmentType::BusyTSS));
+    ///
+    /// global_descriptor_table.clear_tss_busy(selector);
+    /// let descriptor = global_descriptor_table.get(selector.index()).unwrap();
+    /// assert_eq!(descriptor.system_segment_type(), Some(SystemSegmentType::AvailableTSS));
+    /// assert_eq!(descriptor.raw_access_byte() & 0xF, 0x9);
+    /// ```
+    ///
+    /// # Panics
+    /// This function panics, if the selector doesn't identify a TSS descriptor in the GDT.
+    ///
+    /// # See also
+    /// - [LTR](https://www.felixcloutier.com/x86/ltr) by
+    /// [Felix Clountier](https://www.felixcloutier.com)
+    pub fn clear_tss_busy(&mut self, selector: SegmentSelector) {
+        let index = usize::from(selector.index());
+        assert!(
+            selector.table() == DescriptorTable::GDT && index < self.count,
+            "The selector doesn't identify a descriptor in the GDT"
+        );
+
+        let descriptor = &mut self.descriptors_mut()[index];
+        match descriptor.system_segment_type() {
+            Some(SystemSegmentType::AvailableTSS) => {}
+            Some(SystemSegmentType::BusyTSS) => {
+                descriptor.access.set_bits(0..4, SystemSegmentType::AvailableTSS as u8);
+            }
+            _ => panic!("The descriptor at index {} is not a TSS descriptor", index),
+        }
+    }
+
+    /// This function validates, that every descriptor in the table is valid (see
+    /// [GDTDescriptor::is_valid]) and that every present code and data segment descriptor has the
+    /// [Access::ACCESSED] bit set. The CPU sets this bit when a segment register is loaded with the
+    /// descriptor, so if the GDT is placed in read-only pages, loading a descriptor without the bit
+    /// leads into a Page Fault. [GDTDescriptor::code_segment] and [GDTDescriptor::data_segment] are
+    /// setting the bit, but [GDTDescriptor::new] leaves it to the caller. If a descriptor fails the
+    /// validation, [CpuError::InvalidSelector] is returned with the selector of the first of these
+    /// descriptors.
+    ///
+    /// ```rust
+    /// use libcpu::{
+    ///     gdt::{Access, Flags, GDTDescriptor, GlobalDescriptorTable},
+    ///     CpuError,
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// let (mut global_descriptor_table, _) = GlobalDescriptorTable::standard_kernel();
+    /// assert_eq!(global_descriptor_table.validate(), Ok(()));
+    ///
+    /// global_descriptor_table.push(GDTDescriptor::new(
+    ///     0,
+    ///     0xFFFFF,
+    ///     PrivilegeLevel::KernelSpace,
+    ///     Access::PRESENT | Access::USER_SEGMENT | Access::WRITABLE,
+    ///     Flags::GRANULARITY | Flags::SIZE,
+    /// ));
+    /// assert_eq!(global_descriptor_table.validate(), Err(CpuError::InvalidSelector(0x28)));
+    ///
+    /// let (mut global_descriptor_table, _) = GlobalDescriptorTable::standard_kernel();
+    /// global_descriptor_table.push(GDTDescriptor::new(
+    ///     0,
+    ///     0xFFFFF,
+    ///     PrivilegeLevel::KernelSpace,
+    ///     Access::PRESENT | Access::ACCESSED | Access::USER_SEGMENT | Access::EXECUTABLE,
+    ///     Flags::GRANULARITY | Flags::SIZE | Flags::LONG_MODE,
+    /// ));
+    /// assert_eq!(global_descriptor_table.validate(), Err(CpuError::InvalidSelector(0x28)));
+    /// ```
+    pub fn validate(&self) -> Result<(), CpuError> {
+        let descriptors = &self.descriptors()[..self.count];
+        for (index, descriptor) in descriptors.iter().enumerate().skip(1) {
+            let access = descriptor.access_flags();
+            let missing_accessed = access.contains(Access::PRESENT | Access::USER_SEGMENT)
+                && !access.contains(Access::ACCESSED);
+            if missing_accessed || descriptor.is_valid().is_err() {
+                let privilege_level = descriptor.privilege_level();
+                let selector = SegmentSelector::new(index as u16, DescriptorTable::GDT, privilege_level);
+                return Err(CpuError::InvalidSelector(selector.0));
+            }
+        }
+        Ok(())
+    }
+
+    /// This function replaces the TSS descriptor, that is identified by the selector, with the
+    /// specified descriptor. The other descriptors of the table are not modified. On SMP systems,
+    /// every CPU needs its own TSS, so this function is used to create the per-CPU tables from a
+    /// shared base table (see [GlobalDescriptorTable::with_tss]).
+    ///
+    /// # Panics
+    /// This function panics, if the selector doesn't identify a TSS descriptor in the GDT.
+    pub fn replace_tss(&mut self, selector: SegmentSelector, tss: SystemSegmentDescriptor) {
+        let index = usize::from(selector.index());
+        let slots = size_of::<SystemSegmentDescriptor>() / size_of::<GDTDescriptor>();
+        assert!(
+            selector.table() == DescriptorTable::GDT && index + slots <= self.count,
+            "The selector doesn't identify a descriptor in the GDT"
+        );
+
+        match self.descriptors()[index].system_segment_type() {
+            Some(SystemSegmentType::AvailableTSS | SystemSegmentType::BusyTSS) => unsafe {
+                self.descriptors_mut()[index..]
+                    .as_mut_ptr()
+                    .cast::<SystemSegmentDescriptor>()
+                    .write(tss);
+            },
+            _ => panic!("The descriptor at index {} is not a TSS descriptor", index),
+        }
+    }
+
+    /// This function creates a copy of this table for a single CPU, in which the TSS descriptor,
+    /// that is identified by the selector, is replaced with the TSS of the CPU. The copy can be
+    /// loaded on the CPU (e.g. after it's leaked or stored in a per-CPU structure), while the other
+    /// descriptors are shared with this table.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, GlobalDescriptorTable, SystemSegmentType}, PrivilegeLevel};
+    ///
+    /// let tss = |base| {
+    ///     GDTDescriptor::system_segment(
+    ///         base,
+    ///         0x67,
+    ///         SystemSegmentType::AvailableTSS,
+    ///         PrivilegeLevel::KernelSpace,
+    ///     )
+    /// };
+    ///
+    /// let (mut base_table, _) = GlobalDescriptorTable::standard_kernel();
+    /// let selector = base_table.push_system(tss(0x1000)).unwrap();
+    ///
+    /// let cpu_table = base_table.with_tss(selector, tss(0x2000));
+    /// for index in 0..selector.index() {
+    ///     assert_eq!(cpu_table.get(index), base_table.get(index));
+    /// }
+    /// assert_eq!(cpu_table.get(selector.index()).unwrap().base(), 0x2000);
+    /// assert_eq!(base_table.get(selector.index()).unwrap().base(), 0x1000);
+    /// assert_eq!({ cpu_table.as_ptr().size }, { base_table.as_ptr().size });
+    /// ```
+    ///
+    /// The copy always stores the descriptors in an array owned by the copy, also if the
+    /// descriptors of this table are stored in external memory (see
+    /// [GlobalDescriptorTable::from_raw]).
+    ///
+    /// # Panics
+    /// This function panics, if the descriptors of this table don't fit into [GDT_CAPACITY]
+    /// descriptors or if the selector doesn't identify a TSS descriptor in the GDT.
+    #[must_use]
+    pub fn with_tss(&self, selector: SegmentSelector, tss: SystemSegmentDescriptor) -> Self {
+        assert!(
+            self.count <= GDT_CAPACITY,
+            "The GDT has more than {} descriptors and can't be copied",
+            GDT_CAPACITY
+        );
+
+        let mut table = Self::new();
+        table.descriptors_mut()[..self.count].copy_from_slice(self.used_descriptors());
+        table.count = self.count;
+        table.replace_tss(selector, tss);
+        table
+    }
+
     /// This function generates a pointer to the Global Descriptor Table (GDT) with the base address
     /// and the size of the GDT as limit.
     ///
@@ -371,9 +1816,437 @@ impl GlobalDescriptorTable {
     /// [OSDev.org](https://wiki.osdev.org)
     #[must_use]
     pub fn as_ptr(&self) -> DescriptorTablePointer {
+        self.as_ptr_at(self.descriptors().as_ptr() as MemoryAddress)
+    }
+
+    /// This function generates a pointer to the Global Descriptor Table (GDT), whose limit only
+    /// covers the first `entries` descriptors. This is used to load only a prefix of a larger
+    /// pre-built table, so the descriptors after the prefix are not accessible by selectors.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, GlobalDescriptorTable}, PrivilegeLevel};
+    ///
+    /// let mut global_descriptor_table = GlobalDescriptorTable::new();
+    /// global_descriptor_table.push(GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace));
+    /// global_descriptor_table.push(GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace));
+    /// global_descriptor_table.push(GDTDescriptor::code_segment(PrivilegeLevel::UserSpace));
+    ///
+    /// let pointer = global_descriptor_table.as_ptr_with_len(2);
+    /// assert_eq!({ pointer.size }, 2 * 8 - 1);
+    /// assert_eq!({ pointer.base }, { global_descriptor_table.as_ptr().base });
+    /// assert_eq!({ global_descriptor_table.as_ptr_with_len(4).size }, {
+    ///     global_descriptor_table.as_ptr().size
+    /// });
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// use libcpu::gdt::GlobalDescriptorTable;
+    ///
+    /// // The table only contains the null descriptor
+    /// let _ = GlobalDescriptorTable::new().as_ptr_with_len(2);
+    /// ```
+    ///
+    /// # Panics
+    /// This function panics, if `entries` is zero or exceeds the count of descriptors in the GDT.
+    #[must_use]
+    pub fn as_ptr_with_len(&self, entries: usize) -> DescriptorTablePointer {
+        assert!(
+            entries > 0 && entries <= self.count,
+            "The GDT contains {} descriptors, but {} descriptors are requested",
+            self.count,
+            entries
+        );
+        DescriptorTablePointer {
+            base: self.descriptors().as_ptr() as MemoryAddress,
+            size: (entries * size_of::<GDTDescriptor>() - 1) as u16,
+        }
+    }
+
+    /// This function generates a pointer to the Global Descriptor Table (GDT) with the specified
+    /// base address and the size of the GDT as limit. Unlike [GlobalDescriptorTable::as_ptr], the
+    /// current address of the table is ignored, so the pointer can be created for another mapping
+    /// of the table.
+    ///
+    /// ```rust
+    /// use libcpu::{gdt::{GDTDescriptor, GlobalDescriptorTable}, PrivilegeLevel};
+    ///
+    /// let mut global_descriptor_table = GlobalDescriptorTable::new();
+    /// global_descriptor_table.push(GDTDescriptor::code_segment(PrivilegeLevel::KernelSpace));
+    ///
+    /// let pointer = global_descriptor_table.as_ptr_at(0xFFFF_8000_0010_0000);
+    /// assert_eq!({ pointer.base }, 0xFFFF_8000_0010_0000);
+    /// assert_eq!({ pointer.size }, { global_descriptor_table.as_ptr().size });
+    /// ```
+    #[must_use]
+    pub fn as_ptr_at(&self, base: MemoryAddress) -> DescriptorTablePointer {
         DescriptorTablePointer {
-            base: self.descriptors.as_ptr() as MemoryAddress,
+            base,
             size: (self.count * size_of::<GDTDescriptor>() - 1) as u16,
         }
     }
+
+    /// This function loads the GDT with the specified base address with the `lgdt` instruction and
+    /// returns the loaded pointer. This is needed after the kernel was remapped into the higher
+    /// half, because [GlobalDescriptorTable::load] uses the address of the table in the current
+    /// mapping, which may be the identity-mapped address of the early boot.
+    ///
+    /// ```rust,no_run
+    /// use libcpu::gdt::GlobalDescriptorTable;
+    ///
+    /// fn remap(global_descriptor_table: &GlobalDescriptorTable, higher_half_offset: u64) {
+    ///     let base = global_descriptor_table.as_ptr().base + higher_half_offset;
+    ///     unsafe { global_descriptor_table.reload_at(base) };
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    /// The caller must ensure, that the table is mapped at the specified base address in the
+    /// current address space and stays mapped as long as the table is loaded.
+    ///
+    /// # See also
+    /// - [LGDT/LIDT](https://www.felixcloutier.com/x86/lgdt:lidt) by
+    /// [Felix Clountier](https://www.felixcloutier.com)
+    pub unsafe fn reload_at(&self, base: MemoryAddress) -> DescriptorTablePointer {
+        let pointer = self.as_ptr_at(base);
+
+        #[cfg(feature = "mock")]
+        crate::mock::backend().execute("lgdt", pointer.base);
+
+        #[cfg(not(feature = "mock"))]
+        asm!("lgdt [{}]", in(reg) &pointer, options(readonly, nostack, preserves_flags));
+
+        pointer
+    }
+}
+
+/// This function loads the Task State Segment, that is identified by the selector, with the `ltr`
+/// instruction. The CPU marks the TSS descriptor as busy (see
+/// [GlobalDescriptorTable::clear_tss_busy]).
+///
+/// # Safety
+/// The caller must ensure, that the selector identifies an available TSS descriptor in the loaded
+/// GDT and that the TSS lives for the rest of the program.
+///
+/// # See also
+/// - [LTR](https://www.felixcloutier.com/x86/ltr) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub unsafe fn load_task_register(selector: SegmentSelector) {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().execute("ltr", u64::from(selector.0));
+
+    #[cfg(not(feature = "mock"))]
+    asm!("ltr {:x}", in(reg) selector.0, options(nostack, preserves_flags));
+}
+
+/// This structure represents the Task State Segment (TSS) of the 64-bit mode. In the 64-bit mode,
+/// the TSS isn't used for task switches anymore, but it stores the stacks, that are loaded by the
+/// processor on interrupts:
+///
+/// - `privilege_stack_table` - This field stores the stack pointers, that are loaded when the
+/// privilege level changes to the ring 0, 1 or 2 (like on an interrupt in the user space)
+/// - `interrupt_stack_table` - This field stores the seven stack pointers of the Interrupt Stack
+/// Table (IST). A gate with the IST index `n` switches to the entry `n - 1`
+/// - `iomap_base` - This field stores the offset of the I/O permission bitmap from the base of the
+/// TSS. If the offset is not below the limit of the TSS, no bitmap is used
+///
+/// ```rust
+/// #![feature(offset_of)]
+/// use core::mem::{offset_of, size_of};
+/// use libcpu::gdt::TaskStateSegment;
+///
+/// assert_eq!(size_of::<TaskStateSegment>(), 0x68);
+/// assert_eq!(offset_of!(TaskStateSegment, privilege_stack_table), 0x04);
+/// assert_eq!(offset_of!(TaskStateSegment, interrupt_stack_table), 0x24);
+/// assert_eq!(offset_of!(TaskStateSegment, iomap_base), 0x66);
+/// ```
+///
+/// # See also
+/// - [Task State Segment](https://wiki.osdev.org/Task_State_Segment#Long_Mode) by
+/// [OSDev.org](https://wiki.osdev.org)
+#[cfg(target_arch = "x86_64")]
+#[repr(C, packed(4))]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub struct TaskStateSegment {
+    reserved0: u32,
+
+    /// This field stores the stack pointers for the privilege levels 0 to 2
+    pub privilege_stack_table: [MemoryAddress; 3],
+    reserved1: u64,
+
+    /// This field stores the stack pointers of the Interrupt Stack Table
+    pub interrupt_stack_table: [MemoryAddress; 7],
+    reserved2: u64,
+    reserved3: u16,
+
+    /// This field stores the offset of the I/O permission bitmap
+    pub iomap_base: u16,
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Default for TaskStateSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl TaskStateSegment {
+    /// This function creates a new TSS without stacks and without an I/O permission bitmap.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            reserved0: 0,
+            privilege_stack_table: [0; 3],
+            reserved1: 0,
+            interrupt_stack_table: [0; 7],
+            reserved2: 0,
+            reserved3: 0,
+            iomap_base: size_of::<Self>() as u16,
+        }
+    }
+
+    /// This function creates the system segment descriptor of this TSS, that can be pushed into
+    /// the GDT with [GlobalDescriptorTable::push_system]. The TSS must live as long as the
+    /// descriptor is used, which is enforced by the `'static` lifetime.
+    #[must_use]
+    pub fn descriptor(&'static self) -> SystemSegmentDescriptor {
+        GDTDescriptor::system_segment(
+            self as *const Self as MemoryAddress,
+            (size_of::<Self>() - 1) as u32,
+            SystemSegmentType::AvailableTSS,
+            PrivilegeLevel::KernelSpace,
+        )
+    }
+}
+
+/// This function installs the descriptor tables in the order, that is required by the processor:
+/// 1. The TSS descriptor is pushed into the GDT
+/// 2. The GDT is loaded with `lgdt`
+/// 3. The code segment is reloaded with a far return and the stack and data segments are reloaded
+/// with the kernel data segment. FS and GS are not reloaded, because this would reset the bases
+/// 4. The TSS is loaded with `ltr`, which requires the loaded GDT
+/// 5. The IDT is loaded with `lidt`, so the handlers are running with the new code segment
+///
+/// The GDT must have the layout of [GlobalDescriptorTable::standard_kernel], otherwise
+/// [CpuError::InvalidSelector] is returned with the selector of the first mismatching descriptor
+/// and nothing is loaded. The returned selectors are containing the selector of the TSS.
+///
+/// ```rust,no_run
+/// use libcpu::{
+///     gdt::{install_descriptor_tables, GDTDescriptor, GlobalDescriptorTable, SystemSegmentType},
+///     interrupts::InterruptDescriptorTable,
+///     PrivilegeLevel,
+/// };
+///
+/// fn init(gdt: &'static mut GlobalDescriptorTable, idt: &'static InterruptDescriptorTable) {
+///     static TSS: [u8; 0x68] = [0; 0x68];
+///     let tss = GDTDescriptor::system_segment(
+///         TSS.as_ptr() as u64,
+///         0x67,
+///         SystemSegmentType::AvailableTSS,
+///         PrivilegeLevel::KernelSpace,
+///     );
+///     let selectors = unsafe { install_descriptor_tables(gdt, idt, tss) }.unwrap();
+///     assert_eq!(selectors.tss.unwrap().index(), 5);
+/// }
+/// ```
+#[cfg_attr(feature = "mock", doc = r#"
+The order of the loads is verified with a backend, that records the loads:
+```rust
+use libcpu::{
+    gdt::{install_descriptor_tables, GDTDescriptor, GlobalDescriptorTable, SystemSegmentType},
+    interrupts::InterruptDescriptorTable,
+    mock::{set_backend, MockBackend},
+    PrivilegeLevel, SpinLock,
+};
+
+struct RecordingProcessor {
+    loads: SpinLock<Vec<(&'static str, u64)>>,
+}
+
+impl MockBackend for RecordingProcessor {
+    fn write_segment_register(&self, register: &'static str, value: u16) {
+        self.loads.lock().push((register, u64::from(value)));
+        self.registers().write_segment_register(register, value)
+    }
+
+    fn execute(&self, instruction: &'static str, operand: u64) {
+        self.loads.lock().push((instruction, operand));
+    }
+}
+
+static PROCESSOR: RecordingProcessor = RecordingProcessor { loads: SpinLock::new(Vec::new()) };
+unsafe { set_backend(&PROCESSOR) };
+
+let gdt = Box::leak(Box::new(GlobalDescriptorTable::standard_kernel().0));
+let idt = Box::leak(Box::new(InterruptDescriptorTable::default()));
+let tss = GDTDescriptor::system_segment(
+    0x1000,
+    0x67,
+    SystemSegmentType::AvailableTSS,
+    PrivilegeLevel::KernelSpace,
+);
+
+let gdt_base = gdt.as_ptr().base;
+let idt_base = idt.as_ptr().base;
+let selectors = unsafe { install_descriptor_tables(gdt, idt, tss) }.unwrap();
+assert_eq!(selectors.tss.unwrap().0, 0x28);
+assert_eq!(*PROCESSOR.loads.lock(), [
+    ("lgdt", gdt_base),
+    ("cs", 0x08),
+    ("ss", 0x10),
+    ("ds", 0x10),
+    ("es", 0x10),
+    ("ltr", 0x28),
+    ("lidt", idt_base),
+]);
+```
+
+A GDT without the standard layout is rejected:
+```rust
+use libcpu::{
+    gdt::{install_descriptor_tables, GDTDescriptor, GlobalDescriptorTable, SystemSegmentType},
+    interrupts::InterruptDescriptorTable,
+    CpuError, PrivilegeLevel,
+};
+
+let gdt = Box::leak(Box::new(GlobalDescriptorTable::new()));
+gdt.push(GDTDescriptor::data_segment(PrivilegeLevel::KernelSpace));
+let idt = Box::leak(Box::new(InterruptDescriptorTable::default()));
+let tss = GDTDescriptor::system_segment(
+    0x1000,
+    0x67,
+    SystemSegmentType::AvailableTSS,
+    PrivilegeLevel::KernelSpace,
+);
+
+let result = unsafe { install_descriptor_tables(gdt, idt, tss) };
+assert_eq!(result, Err(CpuError::InvalidSelector(0x08)));
+```
+"#)]
+///
+/// # Safety
+/// The caller must ensure, that the TSS lives for the rest of the program and that the currently
+/// executed code is mapped in the segments of the GDT.
+///
+/// # Panics
+/// This function panics, if the GDT has no capacity for the TSS descriptor.
+///
+/// # See also
+/// - [GDT Tutorial](https://wiki.osdev.org/GDT_Tutorial) by [OSDev.org](https://wiki.osdev.org)
+/// - [Task State Segment](https://wiki.osdev.org/Task_State_Segment) by
+/// [OSDev.org](https://wiki.osdev.org)
+pub unsafe fn install_descriptor_tables(
+    gdt: &'static mut GlobalDescriptorTable, idt: &'static InterruptDescriptorTable,
+    tss: SystemSegmentDescriptor
+) -> Result<Selectors, CpuError> {
+    let layout = [
+        (PrivilegeLevel::KernelSpace, true),
+        (PrivilegeLevel::KernelSpace, false),
+        (PrivilegeLevel::UserSpace, false),
+        (PrivilegeLevel::UserSpace, true),
+    ];
+    let mut selectors = [SegmentSelector::default(); 4];
+    for (index, (privilege, executable)) in layout.into_iter().enumerate() {
+        let selector = SegmentSelector::new(index as u16 + 1, DescriptorTable::GDT, privilege);
+        let valid = gdt.get(selector.index()).map_or(false, |descriptor| {
+            let access = descriptor.access_flags();
+            access.contains(Access::PRESENT | Access::USER_SEGMENT)
+                && access.contains(Access::EXECUTABLE) == executable
+                && descriptor.privilege_level() == privilege
+        });
+        if !valid {
+            return Err(CpuError::InvalidSelector(selector.0));
+        }
+        selectors[index] = selector;
+    }
+
+    let tss = gdt.push_system(tss).expect("The GDT has no capacity for the TSS descriptor");
+    let gdt: &'static GlobalDescriptorTable = gdt;
+    gdt.load();
+
+    let [kernel_code, kernel_data, user_data, user_code] = selectors;
+    set_cs(kernel_code);
+    set_ss(kernel_data);
+    set_ds(kernel_data);
+    set_es(kernel_data);
+
+    load_task_register(tss);
+    idt.load();
+    Ok(Selectors {
+        kernel_code,
+        kernel_data,
+        user_data,
+        user_code,
+        tss: Some(tss),
+    })
+}
+
+/// This constant represents the entry of the Interrupt Stack Table in the TSS, that is used by
+/// [setup_double_fault_stack] for the Double Fault handler. The gate of the handler references the
+/// entry with the IST index `DOUBLE_FAULT_IST_SLOT + 1`.
+#[cfg(target_arch = "x86_64")]
+pub const DOUBLE_FAULT_IST_SLOT: usize = 0;
+
+/// This function installs a separate stack for the Double Fault handler and installs the
+/// descriptor tables afterwards. Without a separate stack, a stack overflow in the kernel raises a
+/// Page Fault, that can't push the interrupt stack frame, so the Double Fault can't be delivered
+/// either and the processor triple faults.
+///
+/// The tables are prepared before anything is loaded into the processor:
+/// 1. The stack top is stored in the IST entry [DOUBLE_FAULT_IST_SLOT] of the TSS
+/// 2. The gate of the Double Fault (vector 8) is pointed at this IST entry
+/// 3. The tables are installed with [install_descriptor_tables], so the TSS is loaded before the
+/// IDT
+///
+/// The handler of the Double Fault must be installed in the IDT before or after this function,
+/// because only the IST index of the gate is modified. The configured IDT and TSS are returned
+/// with the selectors, because the mutable references are consumed by this function.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{
+    gdt::{setup_double_fault_stack, GlobalDescriptorTable, TaskStateSegment},
+    gdt::DOUBLE_FAULT_IST_SLOT,
+    interrupts::{InterruptDescriptorTable, DOUBLE_FAULT},
+};
+
+let gdt = Box::leak(Box::new(GlobalDescriptorTable::standard_kernel().0));
+let idt = Box::leak(Box::new(InterruptDescriptorTable::default()));
+let tss = Box::leak(Box::new(TaskStateSegment::new()));
+
+let stack_top = 0xFFFF_8000_0010_0000;
+let (selectors, idt, tss) = unsafe { setup_double_fault_stack(gdt, idt, tss, stack_top) }.unwrap();
+assert_eq!(selectors.tss.unwrap().index(), 5);
+
+let ist_index = idt.get(DOUBLE_FAULT).ist_index();
+assert_eq!(usize::from(ist_index), DOUBLE_FAULT_IST_SLOT + 1);
+assert_eq!({ tss.interrupt_stack_table }[usize::from(ist_index) - 1], stack_top);
+```
+"#)]
+///
+/// # Safety
+/// The caller must ensure, that the stack is mapped, that no other code uses the stack and that
+/// the currently executed code is mapped in the segments of the GDT.
+///
+/// # Panics
+/// This function panics, if the GDT has no capacity for the TSS descriptor.
+///
+/// # See also
+/// - [Double Fault](https://wiki.osdev.org/Exceptions#Double_Fault) by
+/// [OSDev.org](https://wiki.osdev.org)
+/// - [Task State Segment](https://wiki.osdev.org/Task_State_Segment) by
+/// [OSDev.org](https://wiki.osdev.org)
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn setup_double_fault_stack(
+    gdt: &'static mut GlobalDescriptorTable, idt: &'static mut InterruptDescriptorTable,
+    tss: &'static mut TaskStateSegment, stack_top: MemoryAddress
+) -> Result<(Selectors, &'static InterruptDescriptorTable, &'static TaskStateSegment), CpuError> {
+    tss.interrupt_stack_table[DOUBLE_FAULT_IST_SLOT] = stack_top;
+    idt.set_ist_index(crate::interrupts::DOUBLE_FAULT, DOUBLE_FAULT_IST_SLOT as u8 + 1);
+
+    let idt: &'static InterruptDescriptorTable = idt;
+    let tss: &'static TaskStateSegment = tss;
+    let selectors = install_descriptor_tables(gdt, idt, tss.descriptor())?;
+    Ok((selectors, idt, tss))
 }
@@ -36,25 +36,85 @@
 //! [HackerNoon.com](https://hackernoon.com/)
 
 use crate::{
+    get_cr2,
     get_cs,
+    read_idtr,
+    CpuError,
+    DescriptorTable,
     DescriptorTablePointer,
     MemoryAddress,
     PrivilegeLevel,
     SegmentSelector,
 };
+use bit_field::BitField;
 use core::{
     arch::asm,
+    fmt,
     mem::size_of,
+    ptr,
+    slice,
+    sync::atomic::{
+        AtomicPtr,
+        Ordering,
+    },
 };
 
 pub type HandlerFunction = extern "x86-interrupt" fn(InterruptStackFrame);
 
+/// This type represents a handler for the exceptions, that are pushing an error code on the stack
+/// (see [EXCEPTION_HAS_ERROR_CODE]). The error code is passed as second parameter.
+pub type HandlerFunctionWithErrorCode = extern "x86-interrupt" fn(InterruptStackFrame, u64);
+
 pub extern "x86-interrupt" fn default_interrupt_handler(stack_frame: InterruptStackFrame) {
     unsafe {
         stack_frame.ret();
     }
 }
 
+/// This function returns a pointer to an empty Interrupt Descriptor Table with a limit of zero.
+/// After loading this pointer, every interrupt or exception can't be delivered by the CPU.
+///
+/// ```rust
+/// use libcpu::interrupts::null_idt_pointer;
+///
+/// let pointer = null_idt_pointer();
+/// assert_eq!({ pointer.size }, 0);
+/// assert_eq!({ pointer.base }, 0);
+/// ```
+///
+/// # See also
+/// - [triple_fault] (Source Code)
+#[inline]
+#[must_use]
+pub fn null_idt_pointer() -> DescriptorTablePointer {
+    DescriptorTablePointer { size: 0, base: 0 }
+}
+
+/// This function resets the CPU by forcing a triple fault. The function loads an empty IDT (see
+/// [null_idt_pointer]) and triggers a breakpoint exception. The CPU can't deliver the exception and
+/// the resulting double fault, so the CPU is reset by the hardware.
+///
+/// This is a last-resort reset mechanism for bare metal systems, when other reset mechanisms (like
+/// ACPI or the keyboard controller) are not available. No cleanup is done before the reset.
+///
+/// ```no_run
+/// use libcpu::interrupts::triple_fault;
+/// triple_fault();
+/// ```
+///
+/// # See also
+/// - [Triple Fault](https://wiki.osdev.org/Triple_Fault) by [OSDev.org](https://wiki.osdev.org/)
+pub fn triple_fault() -> ! {
+    unsafe {
+        asm!(
+            "lidt [{}]",
+            "int3",
+            in(reg) &null_idt_pointer(),
+            options(noreturn)
+        );
+    }
+}
+
 /// This enum describes the types of gates that interrupt descriptors are able to represent. I don't
 /// included the Task Gate, because that Gate can lead into a GP exception or is poorly optimized or
 /// entirely removed. ([Resource](https://wiki.osdev.org/Interrupt_Descriptor_Table#Task_Gate))
@@ -68,6 +128,7 @@ pub extern "x86-interrupt" fn default_interrupt_handler(stack_frame: InterruptSt
 /// - [Interrupt Service Routines](https://wiki.osdev.org/Interrupt_Service_Routines) by
 /// [OSDev.org](https://wiki.osdev.org/)
 #[repr(u8)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
 pub enum GateType {
     /// Interrupt gates are used to specify an ISR. Interrupt gates are automatically deactivating
     /// interrupts and reactivating them upon a `iret`.
@@ -92,6 +153,10 @@ pub struct InterruptStackFrame {
 
 impl InterruptStackFrame {
     /// This function calls the iretq instruction to recover the CPU state
+    ///
+    /// # Safety
+    /// The values of the stack frame are restored into the CPU without any validation, so the
+    /// caller has to ensure, that the frame describes a valid state to return to.
     #[inline(always)]
     #[cfg(target_arch = "x86_64")]
     pub unsafe fn ret(&self) -> ! {
@@ -114,6 +179,60 @@ impl InterruptStackFrame {
     }
 }
 
+/// This function returns from an interrupt handler with the `iretq` instruction. It's used by
+/// hand-written handlers, that are not using the `x86-interrupt` ABI. When this function is
+/// entered, the stack pointer must point to the [InterruptStackFrame] pushed by the CPU:
+/// ```text
+/// rsp + 0x00 -> Instruction Pointer
+/// rsp + 0x08 -> Code Segment
+/// rsp + 0x10 -> CPU Flags (RFLAGS)
+/// rsp + 0x18 -> Stack Pointer
+/// rsp + 0x20 -> Stack Segment
+/// ```
+/// For exceptions with an error code, the error code must be removed from the stack before. This
+/// function has no prologue, so it must be entered with a `jmp` after the epilogue of the handler
+/// has restored the registers. A `call` would push the return address on top of the frame.
+///
+/// ```rust,no_run
+/// #![feature(naked_functions)]
+/// use libcpu::interrupts::{iretq, InterruptStackFrame};
+///
+/// extern "C" fn handle_timer(_stack_frame: &InterruptStackFrame) {}
+///
+/// #[naked]
+/// unsafe extern "C" fn timer_entry() -> ! {
+///     core::arch::asm!(
+///         // Prologue: Save the scratch registers and pass the frame to the handler
+///         "push rax", "push rcx", "push rdx", "push rsi", "push rdi",
+///         "push r8", "push r9", "push r10", "push r11",
+///         "lea rdi, [rsp + 9 * 8]",
+///         "call {handler}",
+///         // Epilogue: Restore the scratch registers, so the stack pointer points to the frame
+///         "pop r11", "pop r10", "pop r9", "pop r8",
+///         "pop rdi", "pop rsi", "pop rdx", "pop rcx", "pop rax",
+///         "jmp {iretq}",
+///         handler = sym handle_timer,
+///         iretq = sym iretq,
+///         options(noreturn)
+///     );
+/// }
+/// ```
+///
+/// # Safety
+/// The caller must ensure, that the stack pointer points to a valid [InterruptStackFrame], which
+/// describes a valid state to return to.
+///
+/// # See also
+/// - [IRET/IRETD/IRETQ](https://www.felixcloutier.com/x86/iret:iretd:iretq) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+/// - [Interrupt Service Routines](https://wiki.osdev.org/Interrupt_Service_Routines) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[naked]
+#[cfg(target_arch = "x86_64")]
+pub unsafe extern "C" fn iretq() -> ! {
+    asm!("iretq", options(noreturn));
+}
+
 /// This structure represents all available vector indexes of exceptions, provided by the
 /// architecture/CPU. We didn't included the exceptions 'Coprocessor Segment Overrun' and
 /// 'FPU Error Interrupt' because they seems to be deprecated.
@@ -210,7 +329,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Division_Error) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    Division = 0x0,
+    Division = DIVIDE_ERROR,
 
     /// This exception occurs under different conditions. If the exception is a fault, the
     /// instruction pointer points to the exception-causing instruction. Otherwise the save
@@ -227,7 +346,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Debug) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    Debug = 0x1,
+    Debug = DEBUG,
 
     /// This interrupt occurs for unrecoverable hardware problems. Newer computers are handling
     /// these thins over machine check exceptions etc.
@@ -237,7 +356,7 @@ pub enum Exception {
     /// # See also
     /// - [Non-Maskable Interrupt (NMI)](https://wiki.osdev.org/Non_Maskable_Interrupt) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    NonMaskableInterrupt = 0x2,
+    NonMaskableInterrupt = NON_MASKABLE_INTERRUPT,
 
     /// This exception occurs when the CPU hits a INT3 instruction. The saved instruction pointer
     /// points to the byte after the INT3 instruction.
@@ -247,7 +366,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Breakpoint) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    Breakpoint = 0x3,
+    Breakpoint = BREAKPOINT,
 
     /// This exception occurs when `INTO` instruction is executed and the Overflow Bet is set. The
     /// saved instruction pointer points to the instruction after the exception-causing instruction.
@@ -257,7 +376,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Overflow) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    Overflow = 0x4,
+    Overflow = OVERFLOW,
 
     /// This exception occurs when an comparison on index with the lower and upper bounds of an array
     /// and the index is out of bounds. The instruction pointer points to the exception-causing
@@ -268,7 +387,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Bound_Range_Exceeded) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    BoundRangeExceeded = 0x5,
+    BoundRangeExceeded = BOUND_RANGE_EXCEEDED,
 
     /// This exception occur when the CPU hits an invalid or undefined opcode or the instruction
     /// length exceeds 15 bytes, or the instruction tries to access a non-existent control register
@@ -280,7 +399,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Invalid_Opcode) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    InvalidOpcode = 0x6,
+    InvalidOpcode = INVALID_OPCODE,
 
     /// This exception occurs when an FPU instruction is attempted but there is no FPU. However, t
     /// here are flags in the CR0 register that disable the FPU/MMX/SSE instructions, causing this
@@ -294,7 +413,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Device_Not_Available) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    DeviceNotAvailable = 0x7,
+    DeviceNotAvailable = DEVICE_NOT_AVAILABLE,
 
     /// This exception occurs when an exception is unhandled. Normally, two exception at the same
     /// time are handles one after another, but in some cases that is impossible. The saved
@@ -306,7 +425,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Double_Fault) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    DoubleFault = 0x8,
+    DoubleFault = DOUBLE_FAULT,
 
     /// This exception occurs when an illegal segment selector is referenced as part of a task
     /// switch. When the exception occurred before loading the segment selectors from the TSS, the
@@ -318,7 +437,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Invalid_TTS) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    InvalidTSS = 0xA,
+    InvalidTSS = INVALID_TSS,
 
     /// This exception occurs when the CPU tries to load a segment or gate that doesn't have set
     /// the Present bit. If the exception happens during a hardware task switch, the segment values
@@ -330,7 +449,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Segment_Not_Present) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    SegmentNotPresent = 0xB,
+    SegmentNotPresent = SEGMENT_NOT_PRESENT,
 
     /// This exception occurs when the CPU tries to load a not-present segment descriptor, or the
     /// stack address is not in a canonical form, or the stack limit check fails. The saved
@@ -346,7 +465,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Stack-Segment_Fault) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    StackSegmentFault = 0xC,
+    StackSegmentFault = STACK_SEGMENT_FAULT,
 
     /// This exception occurs for various reasons. The saved instruction pointer points to the
     /// instruction which caused the exception. The following text shows a list of the most common
@@ -362,7 +481,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#General_Protection_Fault) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    GeneralProtectionFault = 0xD,
+    GeneralProtectionFault = GENERAL_PROTECTION_FAULT,
 
     /// This exception occurs when a page directory or table entry is not present in the physical
     /// memory, or the CPU attempts to load a instruction TLB with a translation for a
@@ -370,12 +489,12 @@ pub enum Exception {
     /// or table entries is set to 1. The saved instruction pointer points to the exception-causing
     /// instruction.
     ///
-    /// **- Error Code: No**
+    /// **- Error Code: Yes (The Page Fault error code, the faulting address is stored in CR2)**
     ///
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Page_Fault) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    PageFault = 0xE,
+    PageFault = PAGE_FAULT,
 
     /// This exception occurs when the `FWAIT` or `WAIT` instruction or any floating-point waiting
     /// instruction is executed and CR0.EN is zero and an unmasked x87 floating-point exception is
@@ -388,19 +507,19 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#x87_Floating-Point_Exception) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    X87FloatingPoint = 0x10,
+    X87FloatingPoint = X87_FLOATING_POINT,
 
     /// This exception occurs when alignment checking is enabled and an unaligned memory data is
     /// referenced. Alignment checking is only performed in CPU privilege level
     /// [crate::PrivilegeLevel::UserSpace]. The saved instruction pointer points to the
     /// exception-causing instruction.
     ///
-    /// **- Error Code: No**
+    /// **- Error Code: Yes (Zero)**
     ///
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Alignment_Check) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    AlignmentCheck = 0x17,
+    AlignmentCheck = ALIGNMENT_CHECK,
 
     /// This exception occurs when the processor detects internal errors, lik bad memory, bus errors
     /// etc. The value of the saved instruction pointer depends on the implementation and the
@@ -411,7 +530,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#Machine_Check) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    MachineCheck = 0x18,
+    MachineCheck = MACHINE_CHECK,
 
     /// This exception occurs when an unmasked 128-bit media floating-point exception occurs and the
     /// CR4.OSXMMEXCPT bit is set. If not, then these exceptions will cause a
@@ -423,7 +542,7 @@ pub enum Exception {
     /// # See also
     /// - [Exceptions](https://wiki.osdev.org/Exceptions#SIMD_Floating-Point_Exception) by
     /// [OSDev.org](https://wiki.osdev.org/)
-    SIMDFloatingPoint = 0x19,
+    SIMDFloatingPoint = SIMD_FLOATING_POINT,
 
     /// This exception occurs when a EPT violation in VMX non-root operations occurs. Not al EPT
     /// violations cause virtualization exceptions. The handler can recover from these exception
@@ -436,7 +555,7 @@ pub enum Exception {
     /// # See also
     /// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
     /// Chapter 6.15 (Interrupt 20) by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
-    Virtualization = 0x14,
+    Virtualization = VIRTUALIZATION,
 
     /// This exception occurs when a control flow transfer that violates the Intel CET (Control Flow
     /// Enforcement Technology) or other Shadow Stack implementations was indicated. The saved
@@ -466,7 +585,7 @@ pub enum Exception {
     /// Chapter 8.2.20 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
     /// - [AMD64 Architecture Programmer's Manual Volume 2](https://www.amd.com/content/dam/amd/en/documents/processor-tech-docs/programmer-references/24593.pdf)
     /// Chapter 8.4.3 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
-    ControlProtection = 0x21,
+    ControlProtection = CONTROL_PROTECTION,
 
     /// This exception occurs when the hypervisor into a secure guest VM to notify the VM of pending
     /// events. (This fault is only available on AMD CPUs with SEV-SNP)
@@ -483,7 +602,7 @@ pub enum Exception {
     /// Chapter 8.2.21 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
     /// - [AMD64 Architecture Programmer's Manual Volume 2](https://www.amd.com/content/dam/amd/en/documents/processor-tech-docs/programmer-references/24593.pdf)
     /// Chapter 15.36.16 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
-    HypervisorInjection = 0x1C,
+    HypervisorInjection = HYPERVISOR_INJECTION,
 
     /// This exception occurs when an SEV-ES enabled guest is running and a NAE event occurs. (This
     /// fault is only available on AMD CPUs with AMD SEV-ES)
@@ -495,7 +614,7 @@ pub enum Exception {
     /// Chapter 8.2.22 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
     /// - [AMD64 Architecture Programmer's Manual Volume 2](https://www.amd.com/content/dam/amd/en/documents/processor-tech-docs/programmer-references/24593.pdf)
     /// Chapter 15.35.5 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
-    VMMCommunication = 0x1D,
+    VMMCommunication = VMM_COMMUNICATION,
 
     /// This exception occurs when security-sensitive events under SVM are occurring. The current
     /// only use for this exception is to send external INITS into an exception so the VMM can
@@ -508,7 +627,7 @@ pub enum Exception {
     /// Chapter 8.2.23 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
     /// - [AMD64 Architecture Programmer's Manual Volume 2](https://www.amd.com/content/dam/amd/en/documents/processor-tech-docs/programmer-references/24593.pdf)
     /// Chapter 15.28 by [Advanced Micro Devices, Inc.](https://www.amd.com/en.html)
-    Security = 0x30,
+    Security = SECURITY,
 }
 
 impl From<Exception> for usize {
@@ -518,6 +637,145 @@ impl From<Exception> for usize {
     }
 }
 
+/// This constant represents the first vector, that isn't reserved for the CPU exceptions. The
+/// vectors from this vector up to 255 are available for the device interrupts (IRQs) and the
+/// software interrupts.
+pub const FIRST_USER_VECTOR: u8 = 32;
+
+/// This constant represents the vector of the [Exception::Division] exception.
+pub const DIVIDE_ERROR: u8 = 0;
+
+/// This constant represents the vector of the [Exception::Debug] exception.
+pub const DEBUG: u8 = 1;
+
+/// This constant represents the vector of the [Exception::NonMaskableInterrupt] exception.
+pub const NON_MASKABLE_INTERRUPT: u8 = 2;
+
+/// This constant represents the vector of the [Exception::Breakpoint] exception.
+pub const BREAKPOINT: u8 = 3;
+
+/// This constant represents the vector of the [Exception::Overflow] exception.
+pub const OVERFLOW: u8 = 4;
+
+/// This constant represents the vector of the [Exception::BoundRangeExceeded] exception.
+pub const BOUND_RANGE_EXCEEDED: u8 = 5;
+
+/// This constant represents the vector of the [Exception::InvalidOpcode] exception.
+pub const INVALID_OPCODE: u8 = 6;
+
+/// This constant represents the vector of the [Exception::DeviceNotAvailable] exception.
+pub const DEVICE_NOT_AVAILABLE: u8 = 7;
+
+/// This constant represents the vector of the [Exception::DoubleFault] exception.
+pub const DOUBLE_FAULT: u8 = 8;
+
+/// This constant represents the vector of the [Exception::InvalidTSS] exception.
+pub const INVALID_TSS: u8 = 10;
+
+/// This constant represents the vector of the [Exception::SegmentNotPresent] exception.
+pub const SEGMENT_NOT_PRESENT: u8 = 11;
+
+/// This constant represents the vector of the [Exception::StackSegmentFault] exception.
+pub const STACK_SEGMENT_FAULT: u8 = 12;
+
+/// This constant represents the vector of the [Exception::GeneralProtectionFault] exception.
+pub const GENERAL_PROTECTION_FAULT: u8 = 13;
+
+/// This constant represents the vector of the [Exception::PageFault] exception.
+pub const PAGE_FAULT: u8 = 14;
+
+/// This constant represents the vector of the [Exception::X87FloatingPoint] exception.
+pub const X87_FLOATING_POINT: u8 = 16;
+
+/// This constant represents the vector of the [Exception::AlignmentCheck] exception.
+pub const ALIGNMENT_CHECK: u8 = 17;
+
+/// This constant represents the vector of the [Exception::MachineCheck] exception.
+pub const MACHINE_CHECK: u8 = 18;
+
+/// This constant represents the vector of the [Exception::SIMDFloatingPoint] exception.
+pub const SIMD_FLOATING_POINT: u8 = 19;
+
+/// This constant represents the vector of the [Exception::Virtualization] exception.
+pub const VIRTUALIZATION: u8 = 20;
+
+/// This constant represents the vector of the [Exception::ControlProtection] exception.
+pub const CONTROL_PROTECTION: u8 = 21;
+
+/// This constant represents the vector of the [Exception::HypervisorInjection] exception.
+pub const HYPERVISOR_INJECTION: u8 = 28;
+
+/// This constant represents the vector of the [Exception::VMMCommunication] exception.
+pub const VMM_COMMUNICATION: u8 = 29;
+
+/// This constant represents the vector of the [Exception::Security] exception.
+pub const SECURITY: u8 = 30;
+
+/// This table contains whether the CPU pushes an error code on the stack for the exception with the
+/// vector as index. A handler for these exceptions must be a [HandlerFunctionWithErrorCode],
+/// otherwise the stack is corrupted when the handler returns.
+///
+/// ```rust
+/// use libcpu::interrupts::{EXCEPTION_HAS_ERROR_CODE, DIVIDE_ERROR, PAGE_FAULT};
+///
+/// assert!(EXCEPTION_HAS_ERROR_CODE[PAGE_FAULT as usize]);
+/// assert!(!EXCEPTION_HAS_ERROR_CODE[DIVIDE_ERROR as usize]);
+/// ```
+pub const EXCEPTION_HAS_ERROR_CODE: [bool; FIRST_USER_VECTOR as usize] = {
+    let mut table = [false; FIRST_USER_VECTOR as usize];
+    table[DOUBLE_FAULT as usize] = true;
+    table[INVALID_TSS as usize] = true;
+    table[SEGMENT_NOT_PRESENT as usize] = true;
+    table[STACK_SEGMENT_FAULT as usize] = true;
+    table[GENERAL_PROTECTION_FAULT as usize] = true;
+    table[PAGE_FAULT as usize] = true;
+    table[ALIGNMENT_CHECK as usize] = true;
+    table[CONTROL_PROTECTION as usize] = true;
+    table[VMM_COMMUNICATION as usize] = true;
+    table[SECURITY as usize] = true;
+    table
+};
+
+/// This function returns whether the CPU pushes an error code on the stack for the specified
+/// vector. Only some CPU exceptions are pushing an error code.
+#[inline]
+#[must_use]
+pub const fn has_error_code(vector: u8) -> bool {
+    is_exception_vector(vector) && EXCEPTION_HAS_ERROR_CODE[vector as usize]
+}
+
+/// This function returns whether the specified vector is reserved for the CPU exceptions (vectors
+/// 0 to 31). These vectors must not be used for device interrupts.
+///
+/// ```rust
+/// use libcpu::interrupts::{is_exception_vector, PAGE_FAULT};
+///
+/// assert!(is_exception_vector(PAGE_FAULT));
+/// assert!(is_exception_vector(31));
+/// assert!(!is_exception_vector(32));
+/// ```
+#[inline]
+#[must_use]
+pub const fn is_exception_vector(vector: u8) -> bool {
+    vector < FIRST_USER_VECTOR
+}
+
+/// This function returns whether the specified vector is available for device and software
+/// interrupts (vectors 32 to 255).
+///
+/// ```rust
+/// use libcpu::interrupts::{is_user_vector, DOUBLE_FAULT};
+///
+/// assert!(!is_user_vector(DOUBLE_FAULT));
+/// assert!(is_user_vector(32));
+/// assert!(is_user_vector(255));
+/// ```
+#[inline]
+#[must_use]
+pub const fn is_user_vector(vector: u8) -> bool {
+    !is_exception_vector(vector)
+}
+
 /// This structure implements a single descriptor in the IDT (Interrupt Descriptor Table). This
 /// structure is compatible with the raw memory representation of a descriptor. The implementation
 /// of the IDT is only needed for IA-32 and x86_64 architectures.
@@ -548,28 +806,151 @@ pub struct IDTDescriptor {
     reserved: u32,
 }
 
-impl IDTDescriptor {
-    pub fn default() -> Self {
+impl Default for IDTDescriptor {
+    fn default() -> Self {
         IDTDescriptor::new(
             default_interrupt_handler,
             GateType::Trap,
             PrivilegeLevel::KernelSpace,
         )
     }
+}
 
+impl IDTDescriptor {
     pub fn new(
         handler_address: HandlerFunction, gate_type: GateType, privilege_level: PrivilegeLevel
     ) -> Self {
+        Self::with_address(handler_address as usize as u64, gate_type, privilege_level)
+    }
+
+    /// This function creates a new gate for the specified handler, which is called with the error
+    /// code of the exception.
+    #[cfg_attr(feature = "mock", doc = r#"
+```rust
+#![feature(abi_x86_interrupt)]
+use libcpu::{
+    interrupts::{GateType, IDTDescriptor, InterruptStackFrame},
+    set_cs, PrivilegeLevel, SegmentSelector,
+};
+
+extern "x86-interrupt" fn page_fault(_frame: InterruptStackFrame, _error_code: u64) {}
+
+set_cs(SegmentSelector(0x08));
+let gate =
+    IDTDescriptor::with_error_code(page_fault, GateType::Interrupt, PrivilegeLevel::KernelSpace);
+let address = (page_fault as usize as u64).to_le_bytes();
+let bytes: [u8; 16] = unsafe { core::mem::transmute(gate) };
+assert_eq!(bytes, [
+    address[0], address[1], 0x08, 0x00, 0x00, 0x8E, address[2], address[3],
+    address[4], address[5], address[6], address[7], 0x00, 0x00, 0x00, 0x00,
+]);
+```
+"#)]
+    pub fn with_error_code(
+        handler_address: HandlerFunctionWithErrorCode, gate_type: GateType,
+        privilege_level: PrivilegeLevel
+    ) -> Self {
+        Self::with_address(handler_address as usize as u64, gate_type, privilege_level)
+    }
+
+    fn with_address(
+        handler_address: u64, gate_type: GateType, privilege_level: PrivilegeLevel
+    ) -> Self {
+        let mut flags = 0b1000_0000 | (gate_type as u8);
+        flags.set_bits(5..7, privilege_level.as_ring());
         Self {
             lower_isr_address: handler_address as u16,
             segment_selector: get_cs(),
             always0: 0,
-            flags: 0b1000_0000 | (privilege_level as u8) | (gate_type as u8),
-            middle_isr_address: (handler_address as u64 >> 16) as u16,
-            higher_isr_address: (handler_address as u64 >> 32) as u32,
+            flags,
+            middle_isr_address: (handler_address >> 16) as u16,
+            higher_isr_address: (handler_address >> 32) as u32,
             reserved: 0,
         }
     }
+
+    /// This function replaces the privilege level of the gate with the function-specific privilege
+    /// level.
+    #[inline]
+    pub fn set_privilege_level(&mut self, level: PrivilegeLevel) {
+        self.flags.set_bits(5..7, level.as_ring());
+    }
+
+    /// This function replaces the index of the Interrupt Stack Table entry, that is used as stack
+    /// for the handler. The indices 1 to 7 are selecting the entries 0 to 6 of the IST in the TSS
+    /// and the index zero disables the stack switch with the IST. Indices above 7 are detected in
+    /// debug builds.
+    #[inline]
+    pub fn set_ist_index(&mut self, index: u8) {
+        debug_assert!(index < 8, "The IST index {} is out of range", index);
+        self.always0.set_bits(0..3, index);
+    }
+
+    /// This function replaces the code segment selector, that is loaded when the handler is called.
+    /// By default, the gate uses the code segment, that was active when the gate was created.
+    #[inline]
+    pub fn set_segment_selector(&mut self, selector: SegmentSelector) {
+        self.segment_selector = selector;
+    }
+
+    /// This function sets or clears the present bit of the gate. The offset, the segment selector
+    /// and the other flags are left unchanged, so the gate can be restored later.
+    #[inline]
+    pub fn set_present(&mut self, present: bool) {
+        self.flags.set_bit(7, present);
+    }
+
+    /// This function returns whether the present bit of the gate is set.
+    #[inline]
+    #[must_use]
+    pub fn present(&self) -> bool {
+        self.flags.get_bit(7)
+    }
+
+    /// This function returns the type of the gate. If the type nibble doesn't identify an
+    /// interrupt or trap gate, this function returns [None].
+    #[inline]
+    #[must_use]
+    pub fn gate_type(&self) -> Option<GateType> {
+        match self.flags.get_bits(0..4) {
+            0xE => Some(GateType::Interrupt),
+            0xF => Some(GateType::Trap),
+            _ => None,
+        }
+    }
+
+    /// This function returns the privilege level of the gate (DPL). Software interrupts are only
+    /// allowed from this or a more privileged level.
+    #[inline]
+    #[must_use]
+    pub fn privilege_level(&self) -> PrivilegeLevel {
+        PrivilegeLevel::from_ring_bits(self.flags.get_bits(5..7))
+    }
+
+    /// This function returns the index of the Interrupt Stack Table entry, that is used as stack
+    /// for the handler. The index zero means that the stack isn't switched with the IST.
+    #[inline]
+    #[must_use]
+    pub fn ist_index(&self) -> u8 {
+        self.always0.get_bits(0..3)
+    }
+
+    /// This function returns the code segment selector, that is loaded when the handler is called.
+    #[inline]
+    #[must_use]
+    pub fn segment_selector(&self) -> SegmentSelector {
+        self.segment_selector
+    }
+
+    /// This function returns the address of the handler, which is reconstructed from the three
+    /// parts of the offset in the descriptor.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        u64::from(self.lower_isr_address)
+            | (u64::from(self.middle_isr_address) << 16)
+            | (u64::from(self.higher_isr_address) << 32)
+    }
 }
 
 /// This structure represents the Interrupt Descriptor Table with the maximum of 256 entries. In #
@@ -592,31 +973,437 @@ pub struct InterruptDescriptorTable {
     descriptors: [IDTDescriptor; 256],
 }
 
-impl InterruptDescriptorTable {
-    #[must_use]
-    pub fn default() -> Self {
+impl Default for InterruptDescriptorTable {
+    fn default() -> Self {
         Self {
             descriptors: [IDTDescriptor::default(); 256],
         }
     }
+}
+
+impl InterruptDescriptorTable {
+    /// This function creates a new IDT, in which the exception vectors (0 to 31) are pointing to
+    /// minimal handlers. These handlers are passing a description of the exception (vector, error
+    /// code and instruction pointer) to the specified sink and halt the processor afterwards. So an
+    /// unhandled exception leads into a diagnostic instead of a triple fault.
+    ///
+    /// The sink is called with the formatted message, so it can forward the message to any output
+    /// (like a serial port or the framebuffer) without allocation. The sink is shared by all
+    /// tables created with this function. The structured [ExceptionInfo] (including the faulting
+    /// address of Page Faults) is passed to the hook registered with [set_exception_hook] before.
+    ///
+    /// ```rust
+    /// use core::fmt::Arguments;
+    /// use libcpu::interrupts::{default_interrupt_handler, InterruptDescriptorTable};
+    ///
+    /// fn serial_sink(_message: Arguments) {
+    ///     // Write the message to the serial port
+    /// }
+    ///
+    /// let table = InterruptDescriptorTable::with_panic_stubs(serial_sink);
+    /// for vector in 0..32 {
+    ///     assert!(table.get(vector).present());
+    ///     assert_ne!(table.get(vector).offset(), default_interrupt_handler as usize as u64);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_panic_stubs(sink: ExceptionSink) -> Self {
+        EXCEPTION_SINK.store(sink as *mut (), Ordering::Release);
+
+        let mut table = Self::default();
+        macro_rules! install_panic_stubs {
+            ($($vector: literal),*) => {
+                $(
+                if has_error_code($vector) {
+                    let handler = panic_stub_with_error_code::<$vector>;
+                    table.set_handler_with_error_code($vector, handler);
+                } else {
+                    table.set_handler($vector, panic_stub::<$vector>);
+                }
+                )*
+            };
+        }
+
+        install_panic_stubs!(
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23,
+            24, 25, 26, 27, 28, 29, 30, 31
+        );
+        table
+    }
 
     /// This function generates a pointer to the IDT with the [InterruptDescriptorTable::as_ptr]
     /// function and loads it with the `lidt` instruction.
     ///
+    /// The CPU keeps using the table after it was loaded, so the table must live for the rest of
+    /// the program. This is enforced by the `'static` lifetime, so a table on the stack can't be
+    /// loaded. Reloading the table, that is already installed on the current CPU, is allowed.
+    ///
     /// # See also
     /// - [LGDT/LIDT](https://www.felixcloutier.com/x86/lgdt:lidt) by
     /// [Felix Clountier](https://www.felixcloutier.com)
-    pub fn load(&self) {
+    pub fn load(&'static self) {
+        #[cfg(feature = "mock")]
+        crate::mock::backend().execute("lidt", self.as_ptr().base);
+
+        #[cfg(not(feature = "mock"))]
         unsafe {
             asm!("lidt [{}]", in(reg) &self.as_ptr(), options(readonly, nostack, preserves_flags));
         }
     }
 
+    /// This function returns the descriptor at the specified vector in the IDT. The descriptor can
+    /// be decoded to verify, that a handler was installed correctly.
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::interrupts::{GateType, InterruptDescriptorTable, InterruptStackFrame};
+    ///
+    /// extern "x86-interrupt" fn page_fault_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_interrupt_gate(14_usize, page_fault_handler);
+    ///
+    /// let descriptor = table.get(14);
+    /// assert!(descriptor.present());
+    /// assert_eq!(descriptor.gate_type(), Some(GateType::Interrupt));
+    /// assert_eq!(descriptor.ist_index(), 0);
+    /// assert_eq!(descriptor.offset(), page_fault_handler as usize as u64);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get(&self, vector: u8) -> &IDTDescriptor {
+        &self.descriptors[usize::from(vector)]
+    }
+
     /// This function inserts a [IDTDescriptor] at the specified index in the IDT.
     pub fn insert<T: Into<usize>>(&mut self, index: T, descriptor: IDTDescriptor) {
         self.descriptors[index.into()] = descriptor;
     }
 
+    /// This function inserts an interrupt gate with the specified handler at the specified index
+    /// in the IDT. Interrupt gates are clearing the interrupt flag while the handler is running.
+    /// The gate is only accessible from the kernel, use [InterruptDescriptorTable::set_gate_dpl]
+    /// to allow software interrupts from other privilege levels.
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::{
+    ///     interrupts::{GateType, InterruptDescriptorTable, InterruptStackFrame},
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// extern "x86-interrupt" fn timer_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_interrupt_gate(32_u8, timer_handler);
+    /// assert_eq!(table.get(32).gate_type(), Some(GateType::Interrupt));
+    /// assert_eq!(GateType::Interrupt as u8, 0xE);
+    /// assert_eq!(table.get(32).privilege_level(), PrivilegeLevel::KernelSpace);
+    /// assert!(table.get(32).present());
+    /// ```
+    ///
+    /// # See also
+    /// - [GateType::Interrupt] (Source Code)
+    pub fn set_interrupt_gate<T: Into<usize>>(&mut self, index: T, handler: HandlerFunction) {
+        self.insert(
+            index,
+            IDTDescriptor::new(handler, GateType::Interrupt, PrivilegeLevel::KernelSpace),
+        );
+    }
+
+    /// This function inserts a trap gate with the specified handler at the specified index in the
+    /// IDT. Trap gates are leaving the interrupt flag unchanged while the handler is running. The
+    /// gate is only accessible from the kernel, use [InterruptDescriptorTable::set_gate_dpl] to
+    /// allow software interrupts from other privilege levels.
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::{
+    ///     interrupts::{GateType, InterruptDescriptorTable, InterruptStackFrame},
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// extern "x86-interrupt" fn breakpoint_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_trap_gate(3_u8, breakpoint_handler);
+    /// assert_eq!(table.get(3).gate_type(), Some(GateType::Trap));
+    /// assert_eq!(GateType::Trap as u8, 0xF);
+    /// assert_eq!(table.get(3).privilege_level(), PrivilegeLevel::KernelSpace);
+    /// assert!(table.get(3).present());
+    /// ```
+    ///
+    /// # See also
+    /// - [GateType::Trap] (Source Code)
+    pub fn set_trap_gate<T: Into<usize>>(&mut self, index: T, handler: HandlerFunction) {
+        self.insert(
+            index,
+            IDTDescriptor::new(handler, GateType::Trap, PrivilegeLevel::KernelSpace),
+        );
+    }
+
+    /// This function inserts an interrupt gate with the specified handler at the specified vector
+    /// in the IDT. The handler is called without an error code, so the vector must be a vector,
+    /// for which the CPU doesn't push an error code (see [EXCEPTION_HAS_ERROR_CODE]). Otherwise,
+    /// the stack is corrupted when the handler returns, so this is detected in debug builds.
+    ///
+    /// ```rust,should_panic
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::interrupts::{InterruptDescriptorTable, InterruptStackFrame, PAGE_FAULT};
+    ///
+    /// extern "x86-interrupt" fn handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_handler(PAGE_FAULT, handler); // The Page Fault pushes an error code
+    /// ```
+    pub fn set_handler(&mut self, vector: u8, handler: HandlerFunction) {
+        debug_assert!(
+            !has_error_code(vector),
+            "The vector {} requires a handler with error code",
+            vector
+        );
+        self.set_interrupt_gate(vector, handler);
+    }
+
+    /// This function inserts an interrupt gate with the specified handler at the specified vector
+    /// in the IDT. The handler is called with the error code, so the vector must be a CPU
+    /// exception, that pushes an error code (see [EXCEPTION_HAS_ERROR_CODE]). Otherwise, the stack
+    /// is corrupted when the handler returns, so this is detected in debug builds.
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::interrupts::{GateType, InterruptDescriptorTable, InterruptStackFrame};
+    /// use libcpu::interrupts::{DIVIDE_ERROR, PAGE_FAULT};
+    ///
+    /// extern "x86-interrupt" fn page_fault_handler(_frame: InterruptStackFrame, _code: u64) {}
+    /// extern "x86-interrupt" fn divide_error_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_handler_with_error_code(PAGE_FAULT, page_fault_handler);
+    /// table.set_handler(DIVIDE_ERROR, divide_error_handler);
+    /// assert_eq!(table.get(PAGE_FAULT).offset(), page_fault_handler as usize as u64);
+    /// assert_eq!(table.get(PAGE_FAULT).gate_type(), Some(GateType::Interrupt));
+    /// ```
+    pub fn set_handler_with_error_code(
+        &mut self, vector: u8, handler: HandlerFunctionWithErrorCode
+    ) {
+        debug_assert!(
+            has_error_code(vector),
+            "The vector {} doesn't push an error code",
+            vector
+        );
+        let privilege_level = PrivilegeLevel::KernelSpace;
+        self.insert(
+            vector,
+            IDTDescriptor::with_error_code(handler, GateType::Interrupt, privilege_level),
+        );
+    }
+
+    /// This function inserts an interrupt gate with the specified handler for a device interrupt
+    /// (IRQ) at the specified vector in the IDT. The vectors 0 to 31 are reserved for the CPU
+    /// exceptions, so installing a device handler on these vectors is detected in debug builds.
+    ///
+    /// ```rust,should_panic
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::interrupts::{InterruptDescriptorTable, InterruptStackFrame, PAGE_FAULT};
+    ///
+    /// extern "x86-interrupt" fn keyboard_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_device_handler(33, keyboard_handler);
+    /// table.set_device_handler(PAGE_FAULT, keyboard_handler); // The vector is reserved
+    /// ```
+    pub fn set_device_handler(&mut self, vector: u8, handler: HandlerFunction) {
+        debug_assert!(
+            is_user_vector(vector),
+            "The vector {} is reserved for the CPU exceptions",
+            vector
+        );
+        self.set_interrupt_gate(vector, handler);
+    }
+
+    /// This function replaces the privilege level of the gate at the specified index in the IDT.
+    /// Software interrupts with the `int n` instruction are only allowed if the current privilege
+    /// level is equal or more privileged than the gate's privilege level, so this function can be
+    /// used to allow the interrupt selectively for the user space (e.g. for system calls).
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::{
+    ///     interrupts::{GateType, InterruptDescriptorTable, InterruptStackFrame},
+    ///     PrivilegeLevel,
+    /// };
+    ///
+    /// extern "x86-interrupt" fn syscall_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_trap_gate(0x80_u8, syscall_handler);
+    /// table.set_gate_dpl(0x80_u8, PrivilegeLevel::UserSpace);
+    ///
+    /// let descriptor = table.get(0x80);
+    /// assert_eq!(descriptor.privilege_level(), PrivilegeLevel::UserSpace);
+    /// assert_eq!(descriptor.privilege_level().as_ring(), 3);
+    /// assert!(descriptor.present());
+    /// assert_eq!(descriptor.gate_type(), Some(GateType::Trap));
+    /// ```
+    ///
+    /// # See also
+    /// - [PrivilegeLevel] (Source Code)
+    pub fn set_gate_dpl<T: Into<usize>>(&mut self, index: T, level: PrivilegeLevel) {
+        self.descriptors[index.into()].set_privilege_level(level);
+    }
+
+    /// This function clears the present bit of the gate at the specified vector in the IDT. The
+    /// handler is kept in the descriptor, so the gate can be restored with
+    /// [InterruptDescriptorTable::enable]. If the vector fires while the gate is disabled, the CPU
+    /// raises a General Protection Fault (or a Segment Not Present exception), which is intended
+    /// to catch the vector while the handler is replaced.
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::interrupts::{GateType, InterruptDescriptorTable, InterruptStackFrame};
+    ///
+    /// extern "x86-interrupt" fn timer_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_device_handler(32, timer_handler);
+    /// let descriptor = *table.get(32);
+    ///
+    /// table.disable(32);
+    /// assert!(!table.get(32).present());
+    /// assert_eq!(table.get(32).offset(), timer_handler as usize as u64);
+    /// assert_eq!(table.get(32).gate_type(), Some(GateType::Interrupt));
+    ///
+    /// table.enable(32);
+    /// assert_eq!(*table.get(32), descriptor);
+    /// ```
+    #[inline]
+    pub fn disable(&mut self, vector: u8) {
+        self.descriptors[usize::from(vector)].set_present(false);
+    }
+
+    /// This function sets the present bit of the gate at the specified vector in the IDT again,
+    /// after it was cleared with [InterruptDescriptorTable::disable].
+    #[inline]
+    pub fn enable(&mut self, vector: u8) {
+        self.descriptors[usize::from(vector)].set_present(true);
+    }
+
+    /// This function replaces the index of the Interrupt Stack Table entry of the gate at the
+    /// specified index in the IDT. The CPU switches to the stack in this entry of the TSS before
+    /// the handler is called, which is required for handlers, that must run on a valid stack (like
+    /// the Double Fault handler after a stack overflow).
+    ///
+    /// # See also
+    /// - [IDTDescriptor::set_ist_index] (Source Code)
+    pub fn set_ist_index<T: Into<usize>>(&mut self, index: T, ist_index: u8) {
+        self.descriptors[index.into()].set_ist_index(ist_index);
+    }
+
+    /// This function replaces the code segment selector of the gate at the specified vector in the
+    /// IDT. By default, the gates are using the code segment, that was active when the handler was
+    /// set, so this function is used to run single handlers in another segment (like a trampoline
+    /// segment). Setting the handler again resets the selector, so the handler must be set first.
+    ///
+    /// If the selector is the null selector, the CPU raises a General Protection Fault when the
+    /// gate is used. In this case, [CpuError::InvalidSelector] is returned and the gate is not
+    /// modified.
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::{
+    ///     interrupts::{InterruptDescriptorTable, InterruptStackFrame},
+    ///     CpuError, DescriptorTable, PrivilegeLevel, SegmentSelector,
+    /// };
+    ///
+    /// extern "x86-interrupt" fn timer_handler(_stack_frame: InterruptStackFrame) {}
+    ///
+    /// let mut table = InterruptDescriptorTable::default();
+    /// table.set_device_handler(32, timer_handler);
+    /// let default_selector = table.get(32).segment_selector();
+    ///
+    /// let selector = SegmentSelector::new(6, DescriptorTable::GDT, PrivilegeLevel::KernelSpace);
+    /// assert_eq!(table.set_code_selector(32, selector), Ok(()));
+    /// assert_eq!(table.get(32).segment_selector(), selector);
+    /// assert_eq!(table.get(33).segment_selector(), default_selector);
+    ///
+    /// let null = SegmentSelector::new(0, DescriptorTable::GDT, PrivilegeLevel::KernelSpace);
+    /// assert_eq!(table.set_code_selector(32, null), Err(CpuError::InvalidSelector(0)));
+    /// assert_eq!(table.get(32).segment_selector(), selector);
+    /// ```
+    pub fn set_code_selector(&mut self, vector: u8, selector: SegmentSelector) -> Result<(), CpuError> {
+        if selector.index() == 0 && selector.table() == DescriptorTable::GDT {
+            return Err(CpuError::InvalidSelector(selector.0));
+        }
+
+        self.descriptors[usize::from(vector)].set_segment_selector(selector);
+        Ok(())
+    }
+
+    /// This function compares the handler offsets of the gates in this table with the specified
+    /// gates and returns the first vector, at which the offsets are different. If the specified
+    /// gates are fewer than 256, the first vector without a gate is returned. If all offsets are
+    /// matching, this function returns [None].
+    ///
+    /// ```rust
+    /// #![feature(abi_x86_interrupt)]
+    /// use libcpu::interrupts::{InterruptDescriptorTable, InterruptStackFrame, PAGE_FAULT};
+    ///
+    /// extern "x86-interrupt" fn page_fault(_frame: InterruptStackFrame, _error_code: u64) {}
+    /// extern "x86-interrupt" fn hook(_frame: InterruptStackFrame, _error_code: u64) {}
+    ///
+    /// let mut expected = InterruptDescriptorTable::default();
+    /// expected.set_handler_with_error_code(PAGE_FAULT, page_fault);
+    ///
+    /// let mut live = expected;
+    /// let gates = (0..=255).map(|vector| *live.get(vector)).collect::<Vec<_>>();
+    /// assert_eq!(expected.find_mismatch(&gates), None);
+    /// assert_eq!(expected.find_mismatch(&gates[..64]), Some(64));
+    ///
+    /// live.set_handler_with_error_code(PAGE_FAULT, hook);
+    /// let gates = (0..=255).map(|vector| *live.get(vector)).collect::<Vec<_>>();
+    /// assert_eq!(expected.find_mismatch(&gates), Some(PAGE_FAULT));
+    /// ```
+    #[must_use]
+    pub fn find_mismatch(&self, gates: &[IDTDescriptor]) -> Option<u8> {
+        self.descriptors
+            .iter()
+            .enumerate()
+            .find(|(vector, expected)| {
+                gates.get(*vector).map_or(true, |gate| gate.offset() != expected.offset())
+            })
+            .map(|(vector, _)| vector as u8)
+    }
+
+    /// This function reads the currently loaded IDT with the `sidt` instruction and compares the
+    /// handler offsets of its gates with this table (see [InterruptDescriptorTable::find_mismatch]).
+    /// The first vector, at which the loaded IDT differs from this table, is returned. So a copy of
+    /// the installed table can be used to detect hooked handlers (like by a rootkit).
+    ///
+    /// ```rust,no_run
+    /// use libcpu::interrupts::InterruptDescriptorTable;
+    ///
+    /// fn check_integrity(expected: &InterruptDescriptorTable) {
+    ///     if let Some(vector) = unsafe { expected.find_installed_mismatch() } {
+    ///         panic!("The handler of the vector {} was modified", vector);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    /// The caller must ensure, that the loaded IDT is mapped in the current address space.
+    ///
+    /// # See also
+    /// - [SIDT](https://www.felixcloutier.com/x86/sidt) by
+    /// [Felix Clountier](https://www.felixcloutier.com)
+    #[must_use]
+    pub unsafe fn find_installed_mismatch(&self) -> Option<u8> {
+        let pointer = read_idtr();
+        let count = (usize::from(pointer.size) + 1) / size_of::<IDTDescriptor>();
+        let gates = slice::from_raw_parts(pointer.base as *const IDTDescriptor, count.min(256));
+        self.find_mismatch(gates)
+    }
+
     /// This function generates a pointer to the Interrupt Descriptor Table (IDT) with the base
     /// address and the size of the IDT as limit.
     ///
@@ -631,3 +1418,302 @@ impl InterruptDescriptorTable {
         }
     }
 }
+
+/// This type represents the sink of the handlers installed by
+/// [InterruptDescriptorTable::with_panic_stubs]. The sink receives the formatted description of
+/// the unhandled exception.
+pub type ExceptionSink = fn(fmt::Arguments);
+
+/// This type represents the hook of the handlers installed by
+/// [InterruptDescriptorTable::with_panic_stubs], that is registered with [set_exception_hook]. The
+/// hook receives the structured [ExceptionInfo] of the unhandled exception before the formatted
+/// description is passed to the sink, so a crash dumper can access the raw values.
+pub type ExceptionHook = fn(&ExceptionInfo);
+
+static EXCEPTION_SINK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+static EXCEPTION_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// This structure contains the diagnostic context of an exception, that is collected by the
+/// handlers installed by [InterruptDescriptorTable::with_panic_stubs].
+///
+/// - `vector` - This field is the vector of the exception (see [Exception])
+/// - `error_code` - This field is the error code, if the exception pushes one (see
+/// [has_error_code])
+/// - `frame` - This field is the interrupt stack frame, that was pushed by the CPU
+/// - `cr2` - This field is the faulting linear address from the CR2 register, if the exception is
+/// a Page Fault
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{
+    interrupts::{ExceptionInfo, InterruptStackFrame, DIVIDE_ERROR, PAGE_FAULT},
+    set_cr2,
+};
+
+let frame = InterruptStackFrame {
+    instruction_pointer: 0xFFFF_8000_0000_1000,
+    code_segment: 0x08,
+    cpu_flags: 0x202,
+    stack_pointer: 0xFFFF_8000_0010_0000,
+    stack_segment: 0x10,
+};
+
+set_cr2(0xDEAD_B000);
+let info = ExceptionInfo::new(PAGE_FAULT, Some(0b10), frame);
+assert_eq!(info.cr2, Some(0xDEAD_B000));
+assert_eq!(
+    info.to_string(),
+    "Unhandled exception 14 (error code 0x2) at 0xffff800000001000 (address 0xdeadb000)"
+);
+
+let info = ExceptionInfo::new(DIVIDE_ERROR, None, frame);
+assert_eq!(info.cr2, None);
+assert_eq!(info.to_string(), "Unhandled exception 0 at 0xffff800000001000");
+```
+"#)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct ExceptionInfo {
+    pub vector: u8,
+    pub error_code: Option<u64>,
+    pub frame: InterruptStackFrame,
+    pub cr2: Option<MemoryAddress>,
+}
+
+impl ExceptionInfo {
+    /// This function creates the diagnostic context of the exception. For a Page Fault, the
+    /// faulting address is read from the CR2 register, so this function must be called before
+    /// another Page Fault can occur.
+    #[must_use]
+    pub fn new(vector: u8, error_code: Option<u64>, frame: InterruptStackFrame) -> Self {
+        Self {
+            vector,
+            error_code,
+            frame,
+            cr2: (vector == PAGE_FAULT).then(get_cr2),
+        }
+    }
+}
+
+impl fmt::Display for ExceptionInfo {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instruction_pointer = self.frame.instruction_pointer;
+        write!(formatter, "Unhandled exception {}", self.vector)?;
+        if let Some(error_code) = self.error_code {
+            write!(formatter, " (error code {:#x})", error_code)?;
+        }
+        write!(formatter, " at {:#x}", instruction_pointer)?;
+        if let Some(address) = self.cr2 {
+            write!(formatter, " (address {:#x})", address)?;
+        }
+        Ok(())
+    }
+}
+
+/// This function registers the hook, that is called with the [ExceptionInfo] by the handlers
+/// installed by [InterruptDescriptorTable::with_panic_stubs]. The hook is shared by all tables.
+pub fn set_exception_hook(hook: ExceptionHook) {
+    EXCEPTION_HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+fn report_exception(vector: u8, error_code: Option<u64>, stack_frame: &InterruptStackFrame) -> ! {
+    let info = ExceptionInfo::new(vector, error_code, *stack_frame);
+    let hook = EXCEPTION_HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        let hook = unsafe { core::mem::transmute::<*mut (), ExceptionHook>(hook) };
+        hook(&info);
+    }
+
+    let sink = EXCEPTION_SINK.load(Ordering::Acquire);
+    if !sink.is_null() {
+        let sink = unsafe { core::mem::transmute::<*mut (), ExceptionSink>(sink) };
+        sink(format_args!("{}", info));
+    }
+
+    loop {
+        unsafe {
+            asm!("cli", "hlt", options(nomem, nostack));
+        }
+    }
+}
+
+extern "x86-interrupt" fn panic_stub<const VECTOR: u8>(stack_frame: InterruptStackFrame) {
+    report_exception(VECTOR, None, &stack_frame);
+}
+
+extern "x86-interrupt" fn panic_stub_with_error_code<const VECTOR: u8>(
+    stack_frame: InterruptStackFrame, error_code: u64
+) {
+    report_exception(VECTOR, Some(error_code), &stack_frame);
+}
+
+/// This function returns whether the maskable interrupts are enabled, which is the case if the
+/// interrupt flag (IF, bit 9) is set in the RFLAGS (or EFLAGS) register.
+///
+/// # See also
+/// - [FLAGS register](https://en.wikipedia.org/wiki/FLAGS_register) by
+/// [Wikipedia](https://wikipedia.org)
+#[inline]
+#[must_use]
+pub fn interrupts_enabled() -> bool {
+    #[cfg(feature = "mock")]
+    return crate::mock::backend().read_interrupt_flag();
+
+    #[cfg(not(feature = "mock"))]
+    {
+        let flags: usize;
+        unsafe {
+            #[cfg(target_arch = "x86_64")]
+            asm!("pushfq", "pop {}", out(reg) flags, options(nomem, preserves_flags));
+
+            #[cfg(target_arch = "x86")]
+            asm!("pushfd", "pop {}", out(reg) flags, options(nomem, preserves_flags));
+        }
+        flags.get_bit(9)
+    }
+}
+
+/// This function enables the maskable interrupts with the `sti` instruction.
+///
+/// # See also
+/// - [STI](https://www.felixcloutier.com/x86/sti) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn enable_interrupts() {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_interrupt_flag(true);
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("sti", options(nomem, nostack));
+    }
+}
+
+/// This function disables the maskable interrupts with the `cli` instruction. Non-maskable
+/// interrupts and exceptions are still delivered.
+///
+/// # See also
+/// - [CLI](https://www.felixcloutier.com/x86/cli) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn disable_interrupts() {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_interrupt_flag(false);
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("cli", options(nomem, nostack));
+    }
+}
+
+/// This function runs the closure with disabled maskable interrupts. After the closure, the
+/// interrupts are only enabled again, if they were enabled before, so the calls can be nested.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::interrupts::{enable_interrupts, interrupts_enabled, without_interrupts};
+
+enable_interrupts();
+let value = without_interrupts(|| {
+    assert!(!interrupts_enabled());
+    without_interrupts(|| assert!(!interrupts_enabled()));
+    assert!(!interrupts_enabled());
+    42
+});
+assert_eq!(value, 42);
+assert!(interrupts_enabled());
+```
+"#)]
+#[inline]
+pub fn without_interrupts<R>(f: impl FnOnce() -> R) -> R {
+    let enabled = interrupts_enabled();
+    if enabled {
+        disable_interrupts();
+    }
+
+    let result = f();
+    if enabled {
+        enable_interrupts();
+    }
+    result
+}
+
+/// This function idles the processor until the predicate returns true. The predicate is checked
+/// with disabled interrupts. If it returns false, the interrupts are enabled and the processor is
+/// halted with `sti; hlt`. The `sti` instruction delays the interrupts until the next instruction
+/// is executed, so an interrupt, that arrives between the check and the `hlt` instruction, wakes
+/// the processor instead of getting lost. After every interrupt, the predicate is checked again.
+///
+/// After the predicate returned true, the interrupt flag is restored to the state before the call.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{
+    interrupts::{enable_interrupts, idle_until, interrupts_enabled},
+    mock::{set_backend, MockBackend},
+    SpinLock,
+};
+
+struct RecordingProcessor {
+    instructions: SpinLock<Vec<&'static str>>,
+}
+
+impl MockBackend for RecordingProcessor {
+    fn execute(&self, instruction: &'static str, _operand: u64) {
+        self.instructions.lock().push(instruction);
+    }
+
+    fn write_interrupt_flag(&self, enabled: bool) {
+        self.instructions.lock().push(if enabled { "sti" } else { "cli" });
+        self.registers().write_interrupt_flag(enabled)
+    }
+}
+
+static PROCESSOR: RecordingProcessor = RecordingProcessor {
+    instructions: SpinLock::new(Vec::new()),
+};
+unsafe { set_backend(&PROCESSOR) };
+
+enable_interrupts();
+PROCESSOR.instructions.lock().clear();
+
+let mut checks = 0;
+idle_until(|| {
+    assert!(!interrupts_enabled());
+    checks += 1;
+    checks == 3
+});
+assert_eq!(checks, 3);
+assert!(interrupts_enabled());
+assert_eq!(
+    *PROCESSOR.instructions.lock(),
+    ["cli", "sti", "hlt", "cli", "sti", "hlt", "cli", "sti"]
+);
+```
+"#)]
+///
+/// # See also
+/// - [STI](https://www.felixcloutier.com/x86/sti) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+/// - [HLT](https://www.felixcloutier.com/x86/hlt) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn idle_until(mut predicate: impl FnMut() -> bool) {
+    let enabled = interrupts_enabled();
+    loop {
+        disable_interrupts();
+        if predicate() {
+            break;
+        }
+
+        #[cfg(feature = "mock")]
+        {
+            crate::mock::backend().write_interrupt_flag(true);
+            crate::mock::backend().execute("hlt", 0);
+        }
+
+        #[cfg(not(feature = "mock"))]
+        unsafe {
+            asm!("sti", "hlt", options(nomem, nostack));
+        }
+    }
+
+    if enabled {
+        enable_interrupts();
+    }
+}
@@ -0,0 +1,65 @@
+//! This module defines the numbers of the CPUID leaves, that are requested by this library. All
+//! requests and helpers are using these constants, so the leaf numbers are only defined once.
+//!
+//! ```rust
+//! use libcpu::leaves;
+//!
+//! assert_eq!(leaves::BASIC_FEATURES, 1);
+//! assert_eq!(leaves::EXTENDED_PROCESSOR_INFO, 0x8000_0001);
+//! ```
+//!
+//! # See also
+//! - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+//! [Felix Clountier](https://www.felixcloutier.com)
+//! - [CPUID](https://en.wikipedia.org/wiki/CPUID) by [Wikipedia](https://wikipedia.org)
+
+use core::ops::RangeInclusive;
+
+/// This constant represents the leaf, that returns the highest basic leaf (eax) and the vendor
+/// string (ebx, edx, ecx).
+pub const VENDOR: u32 = 0x0;
+
+/// This constant represents the leaf, that returns the processor signature and the basic feature
+/// flags.
+pub const BASIC_FEATURES: u32 = 0x1;
+
+/// This constant represents the leaf, that returns the legacy descriptor bytes of the TLBs, caches
+/// and the prefetching.
+pub const CACHE_DESCRIPTORS: u32 = 0x2;
+
+/// This constant represents the leaf, that returns the structured extended feature flags. The sub
+/// leaves 0 to 2 are containing different sets of flags.
+pub const EXTENDED_FEATURES: u32 = 0x7;
+
+/// This constant represents the leaf, that returns the extended topology (like the x2APIC ID).
+pub const EXTENDED_TOPOLOGY: u32 = 0xB;
+
+/// This constant represents the leaf, that returns the supported state components and the size of
+/// the XSAVE area.
+pub const EXTENDED_STATE: u32 = 0xD;
+
+/// This constant represents the leaf, that returns the ratio of the Time Stamp Counter to the
+/// crystal clock.
+pub const TIME_STAMP_COUNTER: u32 = 0x15;
+
+/// This constant represents the leaf, that returns the base, maximum and bus frequency of the
+/// processor.
+pub const PROCESSOR_FREQUENCY: u32 = 0x16;
+
+/// This constant represents the leaf, that returns the highest hypervisor leaf and the vendor
+/// string of the hypervisor.
+pub const HYPERVISOR: u32 = 0x4000_0000;
+
+/// This constant represents the leaf, that returns the highest extended leaf.
+pub const EXTENDED_BASE: u32 = 0x8000_0000;
+
+/// This constant represents the leaf, that returns the extended processor info and feature flags.
+pub const EXTENDED_PROCESSOR_INFO: u32 = 0x8000_0001;
+
+/// This constant represents the leaf, that returns the memory encryption capabilities of AMD
+/// processors (SME and SEV).
+pub const MEMORY_ENCRYPTION: u32 = 0x8000_001F;
+
+/// This constant represents the leaves, that are returning the processor brand string. Every leaf
+/// returns 16 bytes of the string.
+pub const BRAND_STRING: RangeInclusive<u32> = 0x8000_0002..=0x8000_0004;
@@ -1,9 +1,10 @@
-use crate::{CPUFeature, CPUVendor};
+#[cfg(feature = "cpuid_cache")]
+use crate::{CPUFeatureSet, CPUVendor};
 
 #[cfg(feature = "cpuid_cache")]
-pub(crate) static mut VENDOR_CACHE: Option<CPUVendor>                     = None;
+pub(crate) static mut VENDOR_CACHE: Option<CPUVendor>       = None;
 #[cfg(feature = "cpuid_cache")]
-pub(crate) static mut FEATURES_CACHE: Option<alloc::vec::Vec<CPUFeature>> = None;
+pub(crate) static mut FEATURES_CACHE: Option<CPUFeatureSet> = None;
 
 #[macro_export]
 macro_rules! cpu_vendor {
@@ -19,8 +20,8 @@ macro_rules! cpu_vendor {
             Unknown
         }
 
-        impl alloc::fmt::Display for $name {
-            fn fmt(&self, formatter: &mut alloc::fmt::Formatter<'_>) -> alloc::fmt::Result {
+        impl core::fmt::Display for $name {
+            fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                 write!(formatter, "{}", match self {
                     $(
                     Self::$vendor_enum => $literal,
@@ -32,26 +33,44 @@ macro_rules! cpu_vendor {
 
         impl $name {
 
+            /// This function returns the vendor of the current processor, that is identified by the
+            /// vendor string of CPUID leaf 0. The vendor string is stored in the registers EBX, EDX
+            /// and ECX (in this order) with the first character in the lowest byte of every
+            /// register, so the bytes are extracted in little-endian order.
+            #[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, CPUVendor};
+
+// The result of CPUID leaf 0 on an Intel processor ("Genu", "ntel", "ineI")
+let result = CpuidResult { eax: 0x16, ebx: 0x756E_6547, ecx: 0x6C65_746E, edx: 0x4965_6E69 };
+register_file().set_cpuid(0, 0, result);
+assert_eq!(CPUVendor::get_vendor(), CPUVendor::Intel);
+```
+"#)]
             pub fn get_vendor() -> Self {
-                #[cfg(target = "cpuid_cache")]
-                if let Some(vendor) = unsafe { crate::macros::VENDOR_CACHE } {
+                if !$crate::cpuid_available() {
+                    return Self::Unknown;
+                }
+
+                #[cfg(feature = "cpuid_cache")]
+                if let Some(vendor) = unsafe { $crate::macros::VENDOR_CACHE } {
                     return vendor;
                 }
 
-                use alloc::string::String;
-                let result = crate::x86::cpuid::CPUIDRequest::Vendor.cpuid();
-                let vendor = match String::from_utf8_lossy(&[
-                    result.ebx.to_ne_bytes(),
-                    result.edx.to_ne_bytes(),
-                    result.ecx.to_ne_bytes()
-                ].concat()).trim() {
+                let result = $crate::x86::cpuid::CPUIDRequest::Vendor.cpuid();
+                let mut vendor_string = [0_u8; 12];
+                vendor_string[0..4].copy_from_slice(&result.ebx.to_le_bytes());
+                vendor_string[4..8].copy_from_slice(&result.edx.to_le_bytes());
+                vendor_string[8..12].copy_from_slice(&result.ecx.to_le_bytes());
+                let vendor = match core::str::from_utf8(&vendor_string).unwrap_or_default().trim() {
                     $(
                     $vendor_string_start $(| $vendor_string)? => Self::$vendor_enum,
                     )*
                     _ => Self::Unknown
                 };
-                #[cfg(target = "cpuid_cache")]
-                unsafe { crate::macros::VENDOR_CACHE = Some(vendor) };
+                #[cfg(feature = "cpuid_cache")]
+                unsafe { $crate::macros::VENDOR_CACHE = Some(vendor) };
                 vendor
             }
         }
@@ -63,9 +82,13 @@ macro_rules! segment_register {
     ($name: ident, $register: literal) => {
         paste::paste! {
             pub fn [<set_ $name>](value: SegmentSelector) {
+                #[cfg(feature = "mock")]
+                $crate::mock::backend().write_segment_register($register, value.0);
+
+                #[cfg(not(feature = "mock"))]
                 unsafe {
                     core::arch::asm!(
-                        concat!("mov ", $register, ", {}"),
+                        concat!("mov ", $register, ", {:x}"),
                         in(reg) value.0,
                         options(nomem, nostack, preserves_flags)
                     );
@@ -74,10 +97,15 @@ macro_rules! segment_register {
 
             #[allow(unused_assignments)]
             pub fn [<get_ $name>]() -> SegmentSelector {
+                #[cfg(feature = "mock")]
+                let value = $crate::mock::backend().read_segment_register($register);
+
+                #[cfg(not(feature = "mock"))]
                 let mut value = 0;
+                #[cfg(not(feature = "mock"))]
                 unsafe {
                     core::arch::asm!(
-                        concat!("mov {}, ", $register),
+                        concat!("mov {:x}, ", $register),
                         out(reg) value,
                         options(nomem, nostack, preserves_flags)
                     );
@@ -88,11 +116,40 @@ macro_rules! segment_register {
     };
 }
 
+/// This macro generates the getter and the setter of a control register. The value is passed to
+/// the `mov` instruction with the `reg` operand class, so the width of the general-purpose register
+/// is derived from the type of the value. [crate::Register] has the width of the pointer, so the
+/// operand is a 32-bit register (like `eax`) on x86 and a 64-bit register (like `rax`) on x86_64,
+/// which matches the width of the control registers in both modes.
+///
+/// ```rust,no_run
+/// use libcpu::{
+///     get_cr0, get_cr3, get_cr4, set_cr0, set_cr4, CR0Flags, CR3Flags, CR4Flags, Register,
+/// };
+///
+/// let _: fn() -> CR0Flags = get_cr0;
+/// let _: fn(CR0Flags) = set_cr0;
+/// let _: fn() -> CR3Flags = get_cr3;
+/// let _: fn() -> CR4Flags = get_cr4;
+/// let _: fn(CR4Flags) = set_cr4;
+/// assert_eq!(CR0Flags::PAGING.bits(), 1 << 31);
+///
+/// #[cfg(target_arch = "x86")]
+/// const _: () = assert!(core::mem::size_of::<Register>() == 4);
+///
+/// #[cfg(target_arch = "x86_64")]
+/// const _: () = assert!(core::mem::size_of::<Register>() == 8);
+/// ```
 #[macro_export]
 macro_rules! cpu_register {
     ($name: ident, $register: literal, $flags_struct: ident) => {
         paste::paste! {
             pub fn [<set_ $name>](value: $flags_struct) {
+                #[cfg(feature = "mock")]
+                $crate::mock::backend()
+                    .write_control_register($register, (value | [<get_ $name>]()).bits());
+
+                #[cfg(not(feature = "mock"))]
                 unsafe {
                     core::arch::asm!(
                         concat!("mov ", $register, ", {}"),
@@ -104,7 +161,12 @@ macro_rules! cpu_register {
 
             #[allow(unused_assignments)]
             pub fn [<get_ $name>]() -> $flags_struct {
+                #[cfg(feature = "mock")]
+                let value = $crate::mock::backend().read_control_register($register);
+
+                #[cfg(not(feature = "mock"))]
                 let mut value = 0;
+                #[cfg(not(feature = "mock"))]
                 unsafe {
                     core::arch::asm!(
                         concat!("mov {}, ", $register),
@@ -118,7 +180,11 @@ macro_rules! cpu_register {
     };
     ($name: ident, $register: literal) => {
         paste::paste! {
-            pub fn [<set_ $name>](value: crate::Register) {
+            pub fn [<set_ $name>](value: $crate::Register) {
+                #[cfg(feature = "mock")]
+                $crate::mock::backend().write_control_register($register, value);
+
+                #[cfg(not(feature = "mock"))]
                 unsafe {
                     core::arch::asm!(
                         concat!("mov ", $register, ", {}"),
@@ -129,8 +195,13 @@ macro_rules! cpu_register {
             }
 
             #[allow(unused_assignments)]
-            pub fn [<get_ $name>]() -> crate::Register {
+            pub fn [<get_ $name>]() -> $crate::Register {
+                #[cfg(feature = "mock")]
+                let value = $crate::mock::backend().read_control_register($register);
+
+                #[cfg(not(feature = "mock"))]
                 let mut value = 0;
+                #[cfg(not(feature = "mock"))]
                 unsafe {
                     core::arch::asm!(
                         concat!("mov {}, ", $register),
@@ -144,10 +215,38 @@ macro_rules! cpu_register {
     };
 }
 
+#[macro_export]
+macro_rules! model_specific_register {
+    ($name: ident, $msr: expr, $flags_struct: ident) => {
+        paste::paste! {
+            pub fn [<set_ $name>](value: $flags_struct) {
+                $crate::msr::write_msr($msr, value.bits());
+            }
+
+            pub fn [<get_ $name>]() -> $flags_struct {
+                $flags_struct::from_bits_retain($crate::msr::read_msr($msr))
+            }
+        }
+    };
+    ($name: ident, $msr: expr) => {
+        paste::paste! {
+            pub fn [<set_ $name>](value: u64) {
+                $crate::msr::write_msr($msr, value);
+            }
+
+            pub fn [<get_ $name>]() -> u64 {
+                $crate::msr::read_msr($msr)
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! cpu_features {
+    (@introduced) => { None };
+    (@introduced $introduced: literal) => { Some($introduced) };
     ($(#[$attr:meta])* $vis: vis enum $name: ident {
-        $($(#[$feat_attr:meta])* $feat_ident: ident ($register: ident, $feat_name: literal, $request: path) = $value: expr),*
+        $($(#[$feat_attr:meta])* $feat_ident: ident ($register: ident, $feat_name: literal, $request: path $(, introduced = $introduced: literal)?) = $value: expr),*
     }) => {
         $(#[$attr])*
         $vis enum $name {
@@ -157,8 +256,48 @@ macro_rules! cpu_features {
             )*
         }
 
-        impl alloc::fmt::Display for $name {
-            fn fmt(&self, formatter: &mut alloc::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        // Every feature must be identified by a unique combination of the CPUID request, the
+        // register and the bit, so copy-paste errors in the feature list are failing to compile.
+        const _: () = {
+            const METADATA: &[(u8, &str, u32)] = &[
+                $(
+                ($request as u8, stringify!($register), $value),
+                )*
+            ];
+
+            const fn str_eq(first: &str, second: &str) -> bool {
+                let (first, second) = (first.as_bytes(), second.as_bytes());
+                if first.len() != second.len() {
+                    return false;
+                }
+
+                let mut index = 0;
+                while index < first.len() {
+                    if first[index] != second[index] {
+                        return false;
+                    }
+                    index += 1;
+                }
+                true
+            }
+
+            let mut first = 0;
+            while first < METADATA.len() {
+                let mut second = first + 1;
+                while second < METADATA.len() {
+                    if METADATA[first].0 == METADATA[second].0
+                        && str_eq(METADATA[first].1, METADATA[second].1)
+                        && METADATA[first].2 == METADATA[second].2 {
+                        panic!("Two CPU features are sharing the same request, register and bit");
+                    }
+                    second += 1;
+                }
+                first += 1;
+            }
+        };
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
                 write!(formatter, "{}", match self {
                     $(
                     Self::$feat_ident => $feat_name,
@@ -167,43 +306,327 @@ macro_rules! cpu_features {
             }
         }
 
+        const CPU_FEATURE_SET_WORDS: usize = ([$(stringify!($feat_ident)),*].len() + 63) / 64;
+
+        /// This structure represents a set of CPU features as a bitset with one bit per feature,
+        /// so the features can be collected and checked without an allocator.
+        ///
+        /// ```rust
+        /// use libcpu::{CPUFeature, CPUFeatureSet};
+        ///
+        /// let mut features = CPUFeatureSet::new();
+        /// assert!(features.is_empty());
+        ///
+        /// features.insert(CPUFeature::AVX2);
+        /// features.insert(CPUFeature::SSE);
+        /// assert!(features.contains(CPUFeature::AVX2));
+        /// assert!(!features.contains(CPUFeature::AVX));
+        /// assert_eq!(features.len(), 2);
+        /// assert_eq!(features.iter().next(), Some(CPUFeature::SSE));
+        /// ```
+        #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+        pub struct CPUFeatureSet {
+            bits: [u64; CPU_FEATURE_SET_WORDS],
+        }
+
+        impl CPUFeatureSet {
+            /// This function creates a new empty set of features.
+            #[inline]
+            #[must_use]
+            pub const fn new() -> Self {
+                Self { bits: [0; CPU_FEATURE_SET_WORDS] }
+            }
+
+            /// This function adds the feature to the set.
+            #[inline]
+            pub fn insert(&mut self, feature: $name) {
+                self.bits[feature as usize / 64] |= 1 << (feature as usize % 64);
+            }
+
+            /// This function returns whether the feature is in the set.
+            #[inline]
+            #[must_use]
+            pub fn contains(&self, feature: $name) -> bool {
+                (self.bits[feature as usize / 64] & (1 << (feature as usize % 64))) != 0
+            }
+
+            /// This function returns the number of features in the set.
+            #[inline]
+            #[must_use]
+            pub fn len(&self) -> usize {
+                self.bits.iter().map(|word| word.count_ones() as usize).sum()
+            }
+
+            /// This function returns whether the set contains no features.
+            #[inline]
+            #[must_use]
+            pub fn is_empty(&self) -> bool {
+                self.bits.iter().all(|word| *word == 0)
+            }
+
+            /// This function returns an iterator over the features in the set, in the order of
+            /// the feature list.
+            #[inline]
+            pub fn iter(&self) -> impl Iterator<Item = $name> + '_ {
+                $name::ALL.iter().copied().filter(|feature| self.contains(*feature))
+            }
+        }
+
         impl $name {
 
+            /// This constant lists all features, that are known by this library. Unlike
+            /// `all_features`, this list is available without an allocator.
+            pub const ALL: &'static [Self] = &[
+                $(
+                Self::$feat_ident,
+                )*
+            ];
+
+            /// This function returns the short identifier of the feature (like `"AVX2"`). Unlike the
+            /// [Display](core::fmt::Display) implementation, that returns the long description, the
+            /// identifier is the name of the variant, so it's useful for compact logging.
+            ///
+            /// ```rust
+            /// use libcpu::CPUFeature;
+            ///
+            /// assert_eq!(CPUFeature::AVX2.name(), "AVX2");
+            /// assert_eq!(CPUFeature::SSE4_1.name(), "SSE4_1");
+            /// assert_eq!(CPUFeature::RDRAND.name(), "RDRAND");
+            /// assert_eq!(CPUFeature::HYBRID.name(), "HYBRID");
+            /// assert_ne!(CPUFeature::AVX2.name(), CPUFeature::AVX2.to_string());
+            /// ```
             #[inline]
-            pub fn enabled_features() -> alloc::vec::Vec<Self> {
+            #[must_use]
+            pub const fn name(self) -> &'static str {
+                const NAMES: &[&str] = &[
+                    $(
+                    stringify!($feat_ident),
+                    )*
+                ];
+                NAMES[self as usize]
+            }
+
+            /// This function returns the set of all features, that are supported by the current
+            /// processor. With the `cpuid_cache` feature, the result is cached after the first
+            /// call. Hybrid processors (with the [CPUFeature::HYBRID] feature) are reporting
+            /// different features on the performance and efficiency cores, so their features are
+            /// never cached. This function doesn't allocate, so it's also available without the
+            /// `alloc` feature.
+            ///
+            /// ```rust
+            /// use libcpu::CPUFeature;
+            ///
+            /// let features = CPUFeature::enabled_feature_set();
+            /// for feature in CPUFeature::ALL {
+            ///     assert_eq!(features.contains(*feature), feature.is_supported());
+            /// }
+            /// ```
+            #[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use libcpu::{mock::{set_backend, MockBackend}, CPUFeature};
+
+// The first core supports AVX, the second core doesn't. Both are reporting the HYBRID feature.
+struct HybridProcessor {
+    current_core: AtomicUsize,
+}
+
+impl MockBackend for HybridProcessor {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        match (leaf, sub_leaf, self.current_core.load(Ordering::SeqCst)) {
+            (1, _, 0) => CpuidResult { eax: 0, ebx: 0, ecx: 1 << 28, edx: 0 },
+            (7, 0, _) => CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 15 },
+            _ => CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 0 },
+        }
+    }
+}
+
+static PROCESSOR: HybridProcessor = HybridProcessor { current_core: AtomicUsize::new(0) };
+
+unsafe { set_backend(&PROCESSOR) };
+assert!(CPUFeature::AVX.is_supported());
+
+PROCESSOR.current_core.store(1, Ordering::SeqCst);
+assert!(!CPUFeature::AVX.is_supported());
+assert!(CPUFeature::HYBRID.is_supported());
+```
+"#)]
+            #[inline]
+            #[must_use]
+            pub fn enabled_feature_set() -> CPUFeatureSet {
+                if !$crate::cpuid_available() {
+                    return CPUFeatureSet::new();
+                }
+
                 #[cfg(feature = "cpuid_cache")]
-                if let Some(features) = unsafe { crate::macros::FEATURES_CACHE.clone() } {
+                if let Some(features) = unsafe { $crate::macros::FEATURES_CACHE } {
                     return features;
                 }
 
-                let mut enabled_features = alloc::vec::Vec::new();
+                let mut enabled_features = CPUFeatureSet::new();
                 Self::enabled_features_by(CPUIDRequest::Features, &mut enabled_features);
                 Self::enabled_features_by(CPUIDRequest::ExtendedFeatures1, &mut enabled_features);
                 Self::enabled_features_by(CPUIDRequest::ExtendedFeatures2, &mut enabled_features);
                 Self::enabled_features_by(CPUIDRequest::ExtendedFeatures3, &mut enabled_features);
                 Self::enabled_features_by(CPUIDRequest::ExtendedFeatures4, &mut enabled_features);
 
+                // The cores of hybrid processors are reporting different features, so the features
+                // of the current core are only valid until the thread is migrated to another core
                 #[cfg(feature = "cpuid_cache")]
-                unsafe { crate::macros::FEATURES_CACHE = Some(enabled_features.clone()) };
+                if !enabled_features.contains(Self::HYBRID) {
+                    unsafe { $crate::macros::FEATURES_CACHE = Some(enabled_features) };
+                }
                 enabled_features
             }
 
-            fn enabled_features_by(request: crate::x86::cpuid::CPUIDRequest, vec: &mut alloc::vec::Vec<Self>) {
+            /// This function writes the features, that are supported by the current processor, into
+            /// the buffer and returns the number of written features. If the buffer is too small,
+            /// the remaining features are skipped. This function doesn't allocate.
+            ///
+            /// ```rust
+            /// use libcpu::CPUFeature;
+            ///
+            /// let mut buffer = [CPUFeature::SSE3; CPUFeature::ALL.len()];
+            /// let count = CPUFeature::enabled_features_into(&mut buffer);
+            /// assert_eq!(count, CPUFeature::enabled_feature_set().len());
+            /// assert!(buffer[..count].iter().all(|feature| feature.is_supported()));
+            ///
+            /// assert_eq!(CPUFeature::enabled_features_into(&mut []), 0);
+            /// ```
+            #[inline]
+            pub fn enabled_features_into(buffer: &mut [Self]) -> usize {
+                let features = Self::enabled_feature_set();
+                let slots = buffer.iter_mut().zip(features.iter());
+                slots.map(|(slot, feature)| *slot = feature).count()
+            }
+
+            /// This function returns whether the feature is supported by the current processor.
+            /// This function doesn't allocate.
+            #[inline]
+            #[must_use]
+            pub fn is_supported(self) -> bool {
+                Self::enabled_feature_set().contains(self)
+            }
+
+            /// This function returns all features, that are supported by the current processor, as
+            /// a list. This function is only available with the `alloc` feature.
+            #[cfg(feature = "alloc")]
+            #[inline]
+            pub fn enabled_features() -> alloc::vec::Vec<Self> {
+                Self::enabled_feature_set().iter().collect()
+            }
+
+            fn enabled_features_by(
+                request: $crate::x86::cpuid::CPUIDRequest,
+                set: &mut CPUFeatureSet,
+            ) {
                 let cpuid = request.cpuid();
                 $(
                 if $request == request && (cpuid.$register & $value) == $value {
-                    vec.push(Self::$feat_ident);
+                    set.insert(Self::$feat_ident);
                 }
                 )*
             }
 
+            /// This function returns the CPUID request, that reports the feature.
+            ///
+            /// ```rust
+            /// use libcpu::{CPUFeature, CPUIDRequest};
+            ///
+            /// assert_eq!(CPUFeature::AVX2.request(), CPUIDRequest::ExtendedFeatures1);
+            /// assert_eq!(CPUFeature::AVX2.register(), "ebx");
+            /// assert_eq!(CPUFeature::AVX2.bit(), 5);
+            /// ```
+            #[inline]
+            #[must_use]
+            pub fn request(self) -> $crate::x86::cpuid::CPUIDRequest {
+                match self {
+                    $(
+                    Self::$feat_ident => $request,
+                    )*
+                }
+            }
+
+            /// This function returns the name of the register (`"eax"`, `"ebx"`, `"ecx"` or
+            /// `"edx"`), in which the feature is reported by the CPUID request.
+            #[inline]
+            #[must_use]
+            pub fn register(self) -> &'static str {
+                match self {
+                    $(
+                    Self::$feat_ident => stringify!($register),
+                    )*
+                }
+            }
+
+            /// This function returns the index of the bit, that reports the feature in the register.
+            #[inline]
+            #[must_use]
+            pub fn bit(self) -> u8 {
+                match self {
+                    $(
+                    Self::$feat_ident => ($value as u32).trailing_zeros() as u8,
+                    )*
+                }
+            }
+
+            /// This function returns all features, that are known by this library, as a list. This
+            /// function is only available with the `alloc` feature.
+            #[cfg(feature = "alloc")]
             #[inline]
             pub fn all_features() -> alloc::vec::Vec<Self> {
-                alloc::vec![
+                Self::ALL.to_vec()
+            }
+
+            /// This function returns an iterator over all features, that are known by this library,
+            /// with their descriptions. The description is the value returned by the
+            /// [Display](core::fmt::Display) implementation. This function doesn't allocate.
+            ///
+            /// ```rust
+            /// use libcpu::CPUFeature;
+            ///
+            /// let (_, description) = CPUFeature::all_with_descriptions()
+            ///     .find(|(feature, _)| *feature == CPUFeature::AVX)
+            ///     .unwrap();
+            /// assert!(description.contains("Advanced Vector Extensions"));
+            /// assert_eq!(description, CPUFeature::AVX.to_string());
+            /// assert_eq!(CPUFeature::all_with_descriptions().count(), CPUFeature::ALL.len());
+            /// ```
+            #[cfg_attr(feature = "alloc", doc = r#"
+```rust
+use libcpu::CPUFeature;
+
+assert_eq!(CPUFeature::all_with_descriptions().count(), CPUFeature::all_features().len());
+```
+"#)]
+            #[inline]
+            pub fn all_with_descriptions() -> impl Iterator<Item = (Self, &'static str)> {
+                const DESCRIPTIONS: &[($name, &str)] = &[
                     $(
-                    Self::$feat_ident,
+                    ($name::$feat_ident, $feat_name),
                     )*
-                ]
+                ];
+                DESCRIPTIONS.iter().copied()
+            }
+
+            /// This function returns the microarchitecture, that introduced the feature, if it's
+            /// known. This is useful to explain why a feature is missing on older hardware.
+            ///
+            /// ```rust
+            /// use libcpu::CPUFeature;
+            ///
+            /// assert_eq!(CPUFeature::AVX512F.introduced(), Some("AVX-512 (Skylake-X)"));
+            /// assert_eq!(CPUFeature::SSE3.introduced(), None);
+            /// ```
+            #[inline]
+            #[must_use]
+            pub fn introduced(self) -> Option<&'static str> {
+                match self {
+                    $(
+                    Self::$feat_ident => $crate::cpu_features!(@introduced $($introduced)?),
+                    )*
+                }
             }
 
         }
@@ -0,0 +1,145 @@
+//! This module implements bulk memory operations with the `rep movsb` and `rep stosb` string
+//! instructions. On processors with Enhanced REP MOVSB/STOSB (ERMS), these instructions are
+//! optimized by the microcode and are as fast as vectorized copy loops, without the need to save
+//! the SIMD state in the kernel. With Fast Short REP MOVSB (FSRM), the instructions are also fast
+//! for short copies.
+//!
+//! # See also
+//! - [Intel 64 and IA-32 Architectures Optimization Reference Manual](https://cdrdv2-public.intel.com/671488/248966-046A-software-optimization-manual.pdf)
+//! Chapter 3.7.6 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+
+use crate::CPUFeature;
+use core::{
+    arch::asm,
+    sync::atomic::{
+        AtomicU8,
+        Ordering,
+    },
+};
+
+const ERMS_UNKNOWN: u8 = 0;
+const ERMS_SUPPORTED: u8 = 1;
+const ERMS_UNSUPPORTED: u8 = 2;
+
+// The feature detection executes CPUID without the cache, so the result is kept for the hot path
+static ERMS_STATE: AtomicU8 = AtomicU8::new(ERMS_UNKNOWN);
+
+/// This function copies `len` bytes from `src` to `dst` with the `rep movsb` instruction.
+///
+/// # Safety
+/// The caller must ensure, that `src` is valid for reads and `dst` is valid for writes of `len`
+/// bytes and that both regions are not overlapping.
+///
+/// # See also
+/// - [MOVS/MOVSB](https://www.felixcloutier.com/x86/movs:movsb:movsw:movsd:movsq) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub unsafe fn rep_movsb(dst: *mut u8, src: *const u8, len: usize) {
+    asm!(
+        "rep movsb",
+        inout("ecx") len => _,
+        inout("edi") dst => _,
+        inout("esi") src => _,
+        options(nostack, preserves_flags)
+    );
+}
+
+/// This function sets `len` bytes at `dst` to `value` with the `rep stosb` instruction.
+///
+/// # Safety
+/// The caller must ensure, that `dst` is valid for writes of `len` bytes.
+///
+/// # See also
+/// - [STOS/STOSB](https://www.felixcloutier.com/x86/stos:stosb:stosw:stosd:stosq) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub unsafe fn rep_stosb(dst: *mut u8, value: u8, len: usize) {
+    asm!(
+        "rep stosb",
+        inout("ecx") len => _,
+        inout("edi") dst => _,
+        in("al") value,
+        options(nostack, preserves_flags)
+    );
+}
+
+/// This function returns whether [fast_copy] and [fast_set] are using the string instructions,
+/// which is the case if the processor supports the [CPUFeature::ERMS] feature. The result is
+/// detected once and reused by the following calls.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{memory::{fast_copy, fast_string_operations}, mock::register_file};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 1 << 9, ecx: 0, edx: 0 });
+assert!(fast_string_operations());
+
+let mut destination = [0_u8; 4];
+fast_copy(&mut destination, &[1, 2, 3, 4]);
+assert_eq!(destination, [1, 2, 3, 4]);
+```
+
+```rust
+use libcpu::memory::fast_string_operations;
+
+assert!(!fast_string_operations());
+```
+"#)]
+#[must_use]
+pub fn fast_string_operations() -> bool {
+    match ERMS_STATE.load(Ordering::Relaxed) {
+        ERMS_SUPPORTED => true,
+        ERMS_UNSUPPORTED => false,
+        _ => {
+            let supported = CPUFeature::ERMS.is_supported();
+            let state = if supported { ERMS_SUPPORTED } else { ERMS_UNSUPPORTED };
+            ERMS_STATE.store(state, Ordering::Relaxed);
+            supported
+        }
+    }
+}
+
+/// This function copies the bytes of `src` into `dst`. If the processor supports the
+/// [CPUFeature::ERMS] feature, the bytes are copied with [rep_movsb]. Otherwise, the bytes are
+/// copied with [slice::copy_from_slice].
+///
+/// ```rust
+/// use libcpu::memory::fast_copy;
+///
+/// let source = [0xAB_u8; 300];
+/// let mut destination = [0_u8; 300];
+/// fast_copy(&mut destination, &source);
+/// assert_eq!(destination, source);
+/// ```
+///
+/// # Panics
+/// This function panics, if the slices are not having the same length.
+#[inline]
+pub fn fast_copy(dst: &mut [u8], src: &[u8]) {
+    assert_eq!(dst.len(), src.len(), "The source and destination must have the same length");
+    if fast_string_operations() {
+        unsafe { rep_movsb(dst.as_mut_ptr(), src.as_ptr(), src.len()) };
+    } else {
+        dst.copy_from_slice(src);
+    }
+}
+
+/// This function sets all bytes of `dst` to `value`. If the processor supports the
+/// [CPUFeature::ERMS] feature, the bytes are set with [rep_stosb]. Otherwise, the bytes are set
+/// with [slice::fill].
+///
+/// ```rust
+/// use libcpu::memory::fast_set;
+///
+/// let mut destination = [0_u8; 300];
+/// fast_set(&mut destination, 0x5A);
+/// assert!(destination.iter().all(|byte| *byte == 0x5A));
+/// ```
+#[inline]
+pub fn fast_set(dst: &mut [u8], value: u8) {
+    if fast_string_operations() {
+        unsafe { rep_stosb(dst.as_mut_ptr(), value, dst.len()) };
+    } else {
+        dst.fill(value);
+    }
+}
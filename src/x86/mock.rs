@@ -0,0 +1,417 @@
+//! This module implements an assembly-free backend for the control registers, the extended control
+//! registers, the segment registers, the Model-Specific Registers and the `cpuid` instruction. It's
+//! only available with the `mock` feature and is used to test the higher-level logic (like the
+//! feature dispatch or the flag handling) on a normal host, where the privileged instructions are
+//! faulting.
+//!
+//! By default, the accesses are redirected into a [RegisterFile] (see [register_file]), which
+//! simply stores the written values and the configured CPUID leaves. Another backend can be
+//! installed with [set_backend].
+//!
+//! ```rust
+//! use libcpu::{get_cr4, set_cr4, CR4Flags};
+//! use libcpu::msr::{get_efer, set_efer, EFERFlags};
+//!
+//! set_cr4(CR4Flags::PHYSCIAL_ADDRESS_EXTENSION);
+//! set_cr4(CR4Flags::PAGE_GLOBAL_ENABLED);
+//! assert_eq!(get_cr4(), CR4Flags::PHYSCIAL_ADDRESS_EXTENSION | CR4Flags::PAGE_GLOBAL_ENABLED);
+//!
+//! set_efer(EFERFlags::LONG_MODE_ENABLE);
+//! assert_eq!(get_efer(), EFERFlags::LONG_MODE_ENABLE);
+//! ```
+//!
+//! A custom backend only overrides the accesses, that it wants to observe or change (see
+//! [MockBackend]).
+
+use crate::Register;
+use core::arch::x86_64::CpuidResult;
+use core::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, Ordering};
+
+/// This trait represents a backend, that replaces the assembly of the register and CPUID accesses
+/// when the `mock` feature is enabled. All registers and CPUID leaves are delegated to the
+/// [RegisterFile] returned by [MockBackend::registers], so a backend only overrides the accesses it
+/// wants to observe or change.
+///
+/// ```rust
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+/// use libcpu::{get_cr4, mock::{set_backend, MockBackend}, set_cr4, CR4Flags, Register};
+///
+/// struct CountingProcessor {
+///     writes: AtomicUsize,
+/// }
+///
+/// impl MockBackend for CountingProcessor {
+///     fn write_control_register(&self, register: &'static str, value: Register) {
+///         self.writes.fetch_add(1, Ordering::SeqCst);
+///         self.registers().write_control_register(register, value);
+///     }
+/// }
+///
+/// static PROCESSOR: CountingProcessor = CountingProcessor { writes: AtomicUsize::new(0) };
+///
+/// unsafe { set_backend(&PROCESSOR) };
+/// set_cr4(CR4Flags::PAGE_GLOBAL_ENABLED);
+/// assert_eq!(get_cr4(), CR4Flags::PAGE_GLOBAL_ENABLED);
+/// assert_eq!(PROCESSOR.writes.load(Ordering::SeqCst), 1);
+/// ```
+pub trait MockBackend: Sync {
+    /// This function returns the register file, that stores the registers and CPUID leaves of this
+    /// backend. By default, the global register file (see [register_file]) is used.
+    fn registers(&self) -> &RegisterFile {
+        register_file()
+    }
+
+    /// This function returns the value of the control register with the specified name (like
+    /// `"cr4"`).
+    fn read_control_register(&self, register: &'static str) -> Register {
+        self.registers().read_control_register(register)
+    }
+
+    /// This function writes the value into the control register with the specified name (like
+    /// `"cr4"`).
+    fn write_control_register(&self, register: &'static str, value: Register) {
+        self.registers().write_control_register(register, value);
+    }
+
+    /// This function returns the value of the segment register with the specified name (like
+    /// `"cs"`).
+    fn read_segment_register(&self, register: &'static str) -> u16 {
+        self.registers().read_segment_register(register)
+    }
+
+    /// This function writes the value into the segment register with the specified name (like
+    /// `"cs"`).
+    fn write_segment_register(&self, register: &'static str, value: u16) {
+        self.registers().write_segment_register(register, value);
+    }
+
+    /// This function returns the value of the Model-Specific Register with the specified address.
+    fn read_msr(&self, msr: u32) -> u64 {
+        self.registers().read_msr(msr)
+    }
+
+    /// This function writes the value into the Model-Specific Register with the specified address.
+    fn write_msr(&self, msr: u32, value: u64) {
+        self.registers().write_msr(msr, value);
+    }
+
+    /// This function returns the value of the extended control register with the specified index
+    /// (like XCR0), that is read with the `xgetbv` instruction.
+    fn read_extended_control_register(&self, register: u32) -> u64 {
+        self.registers().read_extended_control_register(register)
+    }
+
+    /// This function writes the value into the extended control register with the specified index
+    /// (like XCR0), that is written with the `xsetbv` instruction.
+    fn write_extended_control_register(&self, register: u32, value: u64) {
+        self.registers().write_extended_control_register(register, value);
+    }
+
+    /// This function returns the value of the Protection Key Rights Register for User Pages
+    /// (PKRU), that is read with the `rdpkru` instruction.
+    fn read_pkru(&self) -> u32 {
+        self.registers().read_pkru()
+    }
+
+    /// This function writes the value into the Protection Key Rights Register for User Pages
+    /// (PKRU), that is written with the `wrpkru` instruction.
+    fn write_pkru(&self, value: u32) {
+        self.registers().write_pkru(value);
+    }
+
+    /// This function is called instead of the privileged instructions, that are only loading a
+    /// value into the processor (like `lgdt`, `lidt` or `ltr`), and the `hlt` instruction. The
+    /// operand is the base address of the loaded table or the loaded selector (zero for `hlt`). By
+    /// default, the instruction is ignored.
+    fn execute(&self, _instruction: &'static str, _operand: u64) {}
+
+    /// This function returns whether the interrupt flag (IF) is set in the flags register.
+    fn read_interrupt_flag(&self) -> bool {
+        self.registers().read_interrupt_flag()
+    }
+
+    /// This function is called instead of the `sti` and `cli` instructions, that are setting or
+    /// clearing the interrupt flag (IF).
+    fn write_interrupt_flag(&self, enabled: bool) {
+        self.registers().write_interrupt_flag(enabled);
+    }
+
+    /// This function returns the result of the `cpuid` instruction for the specified leaf and sub
+    /// leaf.
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        self.registers().cpuid(leaf, sub_leaf)
+    }
+
+    /// This function is called instead of the `rdrand` and `rdseed` instructions. The instruction
+    /// is the name of the executed instruction. If the generator has no random value available,
+    /// this function returns [None]. By default, no random value is available.
+    fn read_random(&self, _instruction: &'static str) -> Option<u64> {
+        None
+    }
+
+    /// This function returns the value of the Time Stamp Counter. By default, the counter is
+    /// increased by one with every read.
+    fn read_tsc(&self) -> u64 {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+/// This structure represents the default backend, which stores the values of the control
+/// registers, the extended control registers, the segment registers, the Model-Specific Registers,
+/// the PKRU register, the interrupt flag and the CPUID leaves. Unwritten registers and leaves are read as zero.
+pub struct RegisterFile {
+    control_registers: [AtomicU64; 9],
+    interrupt_flag: AtomicBool,
+    extended_control_registers: [AtomicU64; 2],
+    pkru: AtomicU32,
+    segment_registers: [AtomicU16; 6],
+    msrs: [(AtomicU64, AtomicU64); RegisterFile::MSR_CAPACITY],
+    cpuid_leaves: [(AtomicU64, [AtomicU32; 4]); RegisterFile::CPUID_CAPACITY],
+}
+
+impl RegisterFile {
+    /// This constant represents the count of different MSRs, that can be stored in the register
+    /// file.
+    pub const MSR_CAPACITY: usize = 64;
+
+    /// This constant represents the count of different CPUID leaves, that can be stored in the
+    /// register file.
+    pub const CPUID_CAPACITY: usize = 32;
+
+    /// This function creates a new register file with all registers set to zero.
+    #[must_use]
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub const fn new() -> Self {
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        const EMPTY_MSR: (AtomicU64, AtomicU64) = (AtomicU64::new(0), AtomicU64::new(0));
+        const ZERO_16: AtomicU16 = AtomicU16::new(0);
+        const ZERO_32: AtomicU32 = AtomicU32::new(0);
+        const EMPTY_LEAF: (AtomicU64, [AtomicU32; 4]) = (AtomicU64::new(0), [ZERO_32; 4]);
+        Self {
+            control_registers: [ZERO; 9],
+            interrupt_flag: AtomicBool::new(false),
+            extended_control_registers: [ZERO; 2],
+            pkru: ZERO_32,
+            segment_registers: [ZERO_16; 6],
+            msrs: [EMPTY_MSR; RegisterFile::MSR_CAPACITY],
+            cpuid_leaves: [EMPTY_LEAF; RegisterFile::CPUID_CAPACITY],
+        }
+    }
+
+    /// This function sets the result of the `cpuid` instruction for the specified leaf and sub
+    /// leaf. Leaves without a sub leaf are stored with the sub leaf zero.
+    pub fn set_cpuid(&self, leaf: u32, sub_leaf: u32, result: CpuidResult) {
+        let key = (u64::from(leaf) << 32) | u64::from(sub_leaf);
+        if let Some(registers) = Self::slot(&self.cpuid_leaves, key, true) {
+            registers[0].store(result.eax, Ordering::SeqCst);
+            registers[1].store(result.ebx, Ordering::SeqCst);
+            registers[2].store(result.ecx, Ordering::SeqCst);
+            registers[3].store(result.edx, Ordering::SeqCst);
+        }
+    }
+
+    /// This function returns the stored value of the control register with the specified name
+    /// (like `"cr4"`).
+    ///
+    /// # Panics
+    /// This function panics, if the name doesn't identify a control register.
+    #[must_use]
+    pub fn read_control_register(&self, register: &'static str) -> Register {
+        self.control_register(register).load(Ordering::SeqCst) as Register
+    }
+
+    /// This function stores the value of the control register with the specified name (like
+    /// `"cr4"`).
+    ///
+    /// # Panics
+    /// This function panics, if the name doesn't identify a control register.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn write_control_register(&self, register: &'static str, value: Register) {
+        self.control_register(register).store(value as u64, Ordering::SeqCst);
+    }
+
+    /// This function returns the stored value of the segment register with the specified name (like
+    /// `"cs"`).
+    ///
+    /// # Panics
+    /// This function panics, if the name doesn't identify a segment register.
+    #[must_use]
+    pub fn read_segment_register(&self, register: &'static str) -> u16 {
+        self.segment_register(register).load(Ordering::SeqCst)
+    }
+
+    /// This function stores the value of the segment register with the specified name (like
+    /// `"cs"`).
+    ///
+    /// # Panics
+    /// This function panics, if the name doesn't identify a segment register.
+    pub fn write_segment_register(&self, register: &'static str, value: u16) {
+        self.segment_register(register).store(value, Ordering::SeqCst);
+    }
+
+    /// This function returns the stored value of the Model-Specific Register with the specified
+    /// address. Unwritten MSRs are read as zero.
+    #[must_use]
+    pub fn read_msr(&self, msr: u32) -> u64 {
+        let slot = Self::slot(&self.msrs, u64::from(msr), false);
+        slot.map_or(0, |value| value.load(Ordering::SeqCst))
+    }
+
+    /// This function stores the value of the Model-Specific Register with the specified address.
+    ///
+    /// # Panics
+    /// This function panics, if more than [RegisterFile::MSR_CAPACITY] different MSRs are written.
+    pub fn write_msr(&self, msr: u32, value: u64) {
+        if let Some(slot) = Self::slot(&self.msrs, u64::from(msr), true) {
+            slot.store(value, Ordering::SeqCst);
+        }
+    }
+
+    /// This function returns the stored value of the extended control register with the specified
+    /// index (like XCR0).
+    ///
+    /// # Panics
+    /// This function panics, if the index doesn't identify an extended control register.
+    #[must_use]
+    pub fn read_extended_control_register(&self, register: u32) -> u64 {
+        self.extended_control_register(register).load(Ordering::SeqCst)
+    }
+
+    /// This function stores the value of the extended control register with the specified index
+    /// (like XCR0).
+    ///
+    /// # Panics
+    /// This function panics, if the index doesn't identify an extended control register.
+    pub fn write_extended_control_register(&self, register: u32, value: u64) {
+        self.extended_control_register(register).store(value, Ordering::SeqCst);
+    }
+
+    /// This function returns the stored value of the PKRU register.
+    #[must_use]
+    pub fn read_pkru(&self) -> u32 {
+        self.pkru.load(Ordering::SeqCst)
+    }
+
+    /// This function stores the value of the PKRU register.
+    pub fn write_pkru(&self, value: u32) {
+        self.pkru.store(value, Ordering::SeqCst);
+    }
+
+    /// This function returns whether the stored interrupt flag (IF) is set.
+    #[must_use]
+    pub fn read_interrupt_flag(&self) -> bool {
+        self.interrupt_flag.load(Ordering::SeqCst)
+    }
+
+    /// This function stores the interrupt flag (IF).
+    pub fn write_interrupt_flag(&self, enabled: bool) {
+        self.interrupt_flag.store(enabled, Ordering::SeqCst);
+    }
+
+    /// This function returns the stored result of the `cpuid` instruction for the specified leaf
+    /// and sub leaf (see [RegisterFile::set_cpuid]). Unset leaves are read as zero.
+    #[must_use]
+    pub fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        let key = (u64::from(leaf) << 32) | u64::from(sub_leaf);
+        match Self::slot(&self.cpuid_leaves, key, false) {
+            Some(registers) => CpuidResult {
+                eax: registers[0].load(Ordering::SeqCst),
+                ebx: registers[1].load(Ordering::SeqCst),
+                ecx: registers[2].load(Ordering::SeqCst),
+                edx: registers[3].load(Ordering::SeqCst),
+            },
+            None => CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 0 },
+        }
+    }
+
+    fn control_register(&self, register: &'static str) -> &AtomicU64 {
+        let index = register.strip_prefix("cr").and_then(|index| index.parse::<usize>().ok());
+        let index = match index {
+            Some(index) if index < self.control_registers.len() => index,
+            _ => panic!("Unknown control register '{}'", register),
+        };
+        &self.control_registers[index]
+    }
+
+    fn extended_control_register(&self, register: u32) -> &AtomicU64 {
+        match self.extended_control_registers.get(register as usize) {
+            Some(value) => value,
+            None => panic!("Unknown extended control register 'xcr{}'", register),
+        }
+    }
+
+    fn segment_register(&self, register: &'static str) -> &AtomicU16 {
+        let index = match register {
+            "cs" => 0,
+            "ds" => 1,
+            "es" => 2,
+            "fs" => 3,
+            "gs" => 4,
+            "ss" => 5,
+            _ => panic!("Unknown segment register '{}'", register),
+        };
+        &self.segment_registers[index]
+    }
+
+    // The key of every slot is stored plus one, so zero marks an unused slot
+    fn slot<T>(slots: &[(AtomicU64, T)], key: u64, allocate: bool) -> Option<&T> {
+        let key = key.wrapping_add(1);
+        for (slot_key, value) in slots {
+            match slot_key.load(Ordering::Acquire) {
+                0 if allocate => {
+                    match slot_key.compare_exchange(0, key, Ordering::AcqRel, Ordering::Acquire) {
+                        Ok(_) => return Some(value),
+                        Err(current) if current == key => return Some(value),
+                        Err(_) => continue,
+                    }
+                }
+                0 => return None,
+                current if current == key => return Some(value),
+                _ => continue,
+            }
+        }
+
+        if allocate {
+            panic!("The register file is full, only {} entries can be stored", slots.len());
+        }
+        None
+    }
+}
+
+impl Default for RegisterFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockBackend for RegisterFile {
+    fn registers(&self) -> &RegisterFile {
+        self
+    }
+}
+
+static REGISTER_FILE: RegisterFile = RegisterFile::new();
+static mut BACKEND: &dyn MockBackend = &REGISTER_FILE;
+
+/// This function returns the register file, that is used as backend until another backend is
+/// installed with [set_backend].
+#[must_use]
+pub fn register_file() -> &'static RegisterFile {
+    &REGISTER_FILE
+}
+
+/// This function replaces the backend, that is used for all register and CPUID accesses.
+///
+/// # Safety
+/// The caller must ensure, that no other thread is accessing a register while the backend is
+/// replaced.
+pub unsafe fn set_backend(backend: &'static dyn MockBackend) {
+    BACKEND = backend;
+}
+
+/// This function returns the currently installed backend.
+#[must_use]
+pub fn backend() -> &'static dyn MockBackend {
+    unsafe { BACKEND }
+}
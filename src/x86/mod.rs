@@ -1,4 +1,4 @@
-use crate::{cpu_features, cpu_register, x86::cpuid::CPUIDRequest, MemoryAddress, Register, cpu_vendor, segment_register};
+use crate::{cpu_features, cpu_register, CpuError, MemoryAddress, Register, cpu_vendor, segment_register};
 use bit_field::BitField;
 use bitflags::bitflags;
 use core::{
@@ -12,7 +12,30 @@ use core::{
 pub(crate) mod cpuid;
 pub mod gdt;
 pub mod interrupts;
+pub mod leaves;
 pub(crate) mod macros;
+pub mod memory;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod msr;
+pub mod protection_keys;
+pub mod random;
+pub mod sev;
+pub(crate) mod state;
+pub mod tsc;
+pub mod waitpkg;
+pub mod xsave;
+
+pub use cpuid::{
+    cpu_signature,
+    cpuid_available,
+    legacy_cache_descriptors,
+    max_basic_leaf,
+    max_extended_leaf,
+    CPUIDRequest,
+    CpuSignature,
+};
+pub use state::{fmt_control_registers, CpuState};
 
 /// This structure represents the privilege level for the descriptor. x86 and x86_64 CPUs are
 /// providing a few rings, but only 2 are used in Production-ready operating systems.
@@ -78,17 +101,64 @@ impl Display for PrivilegeLevel {
     }
 }
 
-/// This implementation is used to convert a bits into the privilege level enum. This function is
-/// used in [SegmentSelector::privilege_level]
+impl PrivilegeLevel {
+    /// This function returns the ring number (0 to 3) of the privilege level. Unlike the raw value
+    /// of the enum, that is shifted to the position of the DPL in the access byte of a descriptor,
+    /// the ring number is the value stored in the 2-bit DPL and RPL fields.
+    ///
+    /// ```rust
+    /// use libcpu::PrivilegeLevel;
+    ///
+    /// let levels = [
+    ///     PrivilegeLevel::KernelSpace,
+    ///     PrivilegeLevel::Ring1,
+    ///     PrivilegeLevel::Ring2,
+    ///     PrivilegeLevel::UserSpace,
+    /// ];
+    /// for (ring, level) in levels.into_iter().enumerate() {
+    ///     assert_eq!(level.as_ring(), ring as u8);
+    ///     assert_eq!(PrivilegeLevel::from_ring(level.as_ring()), Some(level));
+    /// }
+    /// assert_eq!(PrivilegeLevel::from_ring(4), None);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn as_ring(self) -> u8 {
+        (self as u8) >> 5
+    }
+
+    /// This function returns the privilege level of the specified ring number. If the ring number
+    /// is above 3, this function returns [None].
+    #[inline]
+    #[must_use]
+    pub const fn from_ring(ring: u8) -> Option<Self> {
+        match ring {
+            0 => Some(Self::KernelSpace),
+            1 => Some(Self::Ring1),
+            2 => Some(Self::Ring2),
+            3 => Some(Self::UserSpace),
+            _ => None,
+        }
+    }
+
+    // The DPL and RPL fields are 2 bits wide, so every masked value is a valid ring number
+    #[inline]
+    pub(crate) const fn from_ring_bits(bits: u8) -> Self {
+        match Self::from_ring(bits & 0b11) {
+            Some(level) => level,
+            None => unreachable!(),
+        }
+    }
+}
+
+/// This implementation is used to convert a bits into the privilege level enum. Values above 3
+/// are leading into a panic, use [PrivilegeLevel::from_ring] for a fallible conversion.
 impl From<u16> for PrivilegeLevel {
     #[must_use]
     fn from(value: u16) -> Self {
-        match value {
-            0x0 => Self::KernelSpace,
-            0x1 => Self::Ring1,
-            0x2 => Self::Ring2,
-            0x4 => Self::UserSpace,
-            _ => panic!("Invalid privilege level {}", value),
+        match u8::try_from(value).ok().and_then(Self::from_ring) {
+            Some(level) => level,
+            None => panic!("Invalid privilege level {}", value),
         }
     }
 }
@@ -178,15 +248,52 @@ impl SegmentSelector {
     /// - `privilege` - The requested privilege level. This value determines if the selector is
     /// valid during permission checks
     ///
+    /// The requested privilege level is placed in the bits 0 to 1 and the table indicator is placed
+    /// in bit 2 with the conversion into a bit (see [SegmentSelector::set_table]), the value of the
+    /// [DescriptorTable] enum isn't used.
+    ///
+    /// ```rust
+    /// use libcpu::{DescriptorTable, PrivilegeLevel, SegmentSelector};
+    ///
+    /// let selector = SegmentSelector::new(5, DescriptorTable::LDT, PrivilegeLevel::UserSpace);
+    /// assert_eq!(selector.index(), 5);
+    /// assert_eq!(selector.table(), DescriptorTable::LDT);
+    /// assert_eq!(selector.privilege_level(), PrivilegeLevel::UserSpace);
+    /// assert_eq!(selector.0, 0b0010_1111);
+    ///
+    /// let selector = SegmentSelector::new(5, DescriptorTable::LDT, PrivilegeLevel::KernelSpace);
+    /// assert_eq!(selector.table(), DescriptorTable::LDT);
+    /// assert_eq!(selector.index(), 5);
+    ///
+    /// let selector = SegmentSelector::new(5, DescriptorTable::GDT, PrivilegeLevel::KernelSpace);
+    /// assert_eq!(selector.table(), DescriptorTable::GDT);
+    /// assert_eq!(selector.index(), 5);
+    /// ```
+    ///
     /// - [Segment Selector](https://wiki.osdev.org/Segment_Selector) by
     /// [OSDev.org](https://wiki.osdev.org/)
     #[inline]
     #[must_use]
     pub fn new(index: u16, table: DescriptorTable, privilege: PrivilegeLevel) -> Self {
-        Self((index << 3) | (table as u16) | (privilege as u16 >> 5))
+        let mut selector = Self::default();
+        selector.set_index(index);
+        selector.set_table(table);
+        selector.set_privilege_level(privilege);
+        selector
     }
 
-    /// This function replaces the privilege level with the function-specific privilege level.
+    /// This function replaces the privilege level with the function-specific privilege level. The
+    /// value of the [PrivilegeLevel] enum is shifted into the bits 0 to 1 of the selector.
+    ///
+    /// ```rust
+    /// use libcpu::{PrivilegeLevel, SegmentSelector};
+    ///
+    /// let mut selector = SegmentSelector(0x08);
+    /// selector.set_privilege_level(PrivilegeLevel::UserSpace);
+    /// assert_eq!(selector, SegmentSelector(0x0B));
+    /// selector.set_privilege_level(PrivilegeLevel::Ring1);
+    /// assert_eq!(selector, SegmentSelector(0x09));
+    /// ```
     ///
     /// # See also
     /// - [CPU Security Rings](https://wiki.osdev.org/Security#Rings) by
@@ -198,7 +305,7 @@ impl SegmentSelector {
     /// - [PrivilegeLevel] (Source Code)
     #[inline]
     pub fn set_privilege_level(&mut self, level: PrivilegeLevel) {
-        self.0.set_bits(0..2, level as u16);
+        self.0.set_bits(0..2, u16::from(level.as_ring()));
     }
 
     /// This function returns the privilege level, set by the creator of this selector.
@@ -214,11 +321,21 @@ impl SegmentSelector {
     #[inline]
     #[must_use]
     pub fn privilege_level(&self) -> PrivilegeLevel {
-        PrivilegeLevel::from(self.0.get_bits(0..2))
+        PrivilegeLevel::from_ring_bits(self.0.get_bits(0..2) as u8)
     }
 
     /// This function replaces the descriptor table with the function-specific descriptor table.
     ///
+    /// ```rust
+    /// use libcpu::{DescriptorTable, SegmentSelector};
+    ///
+    /// let mut selector = SegmentSelector::default();
+    /// selector.set_table(DescriptorTable::LDT);
+    /// assert_eq!(selector.table(), DescriptorTable::LDT);
+    /// selector.set_table(DescriptorTable::GDT);
+    /// assert_eq!(selector.table(), DescriptorTable::GDT);
+    /// ```
+    ///
     /// # See also
     /// - [Segment Selector](https://wiki.osdev.org/Segment_Selector) by
     /// [OSDev.org](https://wiki.osdev.org/)
@@ -240,10 +357,23 @@ impl SegmentSelector {
         DescriptorTable::from(self.0.get_bit(2))
     }
 
-    /// This function replaces the descriptor index with the function-specific descriptor index.
+    /// This function replaces the descriptor index with the function-specific descriptor index. The
+    /// index is stored in the bits 3 to 15, so the table indicator and the privilege level are
+    /// kept.
+    ///
+    /// ```rust
+    /// use libcpu::SegmentSelector;
+    ///
+    /// let mut selector = SegmentSelector(0x0B);
+    /// selector.set_index(4);
+    /// assert_eq!(selector, SegmentSelector(0x23));
+    /// ```
+    ///
+    /// # Panics
+    /// This function panics, if the index doesn't fit into 13 bits.
     #[inline]
     pub fn set_index(&mut self, index: u16) {
-        self.0.set_bits(0..16, index);
+        self.0.set_bits(3..16, index);
     }
 
     /// This function returns the descriptor index, set by the creator of this selector.
@@ -261,6 +391,24 @@ impl SegmentSelector {
 /// (size is depending on the target processor architecture)
 /// - `size` - This field represents the size of the table in bytes. (subtracted by 1)
 ///
+/// The layout of this structure and the descriptors is checked at compile time. On x86_64, the
+/// sizes are the following:
+/// ```rust
+/// #![feature(offset_of)]
+/// use core::mem::{offset_of, size_of};
+/// use libcpu::{gdt::GDTDescriptor, DescriptorTablePointer};
+///
+/// #[cfg(target_arch = "x86_64")]
+/// {
+///     assert_eq!(size_of::<GDTDescriptor>(), 8);
+///     assert_eq!(size_of::<DescriptorTablePointer>(), 10);
+/// }
+///
+/// // The limit is directly followed by the base on x86 and x86_64
+/// assert_eq!(offset_of!(DescriptorTablePointer, size), 0);
+/// assert_eq!(offset_of!(DescriptorTablePointer, base), 2);
+/// ```
+///
 /// # See also
 /// - [Global Descriptor Table](https://wiki.osdev.org/Global_Descriptor_Table#GDTR) by
 /// [OSDev.org](https://wiki.osdev.org)
@@ -290,8 +438,56 @@ pub struct DescriptorTablePointer {
     pub base: MemoryAddress,
 }
 
+// The lgdt and lidt instructions are expecting the 16-bit limit directly followed by the base
+const _: () = assert!(
+    core::mem::size_of::<DescriptorTablePointer>() == 2 + core::mem::size_of::<MemoryAddress>(),
+    "The descriptor table pointer must be packed"
+);
+const _: () = assert!(
+    core::mem::offset_of!(DescriptorTablePointer, size) == 0,
+    "The limit must be the first field of the descriptor table pointer"
+);
+const _: () = assert!(
+    core::mem::offset_of!(DescriptorTablePointer, base) == 2,
+    "The base must directly follow the limit of the descriptor table pointer"
+);
+
+/// This function reads the pointer to the currently loaded Global Descriptor Table with the `sgdt`
+/// instruction.
+///
+/// # See also
+/// - [SGDT](https://www.felixcloutier.com/x86/sgdt) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn read_gdtr() -> DescriptorTablePointer {
+    let mut pointer = DescriptorTablePointer { size: 0, base: 0 };
+    unsafe {
+        asm!("sgdt [{}]", in(reg) &mut pointer, options(nostack, preserves_flags));
+    }
+    pointer
+}
+
+/// This function reads the pointer to the currently loaded Interrupt Descriptor Table with the
+/// `sidt` instruction.
+///
+/// # See also
+/// - [SIDT](https://www.felixcloutier.com/x86/sidt) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn read_idtr() -> DescriptorTablePointer {
+    let mut pointer = DescriptorTablePointer { size: 0, base: 0 };
+    unsafe {
+        asm!("sidt [{}]", in(reg) &mut pointer, options(nostack, preserves_flags));
+    }
+    pointer
+}
+
 // https://github.com/rust-osdev/x86_64/blob/master/src/instructions/segmentation.rs#L74
 pub fn set_cs(selector: SegmentSelector) {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_segment_register("cs", selector.0);
+
+    #[cfg(not(feature = "mock"))]
     unsafe {
         asm!(
         "push {sel}",
@@ -306,11 +502,17 @@ pub fn set_cs(selector: SegmentSelector) {
     }
 }
 
+#[allow(unused_assignments)]
 pub fn get_cs() -> SegmentSelector {
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_segment_register("cs");
+
+    #[cfg(not(feature = "mock"))]
     let mut value = 0;
+    #[cfg(not(feature = "mock"))]
     unsafe {
         asm!(
-            "mov {}, cs",
+            "mov {:x}, cs",
             out(reg) value,
             options(nomem, nostack, preserves_flags)
         );
@@ -318,12 +520,345 @@ pub fn get_cs() -> SegmentSelector {
     SegmentSelector(value)
 }
 
+/// This function returns the current privilege level (CPL) of the processor. The CPL is stored in
+/// the low two bits of the code segment selector, so it can be used to check whether a fault came
+/// from the user or the kernel space.
+///
+/// ```rust,no_run
+/// use libcpu::{current_privilege_level, PrivilegeLevel};
+///
+/// assert_eq!(current_privilege_level(), PrivilegeLevel::KernelSpace);
+/// ```
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{current_privilege_level, set_cs, PrivilegeLevel, SegmentSelector};
+
+set_cs(SegmentSelector(0x23));
+assert_eq!(current_privilege_level(), PrivilegeLevel::UserSpace);
+```
+"#)]
+///
+/// # See also
+/// - [CPU Security Rings](https://wiki.osdev.org/Security#Rings) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+pub fn current_privilege_level() -> PrivilegeLevel {
+    get_cs().privilege_level()
+}
+
 segment_register!(ds, "ds");
 segment_register!(es, "es");
 segment_register!(ss, "ss");
 segment_register!(fs, "fs");
 segment_register!(gs, "gs");
 
+/// This trait abstracts the access to a segment register, so generic code can be written over the
+/// segment registers. Every segment register is represented by a zero-sized marker type ([CS],
+/// [DS], [ES], [FS], [GS] and [SS]).
+///
+/// ```rust,no_run
+/// use libcpu::{Segment, SegmentSelector, DS, SS};
+///
+/// fn reload<S: Segment>(selector: SegmentSelector) {
+///     unsafe { S::write(selector) };
+/// }
+///
+/// reload::<DS>(SS::read());
+/// ```
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{Segment, SegmentSelector, CS, DS, ES, FS, GS, SS};
+
+fn round_trip<S: Segment>(selector: SegmentSelector) {
+    unsafe { S::write(selector) };
+    assert_eq!(S::read(), selector);
+}
+
+round_trip::<CS>(SegmentSelector(0x08));
+round_trip::<DS>(SegmentSelector(0x10));
+round_trip::<ES>(SegmentSelector(0x10));
+round_trip::<FS>(SegmentSelector(0x23));
+round_trip::<GS>(SegmentSelector(0x23));
+round_trip::<SS>(SegmentSelector(0x10));
+```
+"#)]
+///
+/// # See also
+/// - [Segmentation](https://wiki.osdev.org/Segmentation) by [OSDev.org](https://wiki.osdev.org/)
+pub trait Segment {
+    /// This function reads the selector from the segment register.
+    #[must_use]
+    fn read() -> SegmentSelector;
+
+    /// This function writes the selector into the segment register.
+    ///
+    /// # Safety
+    /// The caller must ensure, that the selector references a valid descriptor for this segment
+    /// register, otherwise the CPU raises a General Protection Fault.
+    unsafe fn write(selector: SegmentSelector);
+}
+
+/// This structure represents the code segment register (CS).
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct CS;
+
+/// This structure represents the data segment register (DS).
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct DS;
+
+/// This structure represents the extra segment register (ES).
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct ES;
+
+/// This structure represents the FS segment register.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct FS;
+
+/// This structure represents the GS segment register.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct GS;
+
+/// This structure represents the stack segment register (SS).
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct SS;
+
+macro_rules! segment_marker {
+    ($($marker: ident => $get: ident, $set: ident);*) => {
+        $(
+        impl Segment for $marker {
+            #[inline]
+            fn read() -> SegmentSelector {
+                $get()
+            }
+
+            #[inline]
+            unsafe fn write(selector: SegmentSelector) {
+                $set(selector);
+            }
+        }
+        )*
+    };
+}
+
+segment_marker! {
+    CS => get_cs, set_cs;
+    DS => get_ds, set_ds;
+    ES => get_es, set_es;
+    FS => get_fs, set_fs;
+    GS => get_gs, set_gs;
+    SS => get_ss, set_ss
+}
+
+/// This structure captures the selectors of the data segment registers (DS, ES, FS, GS and SS) on
+/// creation and restores them when it's dropped. So a scope (like a trampoline) can load other
+/// segments temporarily and the previous segments are restored also if the scope panics. The code
+/// segment isn't captured, because it can only be changed with a far jump or return.
+///
+/// On x86_64, writing the FS or GS register replaces the hidden base with the base of the
+/// descriptor, so the bases, that were written into the FS and GS base MSRs, must be restored by
+/// the caller after the guard is dropped.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{get_ds, get_gs, get_ss, set_ds, set_gs, set_ss, SegmentGuard, SegmentSelector};
+
+set_ds(SegmentSelector(0x10));
+set_gs(SegmentSelector(0x00));
+set_ss(SegmentSelector(0x10));
+{
+    let _guard = SegmentGuard::new();
+    set_ds(SegmentSelector(0x23));
+    set_gs(SegmentSelector(0x23));
+    set_ss(SegmentSelector(0x23));
+    assert_eq!(get_ds(), SegmentSelector(0x23));
+}
+assert_eq!(get_ds(), SegmentSelector(0x10));
+assert_eq!(get_gs(), SegmentSelector(0x00));
+assert_eq!(get_ss(), SegmentSelector(0x10));
+```
+"#)]
+///
+/// # See also
+/// - [Segment] (Source Code)
+#[derive(Debug)]
+pub struct SegmentGuard {
+    ds: SegmentSelector,
+    es: SegmentSelector,
+    fs: SegmentSelector,
+    gs: SegmentSelector,
+    ss: SegmentSelector,
+}
+
+impl Default for SegmentGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SegmentGuard {
+    /// This function captures the current selectors of the data segment registers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            ds: DS::read(),
+            es: ES::read(),
+            fs: FS::read(),
+            gs: GS::read(),
+            ss: SS::read(),
+        }
+    }
+}
+
+impl Drop for SegmentGuard {
+    fn drop(&mut self) {
+        // The captured selectors were loaded before, so they are referencing valid descriptors
+        unsafe {
+            SS::write(self.ss);
+            DS::write(self.ds);
+            ES::write(self.es);
+            FS::write(self.fs);
+            GS::write(self.gs);
+        }
+    }
+}
+
+struct GSRelativeSize<T>(core::marker::PhantomData<T>);
+
+impl<T> GSRelativeSize<T> {
+    const VALID: () = assert!(
+        matches!(core::mem::size_of::<T>(), 1 | 2 | 4 | 8),
+        "GS-relative accesses are only supported for values with a size of 1, 2, 4 or 8 bytes"
+    );
+}
+
+/// This function reads a value at the specified offset relative to the GS base with a `mov`
+/// instruction and a segment override. This is used to access the fields of the per-CPU structure,
+/// which is stored in the GS base by the kernel. Only values with a size of 1, 2, 4 or 8 bytes are
+/// supported, other sizes are failing to compile.
+///
+/// ```rust,no_run
+/// use libcpu::{msr::set_gs_base, read_gs_relative, write_gs_relative};
+///
+/// static mut PER_CPU: [u64; 2] = [0; 2];
+///
+/// unsafe {
+///     set_gs_base(PER_CPU.as_ptr() as u64);
+///     write_gs_relative::<u64>(8, 0xDEAD_BEEF);
+///     assert_eq!(read_gs_relative::<u64>(8), 0xDEAD_BEEF);
+/// }
+/// ```
+///
+/// # Safety
+/// The caller must ensure, that the GS base points to a valid structure and the value at the
+/// offset is a valid and initialized value of the type.
+///
+/// # See also
+/// - [Thread Local Storage](https://wiki.osdev.org/Thread_Local_Storage) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+pub unsafe fn read_gs_relative<T: Copy>(offset: usize) -> T {
+    #[allow(clippy::let_unit_value)]
+    let _ = GSRelativeSize::<T>::VALID;
+    match core::mem::size_of::<T>() {
+        1 => {
+            let value: u8;
+            asm!(
+                "mov {}, gs:[{}]",
+                out(reg_byte) value,
+                in(reg) offset,
+                options(nostack, preserves_flags, readonly),
+            );
+            core::mem::transmute_copy(&value)
+        }
+        2 => {
+            let value: u16;
+            asm!(
+                "mov {:x}, gs:[{}]",
+                out(reg) value,
+                in(reg) offset,
+                options(nostack, preserves_flags, readonly),
+            );
+            core::mem::transmute_copy(&value)
+        }
+        4 => {
+            let value: u32;
+            asm!(
+                "mov {:e}, gs:[{}]",
+                out(reg) value,
+                in(reg) offset,
+                options(nostack, preserves_flags, readonly),
+            );
+            core::mem::transmute_copy(&value)
+        }
+        _ => {
+            let value: u64;
+            asm!(
+                "mov {}, gs:[{}]",
+                out(reg) value,
+                in(reg) offset,
+                options(nostack, preserves_flags, readonly),
+            );
+            core::mem::transmute_copy(&value)
+        }
+    }
+}
+
+/// This function writes the value at the specified offset relative to the GS base with a `mov`
+/// instruction and a segment override. Only values with a size of 1, 2, 4 or 8 bytes are
+/// supported, other sizes are failing to compile.
+///
+/// # Safety
+/// The caller must ensure, that the GS base points to a valid and writable structure and the
+/// offset is in the bounds of the structure.
+///
+/// # See also
+/// - [Thread Local Storage](https://wiki.osdev.org/Thread_Local_Storage) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+pub unsafe fn write_gs_relative<T: Copy>(offset: usize, value: T) {
+    #[allow(clippy::let_unit_value)]
+    let _ = GSRelativeSize::<T>::VALID;
+    match core::mem::size_of::<T>() {
+        1 => {
+            let value: u8 = core::mem::transmute_copy(&value);
+            asm!(
+                "mov gs:[{}], {}",
+                in(reg) offset,
+                in(reg_byte) value,
+                options(nostack, preserves_flags),
+            );
+        }
+        2 => {
+            let value: u16 = core::mem::transmute_copy(&value);
+            asm!(
+                "mov gs:[{}], {:x}",
+                in(reg) offset,
+                in(reg) value,
+                options(nostack, preserves_flags),
+            );
+        }
+        4 => {
+            let value: u32 = core::mem::transmute_copy(&value);
+            asm!(
+                "mov gs:[{}], {:e}",
+                in(reg) offset,
+                in(reg) value,
+                options(nostack, preserves_flags),
+            );
+        }
+        _ => {
+            let value: u64 = core::mem::transmute_copy(&value);
+            asm!(
+                "mov gs:[{}], {}",
+                in(reg) offset,
+                in(reg) value,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
+
 bitflags! {
     #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
     pub struct CR0Flags: Register {
@@ -341,8 +876,110 @@ bitflags! {
     }
 }
 
+impl CR0Flags {
+    /// This constant represents the bits of the CR0 register, that are reserved. Setting one of
+    /// these bits leads into a General Protection Fault.
+    pub const RESERVED_BITS: Register = !(0b11_1111 | (1 << 16) | (1 << 18) | (0b111 << 29));
+}
+
 cpu_register!(cr0, "cr0", CR0Flags);
 
+/// This function sets the specified flags in the CR0 register, after checking that no reserved bit
+/// is set in the flags. Setting a reserved bit leads into a General Protection Fault, so this
+/// function returns [CpuError::ReservedBitsSet] with the reserved bits of the flags instead and
+/// CR0 is not modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{get_cr0, set_cr0_checked, CpuError, CR0Flags};
+
+let result = set_cr0_checked(CR0Flags::from_bits_retain(1 << 6) | CR0Flags::PAGING);
+assert_eq!(result, Err(CpuError::ReservedBitsSet(1 << 6)));
+assert_eq!(get_cr0(), CR0Flags::empty());
+
+assert_eq!(set_cr0_checked(CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::PAGING), Ok(()));
+assert_eq!(get_cr0(), CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::PAGING);
+```
+"#)]
+///
+/// # See also
+/// - [CR0Flags::RESERVED_BITS] (Source Code)
+/// - [CR0](https://wiki.osdev.org/CPU_Registers_x86#CR0) by [OSDev.org](https://wiki.osdev.org/)
+#[allow(clippy::unnecessary_cast)] // The register is only 32-bit wide on x86
+pub fn set_cr0_checked(flags: CR0Flags) -> Result<(), CpuError> {
+    let reserved_bits = flags.bits() & CR0Flags::RESERVED_BITS;
+    if reserved_bits != 0 {
+        return Err(CpuError::ReservedBitsSet(reserved_bits as u64));
+    }
+
+    set_cr0(flags);
+    Ok(())
+}
+
+/// This function clears [CR0Flags::WRITE_PROTECTED] in the CR0 register, runs the closure and
+/// restores the previous value of CR0. Without the write protection, the kernel can write into
+/// read-only pages (e.g. to patch code). The maskable interrupts are disabled while the closure
+/// runs, so no interrupt handler or context switch observes the relaxed protection, and enabled
+/// again afterwards, if they were enabled before.
+///
+/// SMEP and SMAP are still active while the write protection is disabled, so the closure can't
+/// access user pages.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{get_cr0, interrupts::*, set_cr0, with_write_protect_disabled, CR0Flags};
+
+set_cr0(CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::WRITE_PROTECTED | CR0Flags::PAGING);
+enable_interrupts();
+
+let value = with_write_protect_disabled(|| {
+    assert!(!get_cr0().contains(CR0Flags::WRITE_PROTECTED));
+    assert!(!interrupts_enabled());
+    42
+});
+assert_eq!(value, 42);
+assert_eq!(
+    get_cr0(),
+    CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::WRITE_PROTECTED | CR0Flags::PAGING
+);
+assert!(interrupts_enabled());
+```
+"#)]
+///
+/// # Panics
+/// This function panics, if [CR4Flags::ControlFlowEnforcement] is set in the CR4 register,
+/// because clearing the write protection with enabled CET leads into a General Protection Fault.
+///
+/// # See also
+/// - [CR0](https://wiki.osdev.org/CPU_Registers_x86#CR0) by [OSDev.org](https://wiki.osdev.org/)
+pub fn with_write_protect_disabled<R>(f: impl FnOnce() -> R) -> R {
+    assert!(
+        !get_cr4().contains(CR4Flags::ControlFlowEnforcement),
+        "The write protection can't be disabled while the CET is enabled"
+    );
+
+    interrupts::without_interrupts(|| {
+        let cr0 = get_cr0();
+        write_cr0_raw(cr0 - CR0Flags::WRITE_PROTECTED);
+        let result = f();
+        write_cr0_raw(cr0);
+        result
+    })
+}
+
+// The CR0 setter only sets flags, so clearing a flag needs the value to be written directly. The
+// write isn't marked as `nomem`, so the stores of the closure aren't moved across the toggle.
+fn write_cr0_raw(value: CR0Flags) {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_control_register("cr0", value.bits());
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("mov cr0, {}", in(reg) value.bits(), options(nostack, preserves_flags));
+    }
+}
+
+// CR2 contains the linear address, that caused the last Page Fault
+cpu_register!(cr2, "cr2");
+
 bitflags! {
     #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
     pub struct CR3Flags: Register {
@@ -353,6 +990,74 @@ bitflags! {
 
 cpu_register!(cr3, "cr3", CR3Flags);
 
+/// This function switches the address space by writing the physical address of the PML4 table and
+/// the Process Context Identifier (PCID) into the CR3 register. The PCID is stored in the low 12
+/// bits of CR3. If `flush` is false, bit 63 of CR3 is set, so the TLB entries of the PCID are kept
+/// and the switch is cheaper. If the PCID is greater than 4095, [CpuError::InvalidPcid] is
+/// returned and CR3 is not modified.
+///
+/// Without [CR4Flags::PCID_ENABLE], the PCID must be zero and the TLB is always flushed. Otherwise
+/// the write would lead into a General Protection Fault, so [CpuError::UnsupportedFeature] with
+/// [CPUFeature::PCID] is returned and CR3 is not modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{
+    mock::{register_file, MockBackend}, switch_address_space, CPUFeature, CR4Flags, CpuError,
+};
+
+let result = unsafe { switch_address_space(0x1234_5000, 7, true) };
+assert_eq!(result, Err(CpuError::UnsupportedFeature(CPUFeature::PCID)));
+let result = unsafe { switch_address_space(0x1234_5000, 0, false) };
+assert_eq!(result, Err(CpuError::UnsupportedFeature(CPUFeature::PCID)));
+assert_eq!(register_file().read_control_register("cr3"), 0);
+
+unsafe { switch_address_space(0x1234_5000, 0, true) }.unwrap();
+assert_eq!(register_file().read_control_register("cr3"), 0x1234_5000);
+
+register_file().write_control_register("cr4", CR4Flags::PCID_ENABLE.bits());
+unsafe { switch_address_space(0x1234_5000, 7, true) }.unwrap();
+assert_eq!(register_file().read_control_register("cr3"), 0x1234_5007);
+
+unsafe { switch_address_space(0x1234_5000, 7, false) }.unwrap();
+assert_eq!(register_file().read_control_register("cr3"), (1 << 63) | 0x1234_5007);
+
+let result = unsafe { switch_address_space(0x6789_A000, 4096, true) };
+assert_eq!(result, Err(CpuError::InvalidPcid(4096)));
+assert_eq!(register_file().read_control_register("cr3"), (1 << 63) | 0x1234_5007);
+```
+"#)]
+///
+/// # Safety
+/// The caller must ensure, that the PML4 table maps the currently executed code and the stack, and
+/// that the TLB entries of the PCID are still valid if `flush` is false.
+///
+/// # Panics
+/// This function panics, if the address of the PML4 table isn't aligned to 4 KiB.
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 4.10.4.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn switch_address_space(pml4: u64, pcid: u16, flush: bool) -> Result<(), CpuError> {
+    assert_eq!(pml4 & 0xFFF, 0, "The PML4 table must be aligned to 4 KiB");
+    if pcid > 0xFFF {
+        return Err(CpuError::InvalidPcid(pcid));
+    }
+    if !get_cr4().contains(CR4Flags::PCID_ENABLE) && (pcid != 0 || !flush) {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::PCID));
+    }
+
+    let mut value = pml4 | u64::from(pcid);
+    value.set_bit(63, !flush);
+
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_control_register("cr3", value);
+
+    #[cfg(not(feature = "mock"))]
+    asm!("mov cr3, {}", in(reg) value, options(nostack, preserves_flags));
+    Ok(())
+}
+
 bitflags! {
     #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
     pub struct CR4Flags: Register {
@@ -368,6 +1073,7 @@ bitflags! {
         const OSSupportForFXSR           = 1 << 9;
         const OSSupportXMMExcept         = 1 << 10;
         const UMIP                       = 1 << 11;
+        const LA57                       = 1 << 12;
         const VIRTUAL_MACHINE_EXT_ENABLE = 1 << 13;
         const SAFER_MODE_EXT_ENABLE      = 1 << 14;
         const FSGSBASE                   = 1 << 16;
@@ -377,12 +1083,360 @@ bitflags! {
         const SMAP                       = 1 << 21;
         const ProtectionKeyEnable        = 1 << 22;
         const ControlFlowEnforcement     = 1 << 23;
-        const PKS                        = 1 << 34;
+        const PKS                        = 1 << 24;
+    }
+}
+
+impl CR4Flags {
+    /// This constant represents the bits of the CR4 register, that are reserved. Setting one of
+    /// these bits leads into a General Protection Fault.
+    pub const RESERVED_BITS: Register = !(0x7FFF | (0x3FF << 16) | (0b11 << 27));
+
+    /// This table maps the flags of the CR4 register to the CPU features, that must be supported
+    /// by the processor before the flag can be set.
+    const REQUIRED_FEATURES: [(CR4Flags, CPUFeature); 22] = [
+        (CR4Flags::VME, CPUFeature::VME),
+        (CR4Flags::PVI, CPUFeature::VME),
+        (CR4Flags::TIMESTAMP_DISABLE, CPUFeature::TSC),
+        (CR4Flags::DEBUGGING_EXTENSIONS, CPUFeature::DE),
+        (CR4Flags::PAGE_SIZE_EXTENSION, CPUFeature::PSE),
+        (CR4Flags::PHYSCIAL_ADDRESS_EXTENSION, CPUFeature::PAE),
+        (CR4Flags::MACHINE_CHECK_EXCEPTION, CPUFeature::MCE),
+        (CR4Flags::PAGE_GLOBAL_ENABLED, CPUFeature::PGE),
+        (CR4Flags::OSSupportForFXSR, CPUFeature::FXSR),
+        (CR4Flags::OSSupportXMMExcept, CPUFeature::SSE),
+        (CR4Flags::UMIP, CPUFeature::UMIP),
+        (CR4Flags::LA57, CPUFeature::LA57),
+        (CR4Flags::VIRTUAL_MACHINE_EXT_ENABLE, CPUFeature::VMX),
+        (CR4Flags::SAFER_MODE_EXT_ENABLE, CPUFeature::SMX),
+        (CR4Flags::FSGSBASE, CPUFeature::FSGSBase),
+        (CR4Flags::PCID_ENABLE, CPUFeature::PCID),
+        (CR4Flags::OSXSAVE_ENABLE, CPUFeature::XSAVE),
+        (CR4Flags::SMEP, CPUFeature::SMEP),
+        (CR4Flags::SMAP, CPUFeature::SMAP),
+        (CR4Flags::ProtectionKeyEnable, CPUFeature::PKU),
+        (CR4Flags::ControlFlowEnforcement, CPUFeature::ShadowStack),
+        (CR4Flags::PKS, CPUFeature::PKS),
+    ];
+
+    /// This function returns the CPU features, that must be supported by the processor before the
+    /// flags can be set in the CR4 register. Flags without a required feature are ignored. This
+    /// function is only available with the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn required_features(self) -> alloc::vec::Vec<CPUFeature> {
+        let mut features: alloc::vec::Vec<CPUFeature> = Self::REQUIRED_FEATURES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, feature)| *feature)
+            .collect();
+        features.dedup();
+        features
     }
 }
 
 cpu_register!(cr4, "cr4", CR4Flags);
 
+/// This function sets the specified flags in the CR4 register, after checking that no reserved bit
+/// is set in the flags and that the processor supports the features required by these flags.
+/// Setting a reserved bit or a flag without the required feature leads into a General Protection
+/// Fault, so this function returns [CpuError::ReservedBitsSet] with the reserved bits of the flags
+/// or [CpuError::UnsupportedFeature] with the first missing feature instead and CR4 is not
+/// modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{get_cr4, set_cr4_checked, CpuError, CR4Flags};
+
+let result = set_cr4_checked(CR4Flags::from_bits_retain(1 << 15));
+assert_eq!(result, Err(CpuError::ReservedBitsSet(1 << 15)));
+assert_eq!(get_cr4(), CR4Flags::empty());
+
+assert_eq!(set_cr4_checked(CR4Flags::PCE), Ok(()));
+assert_eq!(get_cr4(), CR4Flags::PCE);
+```
+"#)]
+///
+/// # See also
+/// - [CR4Flags::RESERVED_BITS] (Source Code)
+/// - [CR4Flags::required_features] (Source Code)
+#[allow(clippy::unnecessary_cast)] // The register is only 32-bit wide on x86
+pub fn set_cr4_checked(flags: CR4Flags) -> Result<(), CpuError> {
+    let reserved_bits = flags.bits() & CR4Flags::RESERVED_BITS;
+    if reserved_bits != 0 {
+        return Err(CpuError::ReservedBitsSet(reserved_bits as u64));
+    }
+
+    let enabled_features = CPUFeature::enabled_feature_set();
+    if let Some((_, feature)) = CR4Flags::REQUIRED_FEATURES
+        .iter()
+        .find(|(flag, feature)| flags.contains(*flag) && !enabled_features.contains(*feature))
+    {
+        return Err(CpuError::UnsupportedFeature(*feature));
+    }
+
+    set_cr4(flags);
+    Ok(())
+}
+
+/// This function returns whether the processor supports 5-level paging with 57-bit linear
+/// addresses. This is indicated by the [CPUFeature::LA57] feature.
+///
+/// # See also
+/// - [5-Level Paging](https://en.wikipedia.org/wiki/Intel_5-level_paging) by
+/// [Wikipedia](https://wikipedia.org)
+#[inline]
+#[must_use]
+pub fn supports_5level_paging() -> bool {
+    CPUFeature::LA57.is_supported()
+}
+
+/// This function enables 5-level paging by setting [CR4Flags::LA57] in the CR4 register. If the
+/// processor doesn't support 5-level paging, [CpuError::UnsupportedFeature] is returned and CR4 is
+/// not modified.
+///
+/// The paging mode can't be changed while paging is enabled. Writing CR4.LA57 in long mode leads
+/// into a General Protection Fault, so the caller has to ensure these preconditions:
+/// - Paging is disabled (CR0.PG is cleared), so the CPU is in protected mode while switching
+/// - CR3 points to a PML5 table before paging is enabled again
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{enable_5level_paging, get_cr4, set_cr4, CPUFeature, CR4Flags, CpuError};
+
+set_cr4(CR4Flags::PHYSCIAL_ADDRESS_EXTENSION);
+assert_eq!(enable_5level_paging(), Err(CpuError::UnsupportedFeature(CPUFeature::LA57)));
+assert_eq!(get_cr4(), CR4Flags::PHYSCIAL_ADDRESS_EXTENSION);
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{enable_5level_paging, get_cr4, mock::register_file, CR4Flags};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 16, edx: 0 });
+enable_5level_paging().unwrap();
+assert!(get_cr4().contains(CR4Flags::LA57));
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 4.1.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+pub fn enable_5level_paging() -> Result<(), CpuError> {
+    set_cr4_checked(CR4Flags::LA57)
+}
+
+/// This function returns whether the processor supports User-Mode Instruction Prevention (UMIP).
+/// This is indicated by the [CPUFeature::UMIP] feature.
+#[inline]
+#[must_use]
+pub fn umip_supported() -> bool {
+    CPUFeature::UMIP.is_supported()
+}
+
+/// This function enables User-Mode Instruction Prevention by setting [CR4Flags::UMIP] in the CR4
+/// register. If the processor doesn't support UMIP, [CpuError::UnsupportedFeature] is returned and
+/// CR4 is not modified.
+///
+/// With UMIP, the `sgdt`, `sidt`, `sldt`, `smsw` and `str` instructions are raising a General
+/// Protection Fault in the user space. These instructions are reading the addresses of the kernel
+/// tables and are otherwise allowed in every privilege level, so the user space can use them to
+/// defeat the Kernel Address Space Layout Randomization (KASLR).
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{enable_umip, get_cr4, mock::register_file, umip_supported, CR4Flags};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 2, edx: 0 });
+assert!(umip_supported());
+assert_eq!(enable_umip(), Ok(()));
+assert!(get_cr4().contains(CR4Flags::UMIP));
+```
+
+```rust
+use libcpu::{enable_umip, get_cr4, umip_supported, CPUFeature, CR4Flags, CpuError};
+
+assert!(!umip_supported());
+assert_eq!(enable_umip(), Err(CpuError::UnsupportedFeature(CPUFeature::UMIP)));
+assert!(!get_cr4().contains(CR4Flags::UMIP));
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 2.5 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+pub fn enable_umip() -> Result<(), CpuError> {
+    set_cr4_checked(CR4Flags::UMIP)
+}
+
+/// This function returns whether the caches are disabled by the [CR0Flags::CACHE_DISABLE] flag in
+/// the CR0 register. After a reset, the flag and [CR0Flags::NOT_WRITE_THROUGH] are set until the
+/// firmware enables the caches. If both flags are still set, the processor doesn't fill the caches
+/// anymore, which is used by the firmware to run in the cache-as-RAM mode before the memory is
+/// initialized. A kernel can use this function to detect an unusual cache configuration at startup.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{is_cache_disabled, set_cr0, CR0Flags};
+
+set_cr0(CR0Flags::CACHE_DISABLE | CR0Flags::NOT_WRITE_THROUGH);
+assert!(is_cache_disabled());
+```
+
+```rust
+use libcpu::{is_cache_disabled, set_cr0, CR0Flags};
+
+set_cr0(CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::PAGING);
+assert!(!is_cache_disabled());
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 12.5.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[inline]
+#[must_use]
+pub fn is_cache_disabled() -> bool {
+    get_cr0().contains(CR0Flags::CACHE_DISABLE)
+}
+
+/// This function writes the task priority into the CR8 register. In 64-bit mode, CR8 mirrors the
+/// task-priority register of the local APIC, so interrupts with a priority class lower or equal to
+/// the task priority are not delivered. Only the low 4 bits of the priority are used.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{get_cr8, mock::{register_file, MockBackend}, set_cr8};
+
+set_cr8(0xFA);
+assert_eq!(register_file().read_control_register("cr8"), 0xA);
+assert_eq!(get_cr8(), 0xA);
+```
+"#)]
+///
+/// # See also
+/// - [APIC](https://wiki.osdev.org/APIC#Task_Priority_Register) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[cfg(target_arch = "x86_64")]
+pub fn set_cr8(priority: u8) {
+    let value = Register::from(priority & 0xF);
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_control_register("cr8", value);
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("mov cr8, {}", in(reg) value, options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// This function returns the task priority from the CR8 register. Only the low 4 bits of the
+/// register are used.
+///
+/// # See also
+/// - [APIC](https://wiki.osdev.org/APIC#Task_Priority_Register) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+#[cfg(target_arch = "x86_64")]
+pub fn get_cr8() -> u8 {
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_control_register("cr8");
+
+    #[cfg(not(feature = "mock"))]
+    let value: Register;
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("mov {}, cr8", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+    (value & 0xF) as u8
+}
+
+// Only the control registers, that can be accessed with the `mov` instruction, are known
+#[cfg(feature = "mock")]
+fn control_register_name(register: u8) -> Result<&'static str, CpuError> {
+    match register {
+        0 => Ok("cr0"),
+        2 => Ok("cr2"),
+        3 => Ok("cr3"),
+        4 => Ok("cr4"),
+        #[cfg(target_arch = "x86_64")]
+        8 => Ok("cr8"),
+        _ => Err(CpuError::InvalidControlRegister(register)),
+    }
+}
+
+/// This function reads the raw value of the control register with the specified number (CR0, CR2,
+/// CR3, CR4 and on x86_64 CR8). This is used by code, that handles the control registers
+/// generically (like a VMM or a debugger). The typed accessors (like [get_cr0]) are preferred for
+/// all other code. If the number doesn't identify one of these control registers, this function
+/// returns [CpuError::InvalidControlRegister].
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{read_control_register, write_control_register, CpuError};
+
+for register in [0, 2, 3, 4, 8] {
+    let value = 0x1000 + u64::from(register);
+    assert_eq!(write_control_register(register, value), Ok(()));
+    assert_eq!(read_control_register(register), Ok(value));
+}
+
+for register in [1, 5, 7, 9, 15] {
+    let error = Err(CpuError::InvalidControlRegister(register));
+    assert_eq!(read_control_register(register), error);
+    assert_eq!(write_control_register(register, 0), error.map(|_| ()));
+}
+```
+"#)]
+///
+/// # See also
+/// - [MOV - Move to/from Control Registers](https://www.felixcloutier.com/x86/mov-1) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn read_control_register(register: u8) -> Result<Register, CpuError> {
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_control_register(control_register_name(register)?);
+
+    #[cfg(not(feature = "mock"))]
+    let value: Register;
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        match register {
+            0 => asm!("mov {}, cr0", out(reg) value, options(nomem, nostack, preserves_flags)),
+            2 => asm!("mov {}, cr2", out(reg) value, options(nomem, nostack, preserves_flags)),
+            3 => asm!("mov {}, cr3", out(reg) value, options(nomem, nostack, preserves_flags)),
+            4 => asm!("mov {}, cr4", out(reg) value, options(nomem, nostack, preserves_flags)),
+            #[cfg(target_arch = "x86_64")]
+            8 => asm!("mov {}, cr8", out(reg) value, options(nomem, nostack, preserves_flags)),
+            _ => return Err(CpuError::InvalidControlRegister(register)),
+        }
+    }
+    Ok(value)
+}
+
+/// This function writes the raw value into the control register with the specified number (CR0,
+/// CR2, CR3, CR4 and on x86_64 CR8). Unlike the typed setters (like [set_cr0]), the value isn't
+/// merged with the current value of the register and isn't validated. If the number doesn't
+/// identify one of these control registers, this function returns
+/// [CpuError::InvalidControlRegister] and no register is written.
+///
+/// # See also
+/// - [MOV - Move to/from Control Registers](https://www.felixcloutier.com/x86/mov-1) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn write_control_register(register: u8, value: Register) -> Result<(), CpuError> {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_control_register(control_register_name(register)?, value);
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        match register {
+            0 => asm!("mov cr0, {}", in(reg) value, options(nostack, preserves_flags)),
+            2 => asm!("mov cr2, {}", in(reg) value, options(nostack, preserves_flags)),
+            3 => asm!("mov cr3, {}", in(reg) value, options(nostack, preserves_flags)),
+            4 => asm!("mov cr4, {}", in(reg) value, options(nostack, preserves_flags)),
+            #[cfg(target_arch = "x86_64")]
+            8 => asm!("mov cr8, {}", in(reg) value, options(nomem, nostack, preserves_flags)),
+            _ => return Err(CpuError::InvalidControlRegister(register)),
+        }
+    }
+    Ok(())
+}
+
 cpu_vendor! {
     /// This enum lists all variant of known x86 processor vendors. You can call [request_cpu_vendor]
     /// to get the enum value of the processor vendor on the current system.
@@ -550,7 +1604,7 @@ cpu_features! {
         AES               (ecx, "Hardware-accelerated AES Instruction Set", CPUIDRequest::Features) = 1 << 25,
         XSAVE             (ecx, "Extensible processor state restore instructions", CPUIDRequest::Features) = 1 << 26,
         OSXSAVE           (ecx, "XSAVE enabled by OS", CPUIDRequest::Features) = 1 << 27,
-        AVX               (ecx, "Advanced Vector Extensions (256-bit SIMD)", CPUIDRequest::Features) = 1 << 28,
+        AVX               (ecx, "Advanced Vector Extensions (256-bit SIMD)", CPUIDRequest::Features, introduced = "AVX (Sandy Bridge)") = 1 << 28,
         F16C              (ecx, "Floating-point conversion instructions to/from FP16 format", CPUIDRequest::Features) = 1 << 29,
         RDRAND            (ecx, "RDRAND (HRNG) feature", CPUIDRequest::Features) = 1 << 30,
         HYPERVISOR        (ecx, "Hypervisor is present", CPUIDRequest::Features) = 1 << 31,
@@ -589,7 +1643,7 @@ cpu_features! {
         SGX               (ebx, "Software Guard Extensions", CPUIDRequest::ExtendedFeatures1) = 1 << 2,
         BMI1              (ebx, "Bit Manipulation Instruction Set 1", CPUIDRequest::ExtendedFeatures1) = 1 << 3,
         HLE               (ebx, "TSX Hardware Lock Elision", CPUIDRequest::ExtendedFeatures1) = 1 << 4,
-        AVX2              (ebx, "Advanced Vector Extensions 2 (AVX2)", CPUIDRequest::ExtendedFeatures1) = 1 << 5,
+        AVX2              (ebx, "Advanced Vector Extensions 2 (AVX2)", CPUIDRequest::ExtendedFeatures1, introduced = "AVX2 (Haswell)") = 1 << 5,
         FDP_EXCPTN_ONLY   (ebx, "x86 FPU Data Pointer register updated on exceptions only", CPUIDRequest::ExtendedFeatures1) = 1 << 6,
         SMEP              (ebx, "Supervisor Mode Execution Prevention", CPUIDRequest::ExtendedFeatures1) = 1 << 7,
         BMI2              (ebx, "Bit Manipulation Instruction Set 2", CPUIDRequest::ExtendedFeatures1) = 1 << 8,
@@ -598,7 +1652,7 @@ cpu_features! {
         RTM               (ebx, "TSX Restricted Transactional Memory", CPUIDRequest::ExtendedFeatures1) = 1 << 11,
         PQM               (ebx, "Intel RDT Monitoring or AMD Platform QOS Monitoring", CPUIDRequest::ExtendedFeatures1) = 1 << 12,
         MPX               (ebx, "Intel Memory Protection Extensions", CPUIDRequest::ExtendedFeatures1) = 1 << 14,
-        AVX512F           (ebx, "AVX-512 Foundation", CPUIDRequest::ExtendedFeatures1) = 1 << 16,
+        AVX512F           (ebx, "AVX-512 Foundation", CPUIDRequest::ExtendedFeatures1, introduced = "AVX-512 (Skylake-X)") = 1 << 16,
         AVX512DQ          (ebx, "AVX-512 Doubleword and Quadword instructions", CPUIDRequest::ExtendedFeatures1) = 1 << 17,
         RDSEED            (ebx, "RDSEED instruction", CPUIDRequest::ExtendedFeatures1) = 1 << 18,
         ADX               (ebx, "Intel Multi-Precision Add-Carry Instruction Extensions", CPUIDRequest::ExtendedFeatures1) = 1 << 19,
@@ -739,6 +1793,264 @@ cpu_features! {
     }
 }
 
+/// This function returns a hint for the count of logical processors in the physical package,
+/// which is reported in the bits 16 to 23 of the ebx register of CPUID leaf 0x1. The value is only
+/// valid if [CPUFeature::HTT] is reported, otherwise the package contains a single logical
+/// processor. If the `cpuid` instruction isn't supported or the count is zero, this function
+/// returns [None].
+///
+/// The value is the count of addressable APIC IDs, which can be larger than the count of enabled
+/// processors, so it's only useful to size the per-CPU structures in the early SMP bring-up. The
+/// authoritative count of processors is reported by the firmware (like the ACPI MADT).
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{logical_cpu_count_hint, mock::register_file};
+
+// Captured on an Intel Core i7-8700 processor (6 cores, 12 threads)
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0x906EA, ebx: 0x0C10_0800, ecx: 0x7FFA_FBBF, edx: 0xBFEB_FBFF });
+assert_eq!(logical_cpu_count_hint(), Some(16));
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{logical_cpu_count_hint, mock::register_file};
+
+// The count is ignored without the HTT feature
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0x0010_0000, ecx: 0, edx: 0 });
+assert_eq!(logical_cpu_count_hint(), Some(1));
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 28 });
+assert_eq!(logical_cpu_count_hint(), None);
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn logical_cpu_count_hint() -> Option<u32> {
+    if !cpuid_available() {
+        return None;
+    }
+
+    let result = CPUIDRequest::Features.cpuid();
+    if !result.edx.get_bit(28) {
+        return Some(1);
+    }
+
+    match result.ebx.get_bits(16..24) {
+        0 => None,
+        count => Some(count),
+    }
+}
+
+/// This function returns whether paging is enabled, which is indicated by [CR0Flags::PAGING] in the
+/// CR0 register.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{paging_enabled, set_cr0, CR0Flags};
+
+assert!(!paging_enabled());
+set_cr0(CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::PAGING);
+assert!(paging_enabled());
+```
+"#)]
+///
+/// # See also
+/// - [CR0](https://wiki.osdev.org/CPU_Registers_x86#CR0) by [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+pub fn paging_enabled() -> bool {
+    get_cr0().contains(CR0Flags::PAGING)
+}
+
+/// This function returns whether the protected mode is enabled, which is indicated by
+/// [CR0Flags::PROTECTED_MODE_ENABLE] in the CR0 register.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{protected_mode_enabled, set_cr0, CR0Flags};
+
+assert!(!protected_mode_enabled());
+set_cr0(CR0Flags::PROTECTED_MODE_ENABLE);
+assert!(protected_mode_enabled());
+```
+"#)]
+///
+/// # See also
+/// - [CR0](https://wiki.osdev.org/CPU_Registers_x86#CR0) by [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+pub fn protected_mode_enabled() -> bool {
+    get_cr0().contains(CR0Flags::PROTECTED_MODE_ENABLE)
+}
+
+/// This function returns whether the CPU is running in the 64-bit long mode. The long mode is
+/// active, if [msr::EFERFlags::LONG_MODE_ACTIVE] is set in the EFER and paging is enabled in the
+/// CR0 register. On 32-bit targets, this function always returns false.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{
+    in_long_mode,
+    mock::register_file,
+    msr::{EFERFlags, IA32_EFER},
+    CR0Flags,
+};
+
+for (long_mode_active, paging) in [(false, false), (true, false), (false, true), (true, true)] {
+    let efer = if long_mode_active { EFERFlags::LONG_MODE_ACTIVE.bits() } else { 0 };
+    let cr0 = if paging { CR0Flags::PAGING.bits() } else { 0 };
+    register_file().write_msr(IA32_EFER, efer);
+    register_file().write_control_register("cr0", cr0);
+    assert_eq!(in_long_mode(), long_mode_active && paging);
+}
+```
+"#)]
+///
+/// # See also
+/// - [Long Mode](https://wiki.osdev.org/Setting_Up_Long_Mode) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+pub fn in_long_mode() -> bool {
+    cfg!(target_arch = "x86_64")
+        && msr::get_efer().contains(msr::EFERFlags::LONG_MODE_ACTIVE)
+        && paging_enabled()
+}
+
+/// This function runs the closure with the access to user-space memory allowed. If Supervisor Mode
+/// Access Prevention (SMAP) is enabled in the CR4 register, the kernel can't access user pages
+/// without a Page Fault, so the AC flag is set with the `stac` instruction before the closure and
+/// cleared with the `clac` instruction after the closure (also if the closure panics). Without
+/// SMAP, the closure is executed directly.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::access_user_memory;
+
+// SMAP is disabled in the mocked CR4, so stac/clac (which are faulting in user mode) are skipped
+let value = access_user_memory(|| 42);
+assert_eq!(value, 42);
+```
+"#)]
+///
+/// # See also
+/// - [STAC](https://www.felixcloutier.com/x86/stac) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+/// - [CLAC](https://www.felixcloutier.com/x86/clac) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+/// - [Supervisor Memory Protection](https://wiki.osdev.org/Supervisor_Memory_Protection) by
+/// [OSDev.org](https://wiki.osdev.org/)
+pub fn access_user_memory<R>(function: impl FnOnce() -> R) -> R {
+    struct AccessGuard;
+
+    impl Drop for AccessGuard {
+        fn drop(&mut self) {
+            unsafe {
+                asm!("clac", options(nostack));
+            }
+        }
+    }
+
+    if !get_cr4().contains(CR4Flags::SMAP) {
+        return function();
+    }
+
+    unsafe {
+        asm!("stac", options(nostack));
+    }
+    let _guard = AccessGuard;
+    function()
+}
+
+/// This function executes a full memory barrier with the `mfence` instruction. All loads and stores
+/// before the barrier are globally visible before any load or store after the barrier.
+///
+/// This barrier is the strongest ordering and also covers the acquire (loads after the barrier
+/// are not moved before it) and release (stores before the barrier are not moved after it)
+/// orderings. Unlike [core::sync::atomic::fence], this barrier is also ordering the memory
+/// accesses of devices and non-temporal stores.
+///
+/// ```rust
+/// let mut buffer = [0_u8; 4];
+/// buffer[0] = 0xAB;
+/// libcpu::memory_fence();
+/// assert_eq!(buffer[0], 0xAB);
+/// ```
+///
+/// # See also
+/// - [MFENCE](https://www.felixcloutier.com/x86/mfence) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn memory_fence() {
+    unsafe {
+        asm!("mfence", options(nostack, preserves_flags));
+    }
+}
+
+/// This function executes the `serialize` instruction. All modifications of flags, registers and
+/// memory by previous instructions are completed and all buffered writes are drained before the
+/// next instruction is fetched. Unlike `cpuid`, this instruction doesn't modify any register. If
+/// the processor doesn't support the [CPUFeature::SERIALIZE] feature, this function returns
+/// [CpuError::UnsupportedFeature].
+///
+/// # See also
+/// - [SERIALIZE](https://www.felixcloutier.com/x86/serialize) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn serialize() -> Result<(), CpuError> {
+    if !CPUFeature::SERIALIZE.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::SERIALIZE));
+    }
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("serialize", options(nostack, preserves_flags));
+    }
+    Ok(())
+}
+
+/// This function executes a serializing instruction. If the processor supports the
+/// [CPUFeature::SERIALIZE] feature, the cheaper [serialize] is used. Otherwise, the function falls
+/// back to a `cpuid` instruction, which is serializing on all processors.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use libcpu::{mock::{set_backend, MockBackend}, serialize, serializing_barrier};
+use libcpu::{CPUFeature, CpuError};
+
+struct CountingProcessor {
+    cpuid_calls: AtomicUsize,
+}
+
+impl MockBackend for CountingProcessor {
+    fn cpuid(&self, leaf: u32, sub_leaf: u32) -> CpuidResult {
+        self.cpuid_calls.fetch_add(1, Ordering::SeqCst);
+        self.registers().cpuid(leaf, sub_leaf)
+    }
+}
+
+static PROCESSOR: CountingProcessor = CountingProcessor { cpuid_calls: AtomicUsize::new(0) };
+
+unsafe { set_backend(&PROCESSOR) };
+assert_eq!(serialize(), Err(CpuError::UnsupportedFeature(CPUFeature::SERIALIZE)));
+
+let calls = PROCESSOR.cpuid_calls.load(Ordering::SeqCst);
+serializing_barrier();
+assert!(PROCESSOR.cpuid_calls.load(Ordering::SeqCst) > calls);
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 9.3 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[inline]
+pub fn serializing_barrier() {
+    if serialize().is_err() {
+        let _ = CPUIDRequest::Vendor.cpuid();
+    }
+}
+
 #[inline]
 pub fn wait_for_interrupts() {
     unsafe {
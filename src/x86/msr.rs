@@ -0,0 +1,939 @@
+//! This module implements the access to the Model-Specific Registers (MSRs) of x86 and x86_64
+//! processors. MSRs are used to configure CPU features, that are not part of the control
+//! registers, like the long mode, the fast system calls or the local APIC. Every MSR is identified
+//! by a 32-bit address and has a size of 64 bits.
+//!
+//! Reading or writing an MSR, that is not supported by the processor, leads into a General
+//! Protection Fault. The MSRs can only be accessed in the kernel mode and if the processor supports
+//! the [CPUFeature::MSR] feature.
+//!
+//! # See also
+//! - [Model Specific Registers](https://wiki.osdev.org/Model_Specific_Registers) by
+//! [OSDev.org](https://wiki.osdev.org/)
+//! - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 4](https://cdrdv2-public.intel.com/782161/335592-sdm-vol-4.pdf)
+//! by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+
+use crate::{
+    cpuid_available,
+    get_cr0,
+    leaves,
+    max_basic_leaf,
+    model_specific_register,
+    set_cr4,
+    CPUFeature,
+    CR0Flags,
+    CR4Flags,
+    CpuError,
+    MemoryAddress,
+    SegmentSelector,
+};
+use bit_field::BitField;
+use bitflags::bitflags;
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+use core::ops::Range;
+
+/// This constant represents the address of the MSR, that holds the base address and the state of
+/// the local APIC.
+pub const IA32_APIC_BASE: u32 = 0x1B;
+
+/// This constant represents the address of the MSR, that holds the lock and the enable bits of
+/// the features, that can be disabled by the firmware (like VMX and SGX).
+pub const IA32_FEATURE_CONTROL: u32 = 0x3A;
+
+/// This constant represents the address of the MSR, that holds the code segment selector of the
+/// kernel, which is loaded by the `sysenter` instruction.
+pub const IA32_SYSENTER_CS: u32 = 0x174;
+
+/// This constant represents the address of the MSR, that holds the kernel stack pointer, which is
+/// loaded by the `sysenter` instruction.
+pub const IA32_SYSENTER_ESP: u32 = 0x175;
+
+/// This constant represents the address of the MSR, that holds the kernel entry point, which is
+/// loaded by the `sysenter` instruction.
+pub const IA32_SYSENTER_EIP: u32 = 0x176;
+
+/// This constant represents the address of the MSR, that holds the capabilities of the machine
+/// check architecture (like the count of error-reporting banks).
+pub const IA32_MCG_CAP: u32 = 0x179;
+
+/// This constant represents the address of the MSR, that holds the global machine check status.
+pub const IA32_MCG_STATUS: u32 = 0x17A;
+
+/// This constant represents the address of the MSR, that holds the default memory type and the
+/// enable bits of the Memory Type Range Registers (MTRRs).
+pub const IA32_MTRR_DEF_TYPE: u32 = 0x2FF;
+
+/// This constant represents the address of the control MSR of the first machine check bank. The
+/// MSRs of every bank are following in the order CTL, STATUS, ADDR and MISC.
+pub const IA32_MC0_CTL: u32 = 0x400;
+
+/// This constant represents the address of the MSR, that configures the Control-flow Enforcement
+/// Technology (CET) for the user mode.
+pub const IA32_U_CET: u32 = 0x6A0;
+
+/// This constant represents the address of the MSR, that configures the Control-flow Enforcement
+/// Technology (CET) for the kernel mode.
+pub const IA32_S_CET: u32 = 0x6A2;
+
+/// This constant represents the address of the Extended Feature Enable Register (EFER).
+pub const IA32_EFER: u32 = 0xC000_0080;
+
+/// This constant represents the address of the MSR, that holds the base address of the FS segment.
+pub const IA32_FS_BASE: u32 = 0xC000_0100;
+
+/// This constant represents the address of the MSR, that holds the base address of the GS segment.
+pub const IA32_GS_BASE: u32 = 0xC000_0101;
+
+/// This constant represents the address of the MSR, that holds the base address of the GS segment,
+/// which is swapped with the active GS base by the `swapgs` instruction.
+pub const IA32_KERNEL_GS_BASE: u32 = 0xC000_0102;
+
+/// This constant represents the address of the MSR, that holds the auxiliary value returned by the
+/// `rdtscp` and `rdpid` instructions.
+pub const IA32_TSC_AUX: u32 = 0xC000_0103;
+
+/// This function reads the value of the specified MSR with the `rdmsr` instruction.
+///
+/// # See also
+/// - [RDMSR](https://www.felixcloutier.com/x86/rdmsr) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn read_msr(msr: u32) -> u64 {
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_msr(msr);
+
+    #[cfg(not(feature = "mock"))]
+    let value = {
+        let (lower, higher): (u32, u32);
+        unsafe {
+            asm!(
+                "rdmsr",
+                in("ecx") msr,
+                out("eax") lower,
+                out("edx") higher,
+                options(nomem, nostack, preserves_flags)
+            );
+        }
+        (u64::from(higher) << 32) | u64::from(lower)
+    };
+    value
+}
+
+/// This function writes the value into the specified MSR with the `wrmsr` instruction.
+///
+/// # See also
+/// - [WRMSR](https://www.felixcloutier.com/x86/wrmsr) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn write_msr(msr: u32, value: u64) {
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_msr(msr, value);
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!(
+            "wrmsr",
+            in("ecx") msr,
+            in("eax") value as u32,
+            in("edx") (value >> 32) as u32,
+            options(nostack, preserves_flags)
+        );
+    }
+}
+
+bitflags! {
+    /// This structure represents the flags of the Extended Feature Enable Register (EFER).
+    ///
+    /// Here is a list of all flags with description:
+    /// - [EFERFlags::SYSTEM_CALL_EXTENSIONS] - If set, the `syscall` and `sysret` instructions
+    /// are enabled.
+    /// - [EFERFlags::LONG_MODE_ENABLE] - If set, the long mode is enabled when paging is enabled.
+    /// - [EFERFlags::LONG_MODE_ACTIVE] - This bit is set by the CPU when the long mode is active.
+    /// - [EFERFlags::NO_EXECUTE_ENABLE] - If set, the no-execute bit in the page tables is
+    /// honored by the CPU.
+    /// - [EFERFlags::SECURE_VIRTUAL_MACHINE_ENABLE] - If set, the AMD-V extensions are enabled.
+    /// - [EFERFlags::LONG_MODE_SEGMENT_LIMIT_ENABLE] - If set, the segment limits are checked in
+    /// the long mode (AMD only).
+    /// - [EFERFlags::FAST_FXSAVE_FXRSTOR] - If set, the `fxsave` and `fxrstor` instructions are
+    /// not saving the XMM registers in 64-bit kernel mode (AMD only).
+    /// - [EFERFlags::TRANSLATION_CACHE_EXTENSION] - If set, the `invlpg` instruction only
+    /// invalidates the specified page table entries (AMD only).
+    ///
+    /// # See also
+    /// - [EFER](https://wiki.osdev.org/CPU_Registers_x86-64#IA32_EFER) by
+    /// [OSDev.org](https://wiki.osdev.org/)
+    #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+    pub struct EFERFlags: u64 {
+        const SYSTEM_CALL_EXTENSIONS         = 1 << 0;
+        const LONG_MODE_ENABLE               = 1 << 8;
+        const LONG_MODE_ACTIVE               = 1 << 10;
+        const NO_EXECUTE_ENABLE              = 1 << 11;
+        const SECURE_VIRTUAL_MACHINE_ENABLE  = 1 << 12;
+        const LONG_MODE_SEGMENT_LIMIT_ENABLE = 1 << 13;
+        const FAST_FXSAVE_FXRSTOR            = 1 << 14;
+        const TRANSLATION_CACHE_EXTENSION    = 1 << 15;
+    }
+}
+
+model_specific_register!(efer, IA32_EFER, EFERFlags);
+model_specific_register!(fs_base, IA32_FS_BASE);
+model_specific_register!(gs_base, IA32_GS_BASE);
+
+/// This function writes the address into the IA32_KERNEL_GS_BASE MSR. This is not the active GS
+/// base (see [set_gs_base]), but the value, that is exchanged with the active GS base by the
+/// `swapgs` instruction. The kernel stores the address of the per-CPU data in this MSR during the
+/// bring-up of every processor, so the entry point of an interrupt or system call from the user
+/// space can execute `swapgs` to access the per-CPU data over GS. Before returning to the user
+/// space, `swapgs` must be executed again to restore the GS base of the user.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::msr::{get_gs_base, read_kernel_gs_base, set_kernel_gs_base};
+
+set_kernel_gs_base(0xFFFF_8000_0010_0000);
+assert_eq!(read_kernel_gs_base(), 0xFFFF_8000_0010_0000);
+assert_eq!(get_gs_base(), 0);
+```
+"#)]
+///
+/// # See also
+/// - [SWAPGS](https://www.felixcloutier.com/x86/swapgs) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[allow(clippy::unnecessary_cast)] // The memory address is only 32-bit wide on x86
+pub fn set_kernel_gs_base(address: MemoryAddress) {
+    write_msr(IA32_KERNEL_GS_BASE, address as u64);
+}
+
+/// This function returns the address, that is stored in the IA32_KERNEL_GS_BASE MSR. After a
+/// `swapgs`, this is the GS base, that was active before the instruction.
+///
+/// # See also
+/// - [SWAPGS](https://www.felixcloutier.com/x86/swapgs) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+#[allow(clippy::unnecessary_cast)]
+pub fn read_kernel_gs_base() -> MemoryAddress {
+    read_msr(IA32_KERNEL_GS_BASE) as MemoryAddress
+}
+
+/// This function enables the no-execute bit in the page tables by setting
+/// [EFERFlags::NO_EXECUTE_ENABLE] in the EFER. Without this flag, the no-execute bit is reserved
+/// and the CPU raises a Page Fault for pages that have set it. If the processor doesn't support the
+/// [CPUFeature::NX] feature, [CpuError::UnsupportedFeature] is returned and the EFER is not
+/// modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{msr::enable_nx, CPUFeature, CpuError};
+
+assert_eq!(enable_nx(), Err(CpuError::UnsupportedFeature(CPUFeature::NX)));
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{msr::{enable_nx, get_efer, EFERFlags}, mock::register_file};
+
+register_file().set_cpuid(0x8000_0001, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 20 });
+enable_nx().unwrap();
+assert!(get_efer().contains(EFERFlags::NO_EXECUTE_ENABLE));
+```
+"#)]
+///
+/// # See also
+/// - [Paging](https://wiki.osdev.org/Paging#64-Bit_Paging) by [OSDev.org](https://wiki.osdev.org/)
+pub fn enable_nx() -> Result<(), CpuError> {
+    if !CPUFeature::NX.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::NX));
+    }
+
+    set_efer(get_efer() | EFERFlags::NO_EXECUTE_ENABLE);
+    Ok(())
+}
+
+model_specific_register!(apic_base_register, IA32_APIC_BASE);
+
+const APIC_BOOTSTRAP_PROCESSOR: usize = 8;
+const APIC_X2APIC_ENABLE: usize = 10;
+const APIC_GLOBAL_ENABLE: usize = 11;
+const APIC_BASE_ADDRESS: Range<usize> = 12..52;
+
+/// This function returns the physical base address of the local APIC registers from the
+/// IA32_APIC_BASE MSR (bits 12 to MAXPHYADDR). After a reset, the base address is `0xFEE00000`.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::msr::{apic_base, is_apic_enabled, is_bsp, write_msr, IA32_APIC_BASE};
+
+write_msr(IA32_APIC_BASE, 0xFEE0_0900);
+assert_eq!(apic_base(), 0xFEE0_0000);
+assert!(is_apic_enabled());
+assert!(is_bsp());
+
+write_msr(IA32_APIC_BASE, 0x1_2345_6000);
+assert_eq!(apic_base(), 0x1_2345_6000);
+assert!(!is_apic_enabled());
+assert!(!is_bsp());
+```
+"#)]
+///
+/// # See also
+/// - [APIC](https://wiki.osdev.org/APIC#Local_APIC_configuration) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+#[must_use]
+pub fn apic_base() -> u64 {
+    get_apic_base_register().get_bits(APIC_BASE_ADDRESS) << APIC_BASE_ADDRESS.start
+}
+
+/// This function relocates the local APIC registers to the specified physical base address. The
+/// address must be aligned to 4 KiB, the other bits of the IA32_APIC_BASE MSR are kept.
+///
+/// # See also
+/// - [APIC](https://wiki.osdev.org/APIC#Local_APIC_configuration) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[inline]
+pub fn set_apic_base(address: u64) {
+    assert_eq!(address & 0xFFF, 0, "The APIC base address must be aligned to 4 KiB");
+    let mut value = get_apic_base_register();
+    value.set_bits(APIC_BASE_ADDRESS, address >> APIC_BASE_ADDRESS.start);
+    set_apic_base_register(value);
+}
+
+/// This function returns whether the local APIC is globally enabled in the IA32_APIC_BASE MSR.
+#[inline]
+#[must_use]
+pub fn is_apic_enabled() -> bool {
+    get_apic_base_register().get_bit(APIC_GLOBAL_ENABLE)
+}
+
+/// This function enables the local APIC globally by setting the enable bit in the IA32_APIC_BASE
+/// MSR. After the APIC was disabled with this bit, it can't be enabled again without a reset on
+/// some processors.
+#[inline]
+pub fn enable_apic() {
+    let mut value = get_apic_base_register();
+    value.set_bit(APIC_GLOBAL_ENABLE, true);
+    set_apic_base_register(value);
+}
+
+/// This function returns whether the local APIC is in the x2APIC mode, which is indicated by the
+/// x2APIC enable bit in the IA32_APIC_BASE MSR. In the x2APIC mode, the APIC registers are accessed
+/// over MSRs instead of the memory-mapped registers.
+#[inline]
+#[must_use]
+pub fn is_x2apic_enabled() -> bool {
+    get_apic_base_register().get_bit(APIC_X2APIC_ENABLE)
+}
+
+/// This function switches the local APIC into the x2APIC mode by setting the global enable bit and
+/// the x2APIC enable bit in the IA32_APIC_BASE MSR. Both bits are written at once, because the
+/// transition from the disabled state directly into the x2APIC mode is invalid. If the processor
+/// doesn't support the [CPUFeature::X2APIC] feature, [CpuError::UnsupportedFeature] is returned
+/// and the MSR is not modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{msr::{enable_x2apic, is_x2apic_enabled}, CPUFeature, CpuError};
+
+assert_eq!(enable_x2apic(), Err(CpuError::UnsupportedFeature(CPUFeature::X2APIC)));
+assert!(!is_x2apic_enabled());
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{
+    mock::register_file,
+    msr::{apic_base, enable_x2apic, is_apic_enabled, is_x2apic_enabled, write_msr, IA32_APIC_BASE},
+};
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 21, edx: 0 });
+write_msr(IA32_APIC_BASE, 0xFEE0_0000);
+
+enable_x2apic().unwrap();
+assert!(is_apic_enabled());
+assert!(is_x2apic_enabled());
+assert_eq!(apic_base(), 0xFEE0_0000);
+```
+"#)]
+///
+/// # See also
+/// - [x2APIC](https://wiki.osdev.org/APIC#x2APIC) by [OSDev.org](https://wiki.osdev.org/)
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 11.12.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+pub fn enable_x2apic() -> Result<(), CpuError> {
+    if !CPUFeature::X2APIC.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::X2APIC));
+    }
+
+    let mut value = get_apic_base_register();
+    value.set_bit(APIC_GLOBAL_ENABLE, true);
+    value.set_bit(APIC_X2APIC_ENABLE, true);
+    set_apic_base_register(value);
+    Ok(())
+}
+
+/// This function returns whether the current processor is the bootstrap processor (BSP). The BSP
+/// is the processor, that executes the firmware and the kernel after a reset.
+#[inline]
+#[must_use]
+pub fn is_bsp() -> bool {
+    get_apic_base_register().get_bit(APIC_BOOTSTRAP_PROCESSOR)
+}
+
+/// This function configures the `sysenter` and `sysexit` fast system call instructions, that are
+/// used by 32-bit kernels. The `sysenter` instruction loads the code segment, the stack pointer and
+/// the instruction pointer from the IA32_SYSENTER_CS/ESP/EIP MSRs. The stack segment is the code
+/// segment plus 8, so the kernel data segment must follow the kernel code segment in the GDT.
+///
+/// If the processor doesn't support the [CPUFeature::SEP] feature, [CpuError::UnsupportedFeature]
+/// is returned and the MSRs are not modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{msr::*, mock::register_file, SegmentSelector};
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 11 });
+configure_sysenter(SegmentSelector(0x08), 0xC000_8000, 0xC010_0000).unwrap();
+assert_eq!(read_msr(IA32_SYSENTER_CS), 0x08);
+assert_eq!(read_msr(IA32_SYSENTER_ESP), 0xC000_8000);
+assert_eq!(read_msr(IA32_SYSENTER_EIP), 0xC010_0000);
+```
+"#)]
+///
+/// # See also
+/// - [SYSENTER](https://wiki.osdev.org/SYSENTER) by [OSDev.org](https://wiki.osdev.org/)
+/// - [SYSENTER](https://www.felixcloutier.com/x86/sysenter) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[allow(clippy::unnecessary_cast)] // The memory address is only 32-bit wide on x86
+pub fn configure_sysenter(
+    cs: SegmentSelector,
+    esp: MemoryAddress,
+    eip: MemoryAddress,
+) -> Result<(), CpuError> {
+    if !CPUFeature::SEP.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::SEP));
+    }
+
+    write_msr(IA32_SYSENTER_CS, u64::from(cs.0));
+    write_msr(IA32_SYSENTER_ESP, esp as u64);
+    write_msr(IA32_SYSENTER_EIP, eip as u64);
+    Ok(())
+}
+
+/// This function returns the value of the IA32_TSC_AUX MSR. This value is returned by the `rdtscp`
+/// and `rdpid` instructions, so the kernel stores the number of the current processor in it.
+///
+/// # See also
+/// - [RDTSCP](https://www.felixcloutier.com/x86/rdtscp) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn read_tsc_aux() -> u32 {
+    read_msr(IA32_TSC_AUX) as u32
+}
+
+/// This function writes the value into the IA32_TSC_AUX MSR. The value is usually written by the
+/// kernel for every processor during the bring-up. The conventional packing stores the processor
+/// number in the bits 0 to 11 and the NUMA node in the bits 12 to 31, so a single `rdpid` returns
+/// both.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::msr::{read_tsc_aux, write_tsc_aux};
+
+let (cpu, node) = (5, 1);
+write_tsc_aux((node << 12) | cpu);
+assert_eq!(read_tsc_aux() & 0xFFF, cpu);
+assert_eq!(read_tsc_aux() >> 12, node);
+```
+"#)]
+///
+/// # See also
+/// - [RDPID](https://www.felixcloutier.com/x86/rdpid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn write_tsc_aux(value: u32) {
+    write_msr(IA32_TSC_AUX, u64::from(value));
+}
+
+/// This enum represents the methods to read the number of the current processor, ordered from the
+/// cheapest to the most expensive method.
+///
+/// Here is a list of all methods with description:
+/// - [CpuNumberMethod::Rdpid] - The IA32_TSC_AUX MSR is read with the `rdpid` instruction, which
+/// doesn't read the Time Stamp Counter.
+/// - [CpuNumberMethod::Rdtscp] - The IA32_TSC_AUX MSR is read with the `rdtscp` instruction, which
+/// also reads the Time Stamp Counter.
+/// - [CpuNumberMethod::CpuidLeaf0B] - The x2APIC ID is read from CPUID leaf 0xB. The `cpuid`
+/// instruction is serializing and may be intercepted by a hypervisor, so it's much slower.
+/// - [CpuNumberMethod::None] - The processor reports none of these methods.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum CpuNumberMethod {
+    /// The IA32_TSC_AUX MSR is read with the `rdpid` instruction.
+    Rdpid,
+
+    /// The IA32_TSC_AUX MSR is read with the `rdtscp` instruction.
+    Rdtscp,
+
+    /// The x2APIC ID is read from the extended topology leaf (CPUID leaf 0xB).
+    CpuidLeaf0B,
+
+    /// No method is available.
+    None,
+}
+
+/// This function returns the cheapest method to read the number of the current processor. The
+/// `rdpid` and `rdtscp` instructions are reported by separate features and both are reading the
+/// IA32_TSC_AUX MSR, so a processor can support one of them without the other. The methods with
+/// the IA32_TSC_AUX MSR are only returning the processor number, if the kernel has written it with
+/// [write_tsc_aux] before.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, msr::{fast_cpu_number_method, CpuNumberMethod}};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 22, edx: 0 });
+assert_eq!(fast_cpu_number_method(), CpuNumberMethod::Rdpid);
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, msr::{fast_cpu_number_method, CpuNumberMethod}};
+
+register_file().set_cpuid(0x8000_0001, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 27 });
+assert_eq!(fast_cpu_number_method(), CpuNumberMethod::Rdtscp);
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, msr::{fast_cpu_number_method, CpuNumberMethod}};
+
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0xB, ebx: 0, ecx: 0, edx: 0 });
+assert_eq!(fast_cpu_number_method(), CpuNumberMethod::CpuidLeaf0B);
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, msr::{fast_cpu_number_method, CpuNumberMethod}};
+
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0xA, ebx: 0, ecx: 0, edx: 0 });
+assert_eq!(fast_cpu_number_method(), CpuNumberMethod::None);
+```
+"#)]
+///
+/// # See also
+/// - [RDPID](https://www.felixcloutier.com/x86/rdpid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+/// - [RDTSCP](https://www.felixcloutier.com/x86/rdtscp) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn fast_cpu_number_method() -> CpuNumberMethod {
+    let features = CPUFeature::enabled_feature_set();
+    if features.contains(CPUFeature::RDPID) {
+        CpuNumberMethod::Rdpid
+    } else if features.contains(CPUFeature::RDTSCP) {
+        CpuNumberMethod::Rdtscp
+    } else if cpuid_available() && max_basic_leaf() >= leaves::EXTENDED_TOPOLOGY {
+        CpuNumberMethod::CpuidLeaf0B
+    } else {
+        CpuNumberMethod::None
+    }
+}
+
+/// This function returns the count of error-reporting banks of the machine check architecture from
+/// the IA32_MCG_CAP MSR. If the processor doesn't support the [CPUFeature::MCA] feature, this
+/// function returns [None].
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{msr::{mce_bank_count, write_msr, IA32_MCG_CAP}, mock::register_file};
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 14 });
+write_msr(IA32_MCG_CAP, 0x0F00_0C14);
+assert_eq!(mce_bank_count(), Some(20));
+```
+
+```rust
+use libcpu::msr::mce_bank_count;
+
+assert_eq!(mce_bank_count(), None);
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3b](https://cdrdv2-public.intel.com/782156/253669-sdm-vol-3b.pdf)
+/// Chapter 16.3.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[must_use]
+pub fn mce_bank_count() -> Option<u8> {
+    if !CPUFeature::MCA.is_supported() {
+        return None;
+    }
+
+    Some(read_msr(IA32_MCG_CAP).get_bits(0..8) as u8)
+}
+
+/// This function returns the address of the IA32_MCi_STATUS MSR of the specified machine check
+/// bank.
+///
+/// ```rust
+/// use libcpu::msr::{mc_addr_msr, mc_status_msr};
+///
+/// assert_eq!(mc_status_msr(0), 0x401);
+/// assert_eq!(mc_status_msr(3), 0x40D);
+/// assert_eq!(mc_addr_msr(3), 0x40E);
+/// ```
+#[inline]
+#[must_use]
+pub const fn mc_status_msr(bank: u8) -> u32 {
+    IA32_MC0_CTL + 1 + (bank as u32) * 4
+}
+
+/// This function returns the address of the IA32_MCi_ADDR MSR of the specified machine check bank.
+#[inline]
+#[must_use]
+pub const fn mc_addr_msr(bank: u8) -> u32 {
+    IA32_MC0_CTL + 2 + (bank as u32) * 4
+}
+
+/// This function reads the IA32_MCi_STATUS MSR of the specified machine check bank. If bit 63
+/// (VAL) is set, the bank contains a valid error. A #MC handler uses this value to decode the
+/// error.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::msr::{mc_status_msr, read_mc_status, write_msr};
+
+write_msr(mc_status_msr(2), 1 << 63);
+assert_eq!(read_mc_status(2), 1 << 63);
+assert_eq!(read_mc_status(1), 0);
+```
+"#)]
+#[inline]
+#[must_use]
+pub fn read_mc_status(bank: u8) -> u64 {
+    read_msr(mc_status_msr(bank))
+}
+
+/// This function reads the IA32_MCi_ADDR MSR of the specified machine check bank, which contains
+/// the address of the memory location, that produced the error. The value is only valid if the
+/// ADDRV bit (bit 58) is set in the status of the bank.
+#[inline]
+#[must_use]
+pub fn read_mc_addr(bank: u8) -> u64 {
+    read_msr(mc_addr_msr(bank))
+}
+
+/// This enum represents the memory types, that can be configured with the Memory Type Range
+/// Registers (MTRRs). The memory type controls, how the accesses to the memory are cached.
+///
+/// Here is a list of all memory types with description:
+/// - [MemoryType::Uncacheable] - The accesses are not cached and not reordered.
+/// - [MemoryType::WriteCombining] - The accesses are not cached, but the writes are combined in a
+/// buffer.
+/// - [MemoryType::WriteThrough] - The reads are cached, the writes are written to the cache and
+/// the memory.
+/// - [MemoryType::WriteProtected] - The reads are cached, the writes are not cached.
+/// - [MemoryType::WriteBack] - The reads and writes are cached, the writes are written to the
+/// memory when the cache line is evicted.
+///
+/// # See also
+/// - [MTRR](https://wiki.osdev.org/MTRR) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[repr(u8)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum MemoryType {
+    /// The accesses are not cached and not reordered.
+    Uncacheable = 0,
+
+    /// The accesses are not cached, but the writes are combined in a buffer.
+    WriteCombining = 1,
+
+    /// The reads are cached, the writes are written to the cache and the memory.
+    WriteThrough = 4,
+
+    /// The reads are cached, the writes are not cached.
+    WriteProtected = 5,
+
+    /// The reads and writes are cached, the writes are written to the memory when the cache line is
+    /// evicted.
+    WriteBack = 6,
+}
+
+/// This implementation is used to convert the type field of a MTRR into the memory type. This
+/// function is used in [memory_type_default]
+impl TryFrom<u8> for MemoryType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Uncacheable),
+            1 => Ok(Self::WriteCombining),
+            4 => Ok(Self::WriteThrough),
+            5 => Ok(Self::WriteProtected),
+            6 => Ok(Self::WriteBack),
+            _ => Err(value),
+        }
+    }
+}
+
+/// This function returns the default memory type from the IA32_MTRR_DEF_TYPE MSR. The default
+/// memory type is used for all physical memory, that isn't covered by a MTRR. If the MTRRs are
+/// disabled (bit 11 is clear), all physical memory is uncacheable, so this function returns
+/// [MemoryType::Uncacheable]. If the processor doesn't support the [CPUFeature::MTRR] feature or
+/// the type field doesn't encode a valid memory type, this function returns [None].
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{
+    mock::register_file,
+    msr::{memory_type_default, write_msr, MemoryType, IA32_MTRR_DEF_TYPE},
+};
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 12 });
+
+write_msr(IA32_MTRR_DEF_TYPE, (1 << 11) | 6);
+assert_eq!(memory_type_default(), Some(MemoryType::WriteBack));
+
+write_msr(IA32_MTRR_DEF_TYPE, 6);
+assert_eq!(memory_type_default(), Some(MemoryType::Uncacheable));
+
+write_msr(IA32_MTRR_DEF_TYPE, (1 << 11) | 2);
+assert_eq!(memory_type_default(), None);
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+/// Chapter 12.11.2.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[must_use]
+pub fn memory_type_default() -> Option<MemoryType> {
+    if !CPUFeature::MTRR.is_supported() {
+        return None;
+    }
+
+    let value = read_msr(IA32_MTRR_DEF_TYPE);
+    if !value.get_bit(11) {
+        return Some(MemoryType::Uncacheable);
+    }
+    MemoryType::try_from(value.get_bits(0..8) as u8).ok()
+}
+
+bitflags! {
+    /// This structure represents the flags of the IA32_FEATURE_CONTROL MSR. The firmware sets the
+    /// enable bits and the lock bit during the boot, after the lock bit is set the MSR can't be
+    /// written until the next reset.
+    ///
+    /// Here is a list of all flags with description:
+    /// - [FeatureControlFlags::LOCK] - If set, the MSR is locked and writing it leads into a
+    /// General Protection Fault.
+    /// - [FeatureControlFlags::VMX_INSIDE_SMX] - If set, the `vmxon` instruction is enabled
+    /// inside of the Safer Mode Extensions (SMX) operation.
+    /// - [FeatureControlFlags::VMX_OUTSIDE_SMX] - If set, the `vmxon` instruction is enabled
+    /// outside of the Safer Mode Extensions (SMX) operation.
+    /// - [FeatureControlFlags::SGX_LAUNCH_CONTROL_ENABLE] - If set, the SGX launch enclave public
+    /// key hash MSRs are writable by the operating system.
+    /// - [FeatureControlFlags::SGX_GLOBAL_ENABLE] - If set, the Software Guard Extensions (SGX)
+    /// are enabled.
+    ///
+    /// # See also
+    /// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 4](https://cdrdv2-public.intel.com/782161/335592-sdm-vol-4.pdf)
+    /// Chapter 2.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+    #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+    pub struct FeatureControlFlags: u64 {
+        const LOCK                      = 1 << 0;
+        const VMX_INSIDE_SMX            = 1 << 1;
+        const VMX_OUTSIDE_SMX           = 1 << 2;
+        const SGX_LAUNCH_CONTROL_ENABLE = 1 << 17;
+        const SGX_GLOBAL_ENABLE         = 1 << 18;
+    }
+}
+
+model_specific_register!(feature_control, IA32_FEATURE_CONTROL, FeatureControlFlags);
+
+/// This function returns whether the Virtual Machine Extensions (VMX) are enabled by the firmware.
+/// The processor can report the [CPUFeature::VMX] feature, but the `vmxon` instruction leads into a
+/// General Protection Fault, until the IA32_FEATURE_CONTROL MSR is locked with
+/// [FeatureControlFlags::VMX_OUTSIDE_SMX] set. If the processor doesn't support the
+/// [CPUFeature::VMX] feature, the MSR isn't read and this function returns false.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{
+    mock::register_file,
+    msr::{sgx_enabled_by_firmware, vmx_enabled_by_firmware, write_msr, FeatureControlFlags},
+    msr::IA32_FEATURE_CONTROL,
+};
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 5, edx: 0 });
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 1 << 2, ecx: 0, edx: 0 });
+
+// The firmware locked the MSR without enabling VMX and SGX
+write_msr(IA32_FEATURE_CONTROL, FeatureControlFlags::LOCK.bits());
+assert!(!vmx_enabled_by_firmware());
+assert!(!sgx_enabled_by_firmware());
+
+// The firmware enabled VMX and SGX, but didn't lock the MSR
+let enabled = FeatureControlFlags::VMX_OUTSIDE_SMX | FeatureControlFlags::SGX_GLOBAL_ENABLE;
+write_msr(IA32_FEATURE_CONTROL, enabled.bits());
+assert!(!vmx_enabled_by_firmware());
+assert!(!sgx_enabled_by_firmware());
+
+// The firmware locked the MSR with VMX and SGX enabled
+write_msr(IA32_FEATURE_CONTROL, (enabled | FeatureControlFlags::LOCK).bits());
+assert!(vmx_enabled_by_firmware());
+assert!(sgx_enabled_by_firmware());
+```
+
+```rust
+use libcpu::msr::{
+    sgx_enabled_by_firmware, vmx_enabled_by_firmware, write_msr, FeatureControlFlags,
+    IA32_FEATURE_CONTROL,
+};
+
+write_msr(IA32_FEATURE_CONTROL, FeatureControlFlags::all().bits());
+assert!(!vmx_enabled_by_firmware());
+assert!(!sgx_enabled_by_firmware());
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 4](https://cdrdv2-public.intel.com/782161/335592-sdm-vol-4.pdf)
+/// Chapter 2.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[must_use]
+pub fn vmx_enabled_by_firmware() -> bool {
+    CPUFeature::VMX.is_supported()
+        && get_feature_control()
+            .contains(FeatureControlFlags::LOCK | FeatureControlFlags::VMX_OUTSIDE_SMX)
+}
+
+/// This function returns whether the Software Guard Extensions (SGX) are enabled by the firmware.
+/// The processor can report the [CPUFeature::SGX] feature, but the enclave instructions are only
+/// usable, if the IA32_FEATURE_CONTROL MSR is locked with [FeatureControlFlags::SGX_GLOBAL_ENABLE]
+/// set. If the processor doesn't support the [CPUFeature::SGX] feature, the MSR isn't read and this
+/// function returns false.
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 4](https://cdrdv2-public.intel.com/782161/335592-sdm-vol-4.pdf)
+/// Chapter 2.1 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[must_use]
+pub fn sgx_enabled_by_firmware() -> bool {
+    CPUFeature::SGX.is_supported()
+        && get_feature_control()
+            .contains(FeatureControlFlags::LOCK | FeatureControlFlags::SGX_GLOBAL_ENABLE)
+}
+
+bitflags! {
+    /// This structure represents the flags of the IA32_U_CET and IA32_S_CET MSRs, which are
+    /// configuring the Control-flow Enforcement Technology (CET) for the user and the kernel mode.
+    ///
+    /// Here is a list of all flags with description:
+    /// - [CETFlags::SHADOW_STACK_ENABLE] - If set, the shadow stack is enabled.
+    /// - [CETFlags::WRITE_SHADOW_STACK_ENABLE] - If set, the `wrss` instruction is enabled.
+    /// - [CETFlags::ENDBRANCH_ENABLE] - If set, the indirect branch tracking is enabled, so every
+    /// indirect `call` or `jmp` must land on an `endbr32` or `endbr64` instruction.
+    /// - [CETFlags::LEGACY_BITMAP_ENABLE] - If set, the legacy code bitmap is used to suppress the
+    /// indirect branch tracking for legacy code.
+    /// - [CETFlags::NO_TRACK_ENABLE] - If set, the `notrack` prefix is honored by the processor.
+    /// - [CETFlags::SUPPRESS_DISABLE] - If set, the suppression of the tracker after a legacy
+    /// branch is disabled.
+    /// - [CETFlags::SUPPRESS] - This bit is set, when the indirect branch tracking is suppressed.
+    /// - [CETFlags::TRACKER] - This bit is set, when the processor is waiting for an `endbr`
+    /// instruction.
+    ///
+    /// # See also
+    /// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 1](https://cdrdv2-public.intel.com/782158/253665-sdm-vol-1.pdf)
+    /// Chapter 17 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+    #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+    pub struct CETFlags: u64 {
+        const SHADOW_STACK_ENABLE       = 1 << 0;
+        const WRITE_SHADOW_STACK_ENABLE = 1 << 1;
+        const ENDBRANCH_ENABLE          = 1 << 2;
+        const LEGACY_BITMAP_ENABLE      = 1 << 3;
+        const NO_TRACK_ENABLE           = 1 << 4;
+        const SUPPRESS_DISABLE          = 1 << 5;
+        const SUPPRESS                  = 1 << 10;
+        const TRACKER                   = 1 << 11;
+    }
+}
+
+model_specific_register!(u_cet, IA32_U_CET, CETFlags);
+model_specific_register!(s_cet, IA32_S_CET, CETFlags);
+
+/// This function returns whether the processor supports the shadow stack of the Control-flow
+/// Enforcement Technology (CET). This is indicated by the [CPUFeature::ShadowStack] feature.
+#[inline]
+#[must_use]
+pub fn cet_shadow_stack_supported() -> bool {
+    CPUFeature::ShadowStack.is_supported()
+}
+
+/// This function returns whether the processor supports the indirect branch tracking of the
+/// Control-flow Enforcement Technology (CET). This is indicated by the [CPUFeature::CET_IBT]
+/// feature.
+#[inline]
+#[must_use]
+pub fn cet_ibt_supported() -> bool {
+    CPUFeature::CET_IBT.is_supported()
+}
+
+/// This function enables the shadow stack by setting [CR4Flags::ControlFlowEnforcement] in the CR4
+/// register and [CETFlags::SHADOW_STACK_ENABLE] in the IA32_U_CET and IA32_S_CET MSRs. The other
+/// flags of the MSRs are kept. If the processor doesn't support the [CPUFeature::ShadowStack]
+/// feature, [CpuError::UnsupportedFeature] is returned and neither CR4 nor the MSRs are modified.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{
+    get_cr4,
+    mock::register_file,
+    msr::{enable_cet_shadow_stack, get_s_cet, get_u_cet, write_msr, CETFlags, IA32_U_CET},
+    set_cr0, CR0Flags, CR4Flags,
+};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 7, edx: 0 });
+set_cr0(CR0Flags::WRITE_PROTECTED);
+write_msr(IA32_U_CET, CETFlags::ENDBRANCH_ENABLE.bits());
+
+unsafe { enable_cet_shadow_stack() }.unwrap();
+assert!(get_cr4().contains(CR4Flags::ControlFlowEnforcement));
+assert_eq!(get_u_cet(), CETFlags::SHADOW_STACK_ENABLE | CETFlags::ENDBRANCH_ENABLE);
+assert_eq!(get_s_cet(), CETFlags::SHADOW_STACK_ENABLE);
+```
+
+```rust
+use libcpu::{
+    get_cr4, msr::{enable_cet_shadow_stack, get_s_cet, CETFlags}, set_cr0, CPUFeature, CR0Flags,
+    CR4Flags, CpuError,
+};
+
+set_cr0(CR0Flags::WRITE_PROTECTED);
+let result = unsafe { enable_cet_shadow_stack() };
+assert_eq!(result, Err(CpuError::UnsupportedFeature(CPUFeature::ShadowStack)));
+assert!(!get_cr4().contains(CR4Flags::ControlFlowEnforcement));
+assert_eq!(get_s_cet(), CETFlags::empty());
+```
+"#)]
+///
+/// # Safety
+/// The caller must ensure, that the shadow stack pointer (SSP) of the user and the kernel mode are
+/// pointing to valid shadow stacks, before code with a `call` or `ret` instruction is executed in
+/// that mode. Otherwise, the next `call` or `ret` leads into a Page Fault or a Control Protection
+/// Exception.
+///
+/// # Panics
+/// This function panics, if [CR0Flags::WRITE_PROTECTED] isn't set in the CR0 register, because
+/// setting [CR4Flags::ControlFlowEnforcement] without it leads into a General Protection Fault.
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 1](https://cdrdv2-public.intel.com/782158/253665-sdm-vol-1.pdf)
+/// Chapter 17.2 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+pub unsafe fn enable_cet_shadow_stack() -> Result<(), CpuError> {
+    if !cet_shadow_stack_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::ShadowStack));
+    }
+
+    assert!(
+        get_cr0().contains(CR0Flags::WRITE_PROTECTED),
+        "The CET can only be enabled with the write protection in CR0"
+    );
+    set_cr4(CR4Flags::ControlFlowEnforcement);
+    set_u_cet(get_u_cet() | CETFlags::SHADOW_STACK_ENABLE);
+    set_s_cet(get_s_cet() | CETFlags::SHADOW_STACK_ENABLE);
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! This module implements the access to the Protection Key Rights Register for User Pages (PKRU).
+//! With the protection keys, every user page is tagged with one of 16 keys in the page table
+//! entry. The PKRU register contains the access rights for every key, so the rights of many pages
+//! can be changed by userspace without modifying the page tables.
+//!
+//! The following structure shows how the register is represented (AD = Access Disable, WD = Write
+//! Disable, the number is the protection key):
+//! ```text
+//! 0                   1                   2                   3
+//! 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|A|W|
+//! |D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|D|
+//! |0|0|1|1|2|2|3|3|4|4|5|5|6|6|7|7|8|8|9|9|10 |11 |12 |13 |14 |15 |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! ```
+//!
+//! # See also
+//! - [Memory Protection Keys](https://www.kernel.org/doc/html/latest/core-api/protection-keys.html)
+//! by [The Linux Kernel](https://www.kernel.org)
+//! - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 3a](https://cdrdv2-public.intel.com/782154/253668-sdm-vol-3a.pdf)
+//! Chapter 4.6.2 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+
+use crate::{
+    CPUFeature,
+    CpuError,
+};
+use bit_field::BitField;
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+
+/// This structure represents the value of the PKRU register. Every protection key is represented
+/// by two bits, the access-disable bit and the write-disable bit.
+///
+/// ```rust
+/// use libcpu::protection_keys::Pkru;
+///
+/// let mut pkru = Pkru::default();
+/// pkru.set_access_disabled(0, true);
+/// pkru.set_write_disabled(15, true);
+/// assert_eq!(pkru, Pkru(0b01 | (0b10 << 30)));
+/// assert!(pkru.access_disabled(0) && !pkru.write_disabled(0));
+/// assert!(!pkru.access_disabled(15) && pkru.write_disabled(15));
+///
+/// pkru.set_access_disabled(0, false);
+/// pkru.set_write_disabled(15, false);
+/// assert_eq!(pkru, Pkru(0));
+/// ```
+///
+/// The keys are checked, so a key outside of the 16 keys panics:
+/// ```rust,should_panic
+/// use libcpu::protection_keys::Pkru;
+///
+/// Pkru::default().set_write_disabled(16, true);
+/// ```
+///
+/// # See also
+/// - [read_pkru] and [write_pkru] (Source Code)
+#[repr(transparent)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Pkru(pub u32);
+
+impl Pkru {
+    /// This constant represents the count of protection keys, that are supported by the PKRU
+    /// register.
+    pub const KEY_COUNT: u8 = 16;
+
+    /// This function returns whether all data accesses to pages with the specified key are
+    /// disabled.
+    #[inline]
+    #[must_use]
+    pub fn access_disabled(&self, key: u8) -> bool {
+        self.0.get_bit(Self::bit_index(key))
+    }
+
+    /// This function replaces the access-disable bit of the specified key.
+    #[inline]
+    pub fn set_access_disabled(&mut self, key: u8, disabled: bool) {
+        self.0.set_bit(Self::bit_index(key), disabled);
+    }
+
+    /// This function returns whether write accesses to pages with the specified key are disabled.
+    #[inline]
+    #[must_use]
+    pub fn write_disabled(&self, key: u8) -> bool {
+        self.0.get_bit(Self::bit_index(key) + 1)
+    }
+
+    /// This function replaces the write-disable bit of the specified key.
+    #[inline]
+    pub fn set_write_disabled(&mut self, key: u8, disabled: bool) {
+        self.0.set_bit(Self::bit_index(key) + 1, disabled);
+    }
+
+    #[inline]
+    fn bit_index(key: u8) -> usize {
+        assert!(key < Self::KEY_COUNT, "Invalid protection key {}", key);
+        usize::from(key) * 2
+    }
+}
+
+/// This function reads the PKRU register with the `rdpkru` instruction. If the operating system
+/// hasn't enabled the protection keys (indicated by the [CPUFeature::OSPKE] feature), this function
+/// returns [CpuError::UnsupportedFeature].
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, protection_keys::{read_pkru, write_pkru, Pkru}};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 4, edx: 0 });
+
+let mut pkru = read_pkru().unwrap();
+pkru.set_write_disabled(1, true);
+write_pkru(pkru).unwrap();
+assert_eq!(register_file().read_pkru(), 0b1000);
+assert_eq!(read_pkru(), Ok(Pkru(0b1000)));
+```
+
+```rust
+use libcpu::{protection_keys::{read_pkru, write_pkru, Pkru}, CPUFeature, CpuError};
+
+assert_eq!(read_pkru(), Err(CpuError::UnsupportedFeature(CPUFeature::OSPKE)));
+assert_eq!(write_pkru(Pkru(0)), Err(CpuError::UnsupportedFeature(CPUFeature::OSPKE)));
+```
+"#)]
+///
+/// # See also
+/// - [RDPKRU](https://www.felixcloutier.com/x86/rdpkru) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn read_pkru() -> Result<Pkru, CpuError> {
+    if !CPUFeature::OSPKE.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::OSPKE));
+    }
+
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_pkru();
+
+    #[cfg(not(feature = "mock"))]
+    let value = {
+        let value: u32;
+        unsafe {
+            asm!(
+                "rdpkru",
+                in("ecx") 0,
+                out("eax") value,
+                out("edx") _,
+                options(nomem, nostack, preserves_flags)
+            );
+        }
+        value
+    };
+    Ok(Pkru(value))
+}
+
+/// This function writes the PKRU register with the `wrpkru` instruction. If the operating system
+/// hasn't enabled the protection keys (indicated by the [CPUFeature::OSPKE] feature), this function
+/// returns [CpuError::UnsupportedFeature] and the register is not modified.
+///
+/// # See also
+/// - [WRPKRU](https://www.felixcloutier.com/x86/wrpkru) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+pub fn write_pkru(value: Pkru) -> Result<(), CpuError> {
+    if !CPUFeature::OSPKE.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::OSPKE));
+    }
+
+    #[cfg(feature = "mock")]
+    crate::mock::backend().write_pkru(value.0);
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!(
+            "wrpkru",
+            in("eax") value.0,
+            in("ecx") 0,
+            in("edx") 0,
+            options(nostack, preserves_flags)
+        );
+    }
+    Ok(())
+}
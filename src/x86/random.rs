@@ -0,0 +1,203 @@
+//! This module implements the access to the hardware random number generator with the `rdrand`
+//! and `rdseed` instructions. `rdrand` returns values of a cryptographically secure pseudo random
+//! number generator, that is reseeded by the hardware entropy source. `rdseed` returns values
+//! directly from the entropy source, so it's slower but suitable for seeding other generators.
+//!
+//! Both instructions can fail, if the generator has no random value available at the moment. The
+//! failure is reported by the carry flag, so the instruction is retried a few times before the
+//! functions of this module give up.
+//!
+//! # See also
+//! - [RDRAND](https://www.felixcloutier.com/x86/rdrand) by
+//! [Felix Clountier](https://www.felixcloutier.com)
+//! - [RDSEED](https://www.felixcloutier.com/x86/rdseed) by
+//! [Felix Clountier](https://www.felixcloutier.com)
+//! - [Intel Digital Random Number Generator (DRNG) Software Implementation Guide](https://www.intel.com/content/www/us/en/developer/articles/guide/intel-digital-random-number-generator-drng-software-implementation-guide.html)
+//! by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+
+use crate::CPUFeature;
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+
+/// This constant represents the count of attempts, after which [rdrand_u64], [rdseed_u64] and
+/// [rdrand_fill] give up. Intel recommends 10 attempts for `rdrand`, a failure after that indicates
+/// a broken generator.
+pub const DEFAULT_RETRIES: usize = 10;
+
+#[cfg(all(not(feature = "mock"), target_arch = "x86_64"))]
+macro_rules! random_step {
+    ($instruction: literal) => {{
+        let value: u64;
+        let carry: u8;
+        unsafe {
+            asm!(
+                concat!($instruction, " {value}"),
+                "setc {carry}",
+                value = out(reg) value,
+                carry = out(reg_byte) carry,
+                options(nomem, nostack)
+            );
+        }
+        (carry != 0).then_some(value)
+    }};
+}
+
+// On x86, the instructions are only returning 32 bits, so two values are combined
+#[cfg(all(not(feature = "mock"), target_arch = "x86"))]
+macro_rules! random_step {
+    ($instruction: literal) => {{
+        let (lower, higher): (u32, u32);
+        let (lower_carry, higher_carry): (u8, u8);
+        unsafe {
+            asm!(
+                concat!($instruction, " {lower}"),
+                "setc {lower_carry}",
+                concat!($instruction, " {higher}"),
+                "setc {higher_carry}",
+                lower = out(reg) lower,
+                higher = out(reg) higher,
+                lower_carry = out(reg_byte) lower_carry,
+                higher_carry = out(reg_byte) higher_carry,
+                options(nomem, nostack)
+            );
+        }
+        (lower_carry != 0 && higher_carry != 0)
+            .then_some(u64::from(lower) | (u64::from(higher) << 32))
+    }};
+}
+
+#[cfg(feature = "mock")]
+macro_rules! random_step {
+    ($instruction: literal) => {
+        crate::mock::backend().read_random($instruction)
+    };
+}
+
+#[inline]
+fn rdrand_step() -> Option<u64> {
+    random_step!("rdrand")
+}
+
+#[inline]
+fn rdseed_step() -> Option<u64> {
+    random_step!("rdseed")
+}
+
+/// This function returns a random value from the `rdrand` instruction. The instruction is retried
+/// up to [DEFAULT_RETRIES] times. If the processor doesn't support the [CPUFeature::RDRAND] feature
+/// or all attempts failed, this function returns [None].
+///
+/// # See also
+/// - [RDRAND](https://www.felixcloutier.com/x86/rdrand) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn rdrand_u64() -> Option<u64> {
+    if !CPUFeature::RDRAND.is_supported() {
+        return None;
+    }
+    (0..DEFAULT_RETRIES).find_map(|_| rdrand_step())
+}
+
+/// This function returns a random value from the `rdseed` instruction. The instruction is retried
+/// up to [DEFAULT_RETRIES] times. If the processor doesn't support the [CPUFeature::RDSEED] feature
+/// or all attempts failed, this function returns [None]. Unlike `rdrand`, the entropy source can
+/// be exhausted for a while under load, so a failure isn't an indicator for a broken generator.
+///
+/// # See also
+/// - [RDSEED](https://www.felixcloutier.com/x86/rdseed) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn rdseed_u64() -> Option<u64> {
+    if !CPUFeature::RDSEED.is_supported() {
+        return None;
+    }
+    (0..DEFAULT_RETRIES).find_map(|_| rdseed_step())
+}
+
+/// This function fills the buffer with random bytes from the `rdrand` instruction. Every value is
+/// retried up to [DEFAULT_RETRIES] times. See [rdrand_fill_with_retries] for more information.
+#[cfg_attr(not(feature = "mock"), doc = r#"
+```rust
+use libcpu::{random::rdrand_fill, CPUFeature};
+
+let mut buffer = [0_u8; 64];
+if CPUFeature::RDRAND.is_supported() {
+    assert!(rdrand_fill(&mut buffer));
+    assert!(buffer.iter().any(|byte| *byte != 0));
+} else {
+    assert!(!rdrand_fill(&mut buffer));
+}
+```
+"#)]
+///
+/// # See also
+/// - [RDRAND](https://www.felixcloutier.com/x86/rdrand) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn rdrand_fill(buffer: &mut [u8]) -> bool {
+    rdrand_fill_with_retries(buffer, DEFAULT_RETRIES)
+}
+
+/// This function fills the buffer with random bytes from the `rdrand` instruction. The buffer is
+/// filled with 8 bytes per instruction, the bytes of the last value are truncated to the length of
+/// the buffer. Every value is retried up to the specified count of attempts.
+///
+/// If the processor doesn't support the [CPUFeature::RDRAND] feature or a value failed after all
+/// attempts, this function returns false. In this case, the content of the buffer is unspecified
+/// and must not be used.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use libcpu::mock::{register_file, set_backend, MockBackend};
+use libcpu::random::rdrand_fill_with_retries;
+
+struct FailingProcessor {
+    attempts: AtomicUsize,
+}
+
+impl MockBackend for FailingProcessor {
+    fn read_random(&self, _instruction: &'static str) -> Option<u64> {
+        // The first value is available, all following values are failing
+        match self.attempts.fetch_add(1, Ordering::SeqCst) {
+            0 => Some(0x0807_0605_0403_0201),
+            _ => None,
+        }
+    }
+}
+
+static PROCESSOR: FailingProcessor = FailingProcessor { attempts: AtomicUsize::new(0) };
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 30, edx: 0 });
+unsafe { set_backend(&PROCESSOR) };
+
+let mut buffer = [0_u8; 5];
+assert!(rdrand_fill_with_retries(&mut buffer, 3));
+assert_eq!(buffer, [0x01, 0x02, 0x03, 0x04, 0x05]);
+
+let mut buffer = [0_u8; 16];
+assert!(!rdrand_fill_with_retries(&mut buffer, 3));
+assert_eq!(PROCESSOR.attempts.load(Ordering::SeqCst), 4);
+```
+"#)]
+///
+/// # See also
+/// - [RDRAND](https://www.felixcloutier.com/x86/rdrand) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn rdrand_fill_with_retries(buffer: &mut [u8], retries: usize) -> bool {
+    if !CPUFeature::RDRAND.is_supported() {
+        return false;
+    }
+
+    for chunk in buffer.chunks_mut(8) {
+        let Some(value) = (0..retries).find_map(|_| rdrand_step()) else {
+            return false;
+        };
+        chunk.copy_from_slice(&value.to_le_bytes()[..chunk.len()]);
+    }
+    true
+}
@@ -0,0 +1,108 @@
+//! This module implements the detection of the memory encryption features of AMD processors. With
+//! Secure Memory Encryption (SME), the kernel encrypts pages by setting the C-bit in the page table
+//! entries. With Secure Encrypted Virtualization (SEV) and the extensions SEV-ES and SEV-SNP, the
+//! memory (and register state) of a guest is encrypted with a key, that is unknown to the
+//! hypervisor.
+//!
+//! A guest kernel must know the position of the C-bit to create the page tables, because the bit
+//! is part of the physical address bits.
+//!
+//! # See also
+//! - [AMD64 Architecture Programmer's Manual Volume 2](https://www.amd.com/content/dam/amd/en/documents/processor-tech-docs/programmer-references/24593.pdf)
+//! Chapter 15.34 by [Advanced Micro Devices](https://www.amd.com)
+//! - [CPUID](https://en.wikipedia.org/wiki/CPUID#EAX=8000001Fh:_Encrypted_Memory_Capabilities) by
+//! [Wikipedia](https://wikipedia.org)
+
+use crate::{
+    x86::{
+        cpuid::{max_extended_leaf, CPUIDRequest},
+        leaves,
+    },
+    CPUVendor,
+    RegisterExt,
+};
+
+/// This structure contains the memory encryption capabilities, that are reported by CPUID leaf
+/// 0x8000_001F.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash, Default)]
+pub struct MemoryEncryptionInfo {
+    /// This field indicates whether Secure Memory Encryption (SME) is supported.
+    pub sme: bool,
+
+    /// This field indicates whether Secure Encrypted Virtualization (SEV) is supported.
+    pub sev: bool,
+
+    /// This field indicates whether SEV with Encrypted State (SEV-ES) is supported.
+    pub sev_es: bool,
+
+    /// This field indicates whether SEV with Secure Nested Paging (SEV-SNP) is supported.
+    pub sev_snp: bool,
+
+    /// This field contains the position of the C-bit in the page table entries.
+    pub c_bit_position: u8,
+
+    /// This field contains the count of physical address bits, that are lost when the memory
+    /// encryption is enabled.
+    pub physical_address_reduction: u8,
+}
+
+impl MemoryEncryptionInfo {
+    /// This function returns whether any of the memory encryption features is supported.
+    #[inline]
+    #[must_use]
+    pub fn supported(&self) -> bool {
+        self.sme || self.sev
+    }
+}
+
+/// This function returns the memory encryption capabilities of the processor. If the processor
+/// isn't an AMD processor or doesn't report CPUID leaf 0x8000_001F, no feature is reported as
+/// supported.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, sev::memory_encryption};
+
+// Captured on an AMD EPYC 9004 series processor
+let registers = register_file();
+registers.set_cpuid(0, 0, CpuidResult { eax: 0x10, ebx: 0x6874_7541, ecx: 0x444D_4163, edx: 0x6974_6E65 });
+registers.set_cpuid(0x8000_0000, 0, CpuidResult { eax: 0x8000_0028, ebx: 0, ecx: 0, edx: 0 });
+registers.set_cpuid(0x8000_001F, 0, CpuidResult { eax: 0x0101_FDFF, ebx: 0x4173, ecx: 0x3EF, edx: 0x1 });
+
+let info = memory_encryption();
+assert!(info.sme && info.sev && info.sev_es && info.sev_snp);
+assert_eq!(info.c_bit_position, 51);
+assert_eq!(info.physical_address_reduction, 5);
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, sev::memory_encryption};
+
+let registers = register_file();
+registers.set_cpuid(0, 0, CpuidResult { eax: 0x10, ebx: 0x756E_6547, ecx: 0x6C65_746E, edx: 0x4965_6E69 });
+registers.set_cpuid(0x8000_001F, 0, CpuidResult { eax: 0x0101_FDFF, ebx: 0x4173, ecx: 0x3EF, edx: 0x1 });
+assert!(!memory_encryption().supported());
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn memory_encryption() -> MemoryEncryptionInfo {
+    if CPUVendor::get_vendor() != CPUVendor::AMD
+        || max_extended_leaf() < leaves::MEMORY_ENCRYPTION {
+        return MemoryEncryptionInfo::default();
+    }
+
+    let result = CPUIDRequest::MemoryEncryption.cpuid();
+    MemoryEncryptionInfo {
+        sme: result.eax.bit(0),
+        sev: result.eax.bit(1),
+        sev_es: result.eax.bit(3),
+        sev_snp: result.eax.bit(4),
+        c_bit_position: result.ebx.bits(0..6) as u8,
+        physical_address_reduction: result.ebx.bits(6..12) as u8,
+    }
+}
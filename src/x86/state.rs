@@ -0,0 +1,218 @@
+//! This module implements a snapshot of the processor state, that is useful for debugging. The
+//! snapshot contains the control registers CR0, CR3 and CR4, the flags register and the current
+//! privilege level and is formatted as a readable register dump, so it can be printed by a panic or
+//! exception handler without any allocation.
+//!
+//! # See also
+//! - [CPU Registers x86-64](https://wiki.osdev.org/CPU_Registers_x86-64) by
+//! [OSDev.org](https://wiki.osdev.org/)
+
+use crate::{
+    current_privilege_level, get_cr0, get_cr4, CR0Flags, CR4Flags, PrivilegeLevel, Register,
+};
+use core::fmt::{Display, Formatter};
+
+/// This structure represents a snapshot of the control registers, the flags register and the
+/// current privilege level. The snapshot is formatted as a readable dump with [Display], so it can
+/// be printed in a panic or exception handler. The capture only reads registers and doesn't
+/// allocate.
+///
+/// ```rust,no_run
+/// use libcpu::CpuState;
+///
+/// let state = CpuState::capture();
+/// assert!(state.cr0.contains(libcpu::CR0Flags::PROTECTED_MODE_ENABLE));
+/// ```
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{set_cr0, CR0Flags, CpuState};
+
+set_cr0(CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::WRITE_PROTECTED | CR0Flags::PAGING);
+let dump = format!("{}", CpuState::capture());
+assert!(dump.contains("PROTECTED_MODE_ENABLE | WRITE_PROTECTED | PAGING"));
+assert!(dump.contains("CPL:    KernelSpace"));
+```
+"#)]
+///
+/// # See also
+/// - [CPU Registers x86-64](https://wiki.osdev.org/CPU_Registers_x86-64) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct CpuState {
+    /// The flags of the CR0 register
+    pub cr0: CR0Flags,
+
+    /// The raw value of the CR3 register (the address of the top-level page table with the PCID or
+    /// the cache flags)
+    pub cr3: Register,
+
+    /// The flags of the CR4 register
+    pub cr4: CR4Flags,
+
+    /// The raw value of the RFLAGS (or EFLAGS) register
+    pub rflags: Register,
+
+    /// The privilege level of the code segment
+    pub privilege_level: PrivilegeLevel,
+}
+
+impl CpuState {
+    /// This function captures the current values of the control registers, the flags register and
+    /// the current privilege level.
+    #[must_use]
+    pub fn capture() -> Self {
+        Self {
+            cr0: get_cr0(),
+            cr3: read_cr3_raw(),
+            cr4: get_cr4(),
+            rflags: read_rflags(),
+            privilege_level: current_privilege_level(),
+        }
+    }
+}
+
+impl Display for CpuState {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "CR0:    {:#018x} (", self.cr0.bits())?;
+        bitflags::parser::to_writer(&self.cr0, &mut *formatter)?;
+        writeln!(formatter, ")")?;
+        writeln!(formatter, "CR3:    {:#018x}", self.cr3)?;
+        write!(formatter, "CR4:    {:#018x} (", self.cr4.bits())?;
+        bitflags::parser::to_writer(&self.cr4, &mut *formatter)?;
+        writeln!(formatter, ")")?;
+        writeln!(formatter, "RFLAGS: {:#018x}", self.rflags)?;
+        write!(formatter, "CPL:    {}", self.privilege_level)
+    }
+}
+
+/// This function returns a compact one-line summary of the CR0, CR3 and CR4 registers, that can be
+/// printed with [Display]. The set flags of CR0 and CR4 are listed with their short names from the
+/// Intel SDM, and CR3 is printed as raw value (e.g.
+/// `CR0[PE|WP|PG] CR3=0x1000 CR4[PAE|PGE|SMEP]`). The registers are read when this function is
+/// called and the summary doesn't allocate.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use libcpu::{
+    fmt_control_registers, mock::{register_file, MockBackend}, set_cr0, set_cr4, CR0Flags,
+    CR4Flags,
+};
+
+set_cr0(CR0Flags::PROTECTED_MODE_ENABLE | CR0Flags::WRITE_PROTECTED | CR0Flags::PAGING);
+register_file().write_control_register("cr3", 0x1000);
+set_cr4(CR4Flags::PHYSCIAL_ADDRESS_EXTENSION | CR4Flags::PAGE_GLOBAL_ENABLED | CR4Flags::SMEP);
+
+let summary = fmt_control_registers().to_string();
+assert_eq!(summary, "CR0[PE|WP|PG] CR3=0x1000 CR4[PAE|PGE|SMEP]");
+```
+"#)]
+///
+/// # See also
+/// - [CPU Registers x86](https://wiki.osdev.org/CPU_Registers_x86) by
+/// [OSDev.org](https://wiki.osdev.org/)
+#[must_use]
+pub fn fmt_control_registers() -> impl Display {
+    ControlRegisters {
+        cr0: get_cr0(),
+        cr3: read_cr3_raw(),
+        cr4: get_cr4(),
+    }
+}
+
+struct ControlRegisters {
+    cr0: CR0Flags,
+    cr3: Register,
+    cr4: CR4Flags,
+}
+
+impl ControlRegisters {
+    const CR0_NAMES: [(CR0Flags, &'static str); 11] = [
+        (CR0Flags::PROTECTED_MODE_ENABLE, "PE"),
+        (CR0Flags::MONITOR_CO_PROCESSOR, "MP"),
+        (CR0Flags::X86_FPU_EMULATION, "EM"),
+        (CR0Flags::TASK_SWITCHED, "TS"),
+        (CR0Flags::EXTENSION_TYPE, "ET"),
+        (CR0Flags::NUMERIC_ERROR, "NE"),
+        (CR0Flags::WRITE_PROTECTED, "WP"),
+        (CR0Flags::ALIGNMENT_CHECK, "AM"),
+        (CR0Flags::NOT_WRITE_THROUGH, "NW"),
+        (CR0Flags::CACHE_DISABLE, "CD"),
+        (CR0Flags::PAGING, "PG"),
+    ];
+
+    const CR4_NAMES: [(CR4Flags, &'static str); 23] = [
+        (CR4Flags::VME, "VME"),
+        (CR4Flags::PVI, "PVI"),
+        (CR4Flags::TIMESTAMP_DISABLE, "TSD"),
+        (CR4Flags::DEBUGGING_EXTENSIONS, "DE"),
+        (CR4Flags::PAGE_SIZE_EXTENSION, "PSE"),
+        (CR4Flags::PHYSCIAL_ADDRESS_EXTENSION, "PAE"),
+        (CR4Flags::MACHINE_CHECK_EXCEPTION, "MCE"),
+        (CR4Flags::PAGE_GLOBAL_ENABLED, "PGE"),
+        (CR4Flags::PCE, "PCE"),
+        (CR4Flags::OSSupportForFXSR, "OSFXSR"),
+        (CR4Flags::OSSupportXMMExcept, "OSXMMEXCPT"),
+        (CR4Flags::UMIP, "UMIP"),
+        (CR4Flags::LA57, "LA57"),
+        (CR4Flags::VIRTUAL_MACHINE_EXT_ENABLE, "VMXE"),
+        (CR4Flags::SAFER_MODE_EXT_ENABLE, "SMXE"),
+        (CR4Flags::FSGSBASE, "FSGSBASE"),
+        (CR4Flags::PCID_ENABLE, "PCIDE"),
+        (CR4Flags::OSXSAVE_ENABLE, "OSXSAVE"),
+        (CR4Flags::SMEP, "SMEP"),
+        (CR4Flags::SMAP, "SMAP"),
+        (CR4Flags::ProtectionKeyEnable, "PKE"),
+        (CR4Flags::ControlFlowEnforcement, "CET"),
+        (CR4Flags::PKS, "PKS"),
+    ];
+
+    fn write_names<T: bitflags::Flags + Copy>(
+        formatter: &mut Formatter<'_>,
+        flags: T,
+        names: &[(T, &str)],
+    ) -> core::fmt::Result {
+        let mut separator = "";
+        for (_, name) in names.iter().filter(|(flag, _)| flags.contains(*flag)) {
+            write!(formatter, "{}{}", separator, name)?;
+            separator = "|";
+        }
+        Ok(())
+    }
+}
+
+impl Display for ControlRegisters {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "CR0[")?;
+        Self::write_names(formatter, self.cr0, &Self::CR0_NAMES)?;
+        write!(formatter, "] CR3={:#x} CR4[", self.cr3)?;
+        Self::write_names(formatter, self.cr4, &Self::CR4_NAMES)?;
+        write!(formatter, "]")
+    }
+}
+
+// The CR3 helpers are truncating the value to the flags, but the dump needs the page table address
+#[allow(unused_assignments)]
+fn read_cr3_raw() -> Register {
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_control_register("cr3");
+
+    #[cfg(not(feature = "mock"))]
+    let mut value = 0;
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        core::arch::asm!("mov {}, cr3", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+    value
+}
+
+// Reading the flags register isn't privileged, so it's also read from the host with the mock
+fn read_rflags() -> Register {
+    let value: Register;
+    unsafe {
+        #[cfg(target_arch = "x86_64")]
+        core::arch::asm!("pushfq", "pop {}", out(reg) value, options(nomem, preserves_flags));
+
+        #[cfg(target_arch = "x86")]
+        core::arch::asm!("pushfd", "pop {}", out(reg) value, options(nomem, preserves_flags));
+    }
+    value
+}
@@ -0,0 +1,176 @@
+//! This module implements the functionality to read the Time Stamp Counter (TSC) of x86 and x86_64
+//! processors. The TSC is a 64-bit counter, that is increased with every clock cycle (or with a
+//! constant rate on newer processors) and can be used for short delays in the early boot, when no
+//! timer is set up yet.
+//!
+//! # See also
+//! - [Time Stamp Counter](https://wiki.osdev.org/TSC) by [OSDev.org](https://wiki.osdev.org/)
+//! - [CPUID](https://en.wikipedia.org/wiki/CPUID#EAX=15h_and_EAX=16h:_CPU,_TSC,_Bus_and_Core_Crystal_Clock_Frequencies)
+//! by [Wikipedia](https://wikipedia.org)
+
+use crate::{
+    x86::{
+        cpuid::{max_basic_leaf, CPUIDRequest},
+        leaves,
+    },
+    CPUFeature,
+    CpuError,
+};
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+
+/// This function reads the current value of the Time Stamp Counter with the `rdtsc` instruction.
+///
+/// # See also
+/// - [RDTSC](https://www.felixcloutier.com/x86/rdtsc) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+#[must_use]
+pub fn read_tsc() -> u64 {
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_tsc();
+
+    #[cfg(not(feature = "mock"))]
+    let value = {
+        let (lower, higher): (u32, u32);
+        unsafe {
+            asm!(
+                "rdtsc",
+                out("eax") lower,
+                out("edx") higher,
+                options(nomem, nostack, preserves_flags)
+            );
+        }
+        (u64::from(higher) << 32) | u64::from(lower)
+    };
+    value
+}
+
+/// This function returns the frequency of the Time Stamp Counter in Hz. The frequency is
+/// calculated with the crystal clock and the TSC ratio of CPUID leaf 0x15. If the crystal clock
+/// isn't reported, the base frequency of CPUID leaf 0x16 is used. If the processor reports
+/// neither, this function returns [None].
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn tsc_frequency_hz() -> Option<u64> {
+    if max_basic_leaf() >= leaves::TIME_STAMP_COUNTER {
+        let result = CPUIDRequest::TimeStampCounter.cpuid();
+        if result.eax != 0 && result.ebx != 0 && result.ecx != 0 {
+            return Some(u64::from(result.ecx) * u64::from(result.ebx) / u64::from(result.eax));
+        }
+    }
+
+    cpu_frequency_mhz().map(|(base_frequency, _, _)| u64::from(base_frequency) * 1_000_000)
+}
+
+/// This function returns the base, maximum and bus frequency of the processor in MHz, that are
+/// reported by CPUID leaf 0x16. If the processor doesn't report the leaf (older processors) or the
+/// frequencies are not enumerated, this function returns [None].
+///
+/// These frequencies are the nominal values from the processor brand and may differ from the
+/// actual frequencies, so they are only useful as a fallback for the calibration of timers.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, tsc::cpu_frequency_mhz};
+
+// Captured on an Intel Core i7-8700 processor
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0x16, ebx: 0, ecx: 0, edx: 0 });
+register_file().set_cpuid(0x16, 0, CpuidResult { eax: 0xC80, ebx: 0x11F8, ecx: 0x64, edx: 0 });
+assert_eq!(cpu_frequency_mhz(), Some((3200, 4600, 100)));
+```
+
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, tsc::cpu_frequency_mhz};
+
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0x15, ebx: 0, ecx: 0, edx: 0 });
+register_file().set_cpuid(0x16, 0, CpuidResult { eax: 0xC80, ebx: 0x11F8, ecx: 0x64, edx: 0 });
+assert_eq!(cpu_frequency_mhz(), None);
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn cpu_frequency_mhz() -> Option<(u16, u16, u16)> {
+    if max_basic_leaf() < leaves::PROCESSOR_FREQUENCY {
+        return None;
+    }
+
+    let result = CPUIDRequest::ProcessorFrequency.cpuid();
+    let base_frequency = (result.eax & 0xFFFF) as u16;
+    if base_frequency == 0 {
+        return None;
+    }
+    Some((base_frequency, (result.ebx & 0xFFFF) as u16, (result.ecx & 0xFFFF) as u16))
+}
+
+/// This function busy-waits for at least the specified count of nanoseconds by polling the Time
+/// Stamp Counter. The wraparound of the counter is handled, so the delay is also correct if the
+/// counter overflows while waiting.
+///
+/// If the processor doesn't support the [CPUFeature::TSC] feature, this function returns
+/// [CpuError::UnsupportedFeature]. If the frequency of the counter is unknown, this function
+/// returns [CpuError::UnknownTscFrequency].
+///
+/// ```rust,no_run
+/// use libcpu::tsc::{read_tsc, spin_delay_ns, tsc_frequency_hz};
+///
+/// let start = read_tsc();
+/// spin_delay_ns(1_000_000).unwrap();
+/// let elapsed = read_tsc().wrapping_sub(start);
+/// assert!(elapsed >= tsc_frequency_hz().unwrap() / 1_000);
+/// ```
+#[cfg_attr(feature = "mock", doc = r#"
+The counter of this backend starts right before the wraparound and advances by 100 cycles per
+read. With a 1 GHz counter, a delay of 1000 ns ends after the start value and 10 further reads:
+```rust
+use core::arch::x86_64::CpuidResult;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use libcpu::{mock::{register_file, set_backend, MockBackend}, tsc::spin_delay_ns};
+
+struct WrappingCounter {
+    tsc: AtomicU64,
+    reads: AtomicUsize,
+}
+
+impl MockBackend for WrappingCounter {
+    fn read_tsc(&self) -> u64 {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.tsc.fetch_add(100, Ordering::SeqCst)
+    }
+}
+
+static PROCESSOR: WrappingCounter = WrappingCounter {
+    tsc: AtomicU64::new(u64::MAX - 250),
+    reads: AtomicUsize::new(0),
+};
+
+register_file().set_cpuid(0, 0, CpuidResult { eax: 0x15, ebx: 0, ecx: 0, edx: 0 });
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 1 << 4 });
+register_file().set_cpuid(0x15, 0, CpuidResult { eax: 1, ebx: 1, ecx: 1_000_000_000, edx: 0 });
+unsafe { set_backend(&PROCESSOR) };
+
+spin_delay_ns(1_000).unwrap();
+assert_eq!(PROCESSOR.reads.load(Ordering::SeqCst), 11);
+assert_eq!(PROCESSOR.tsc.load(Ordering::SeqCst), 849);
+```
+"#)]
+pub fn spin_delay_ns(ns: u64) -> Result<(), CpuError> {
+    if !CPUFeature::TSC.is_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::TSC));
+    }
+
+    let frequency = tsc_frequency_hz().ok_or(CpuError::UnknownTscFrequency)?;
+    let cycles = (u128::from(ns) * u128::from(frequency) / 1_000_000_000) as u64;
+    let start = read_tsc();
+    while read_tsc().wrapping_sub(start) < cycles {
+        core::hint::spin_loop();
+    }
+    Ok(())
+}
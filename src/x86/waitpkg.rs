@@ -0,0 +1,184 @@
+//! This module implements the user-level wait instructions of the WAITPKG extension. With
+//! `umonitor`, the processor monitors a range of memory. With `umwait`, the processor waits in an
+//! optimized state until the monitored memory is written or the deadline is reached. With `tpause`,
+//! the processor waits until the deadline is reached without monitoring memory. Unlike `monitor`
+//! and `mwait`, these instructions are also available in the user space, so they are used for
+//! polling loops with a low latency and a low power consumption.
+//!
+//! The deadline is a value of the Time Stamp Counter (see [crate::tsc::read_tsc]). The operating
+//! system can limit the maximum wait time with the IA32_UMWAIT_CONTROL MSR, so the wait can end
+//! before the deadline is reached.
+//!
+//! # See also
+//! - [UMWAIT](https://www.felixcloutier.com/x86/umwait) by
+//! [Felix Clountier](https://www.felixcloutier.com)
+//! - [TPAUSE](https://www.felixcloutier.com/x86/tpause) by
+//! [Felix Clountier](https://www.felixcloutier.com)
+
+use crate::{
+    tsc::read_tsc,
+    CPUFeature,
+    CpuError,
+};
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+
+/// This enum represents the optimized state, in which the processor waits. The state is selected
+/// by the bit 0 of the control operand of `umwait` and `tpause`.
+///
+/// Here is a list of all states with description:
+/// - [WaitState::PowerOptimized] - The processor waits in the C0.2 state. This state saves more
+/// power, but the wakeup takes longer. The operating system can disable this state with the
+/// IA32_UMWAIT_CONTROL MSR, so the C0.1 state is used instead.
+/// - [WaitState::PerformanceOptimized] - The processor waits in the C0.1 state. This state saves
+/// less power, but the wakeup is faster.
+#[repr(u32)]
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub enum WaitState {
+    /// The processor waits in the C0.2 state, which saves more power but has a slower wakeup.
+    PowerOptimized = 0,
+
+    /// The processor waits in the C0.1 state, which saves less power but has a faster wakeup.
+    PerformanceOptimized = 1,
+}
+
+/// This function returns whether the processor supports the user-level wait instructions. This is
+/// indicated by the [CPUFeature::WAITKG] feature.
+#[inline]
+#[must_use]
+pub fn waitpkg_supported() -> bool {
+    CPUFeature::WAITKG.is_supported()
+}
+
+/// This function arms the address monitoring for the specified address with the `umonitor`
+/// instruction. A following [umwait] ends, when the monitored range is written. If the processor
+/// doesn't support the [CPUFeature::WAITKG] feature, this function returns
+/// [CpuError::UnsupportedFeature].
+///
+/// # See also
+/// - [UMONITOR](https://www.felixcloutier.com/x86/umonitor) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn umonitor(address: *const u8) -> Result<(), CpuError> {
+    if !waitpkg_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::WAITKG));
+    }
+
+    #[cfg(feature = "mock")]
+    let _ = address;
+
+    #[cfg(not(feature = "mock"))]
+    unsafe {
+        asm!("umonitor {}", in(reg) address, options(readonly, nostack, preserves_flags));
+    }
+    Ok(())
+}
+
+/// This function waits with the `umwait` instruction in the specified state, until the range armed
+/// by [umonitor] is written, an interrupt occurs or the deadline is reached. This function returns
+/// whether the wait ended because of a time limit (the deadline or the limit of the operating
+/// system) instead of a wakeup. If the processor doesn't support the [CPUFeature::WAITKG] feature,
+/// this function returns [CpuError::UnsupportedFeature].
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{
+    mock::register_file,
+    tsc::read_tsc,
+    waitpkg::{tpause, umonitor, umwait, WaitState},
+};
+
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 5, edx: 0 });
+
+let value = 0_u8;
+umonitor(&value).unwrap();
+assert_eq!(umwait(WaitState::PerformanceOptimized, read_tsc()), Ok(true));
+assert_eq!(tpause(WaitState::PowerOptimized, u64::MAX), Ok(false));
+```
+
+```rust
+use libcpu::{
+    waitpkg::{tpause, umonitor, umwait, WaitState},
+    CPUFeature, CpuError,
+};
+
+let error = Err(CpuError::UnsupportedFeature(CPUFeature::WAITKG));
+assert_eq!(umonitor(core::ptr::null()), error);
+assert_eq!(umwait(WaitState::PowerOptimized, 0), error.map(|_| false));
+assert_eq!(tpause(WaitState::PowerOptimized, 0), error.map(|_| false));
+```
+"#)]
+///
+/// # See also
+/// - [UMWAIT](https://www.felixcloutier.com/x86/umwait) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn umwait(state: WaitState, deadline: u64) -> Result<bool, CpuError> {
+    if !waitpkg_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::WAITKG));
+    }
+
+    #[cfg(feature = "mock")]
+    let os_limit = {
+        let _ = state;
+        false
+    };
+
+    #[cfg(not(feature = "mock"))]
+    let os_limit = {
+        let carry: u8;
+        unsafe {
+            asm!(
+                "umwait {control:e}",
+                "setc {carry}",
+                control = in(reg) state as u32,
+                carry = out(reg_byte) carry,
+                in("edx") (deadline >> 32) as u32,
+                in("eax") deadline as u32,
+                options(nomem, nostack)
+            );
+        }
+        carry != 0
+    };
+    Ok(os_limit || read_tsc() >= deadline)
+}
+
+/// This function waits with the `tpause` instruction in the specified state, until an interrupt
+/// occurs or the deadline is reached. This function returns whether the wait ended because of a
+/// time limit (the deadline or the limit of the operating system) instead of a wakeup. If the
+/// processor doesn't support the [CPUFeature::WAITKG] feature, this function returns
+/// [CpuError::UnsupportedFeature].
+///
+/// # See also
+/// - [TPAUSE](https://www.felixcloutier.com/x86/tpause) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[inline]
+pub fn tpause(state: WaitState, deadline: u64) -> Result<bool, CpuError> {
+    if !waitpkg_supported() {
+        return Err(CpuError::UnsupportedFeature(CPUFeature::WAITKG));
+    }
+
+    #[cfg(feature = "mock")]
+    let os_limit = {
+        let _ = state;
+        false
+    };
+
+    #[cfg(not(feature = "mock"))]
+    let os_limit = {
+        let carry: u8;
+        unsafe {
+            asm!(
+                "tpause {control:e}",
+                "setc {carry}",
+                control = in(reg) state as u32,
+                carry = out(reg_byte) carry,
+                in("edx") (deadline >> 32) as u32,
+                in("eax") deadline as u32,
+                options(nomem, nostack)
+            );
+        }
+        carry != 0
+    };
+    Ok(os_limit || read_tsc() >= deadline)
+}
@@ -0,0 +1,281 @@
+//! This module implements the functionality to query the XSAVE feature set of x86 and x86_64
+//! processors. The XSAVE instructions are saving and restoring the extended processor state (like
+//! the x87 FPU, SSE, AVX or AVX-512 registers) into the XSAVE area in memory.
+//!
+//! Before the kernel enables XSAVE and the state components in the XCR0 register, it must know the
+//! size of the XSAVE area. This size is reported by CPUID leaf 0xD sub-leaf 0. The size and offset
+//! of every state component (starting with component 2) are reported by the sub-leaf with the
+//! number of the component.
+//!
+//! # See also
+//! - [CPUID](https://en.wikipedia.org/wiki/CPUID#EAX=0Dh:_XSAVE_features_and_state_components)
+//! by [Wikipedia](https://wikipedia.org)
+//! - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 1](https://cdrdv2-public.intel.com/782158/253665-sdm-vol-1.pdf)
+//! Chapter 13 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+
+use crate::{
+    x86::{cpuid::{cpuid, CPUIDRequest}, leaves},
+    CPUFeature,
+};
+use bit_field::BitField;
+use bitflags::bitflags;
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+
+/// This function returns the size of the XSAVE area in bytes, that is required by the state
+/// components currently enabled in the XCR0 register (CPUID leaf 0xD, ebx). If the processor
+/// doesn't support the XSAVE feature, this function returns [None].
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, xsave::xsave_area_size};
+
+// The enabled x87, SSE and AVX state needs 832 bytes, all supported components need 2696 bytes
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 26, edx: 0 });
+register_file().set_cpuid(0xD, 0, CpuidResult { eax: 0b1110_0111, ebx: 832, ecx: 2696, edx: 0 });
+
+assert_eq!(xsave_area_size(), Some(832));
+```
+"#)]
+///
+/// # See also
+/// - [XSAVE](https://www.felixcloutier.com/x86/xsave) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn xsave_area_size() -> Option<u32> {
+    if !CPUFeature::XSAVE.is_supported() {
+        return None;
+    }
+
+    Some(CPUIDRequest::ExtendedState.cpuid().ebx)
+}
+
+/// This function returns the mask of the state components, that are supported by the XSAVE
+/// feature set of the processor (CPUID leaf 0xD, edx:eax). Unlike [xsave_feature_mask], this mask
+/// is independent of the components enabled in the XCR0 register, so the kernel uses it to decide
+/// which components can be enabled. If the processor doesn't support the XSAVE feature, this
+/// function returns [None].
+///
+/// Bit 2 indicates the AVX state, the bits 5 to 7 the AVX-512 state (opmask, upper halves of
+/// ZMM0-15 and ZMM16-31).
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, xsave::xsave_supported_components};
+
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 26, edx: 0 });
+register_file().set_cpuid(0xD, 0, CpuidResult { eax: 0b1110_0111, ebx: 0, ecx: 0, edx: 0 });
+
+let components = xsave_supported_components().unwrap();
+assert_eq!(components & (1 << 2), 1 << 2);
+assert_eq!(components & 0b1110_0000, 0b1110_0000);
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn xsave_supported_components() -> Option<u64> {
+    if !CPUFeature::XSAVE.is_supported() {
+        return None;
+    }
+
+    let result = CPUIDRequest::ExtendedState.cpuid();
+    Some((u64::from(result.edx) << 32) | u64::from(result.eax))
+}
+
+/// This function returns the mask of the state components, that are currently enabled in the XCR0
+/// register. This mask determines the size returned by [xsave_area_size]. The register is read with
+/// the `xgetbv` instruction, so this function returns [None] if the operating system hasn't enabled
+/// XSAVE (indicated by the [CPUFeature::OSXSAVE] feature).
+///
+/// # See also
+/// - [XGETBV](https://www.felixcloutier.com/x86/xgetbv) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn xsave_feature_mask() -> Option<u64> {
+    if !CPUFeature::OSXSAVE.is_supported() {
+        return None;
+    }
+
+    #[cfg(feature = "mock")]
+    let value = crate::mock::backend().read_extended_control_register(0);
+
+    #[cfg(not(feature = "mock"))]
+    let value = {
+        let (lower, higher): (u32, u32);
+        unsafe {
+            asm!(
+                "xgetbv",
+                in("ecx") 0,
+                out("eax") lower,
+                out("edx") higher,
+                options(nomem, nostack, preserves_flags)
+            );
+        }
+        (u64::from(higher) << 32) | u64::from(lower)
+    };
+    Some(value)
+}
+
+bitflags! {
+    /// This structure represents the state components in the XCR0 register, that are relevant for
+    /// the usability of the vector extensions.
+    ///
+    /// Here is a list of all flags with description:
+    /// - [XCR0Flags::X87] - The state of the x87 FPU (always set)
+    /// - [XCR0Flags::SSE] - The state of the XMM registers
+    /// - [XCR0Flags::AVX] - The state of the upper halves of the YMM registers
+    /// - [XCR0Flags::OPMASK] - The state of the AVX-512 opmask registers (k0 to k7)
+    /// - [XCR0Flags::ZMM_HI256] - The state of the upper halves of the ZMM0 to ZMM15 registers
+    /// - [XCR0Flags::HI16_ZMM] - The state of the ZMM16 to ZMM31 registers
+    ///
+    /// # See also
+    /// - [XCR0](https://wiki.osdev.org/CPU_Registers_x86#XCR0) by
+    /// [OSDev.org](https://wiki.osdev.org/)
+    #[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+    pub struct XCR0Flags: u64 {
+        const X87       = 1 << 0;
+        const SSE       = 1 << 1;
+        const AVX       = 1 << 2;
+        const OPMASK    = 1 << 5;
+        const ZMM_HI256 = 1 << 6;
+        const HI16_ZMM  = 1 << 7;
+    }
+}
+
+/// This function returns whether AVX instructions can be executed. The processor must support the
+/// [CPUFeature::AVX] feature and the operating system must have enabled XSAVE
+/// ([CPUFeature::OSXSAVE]) and the SSE and AVX state in the XCR0 register. Otherwise, executing an
+/// AVX instruction raises an Invalid Opcode exception, even if CPUID reports AVX.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::{register_file, MockBackend}, xsave::{avx_usable, XCR0Flags}};
+
+// AVX is present, but the operating system only enabled the SSE state
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: (1 << 27) | (1 << 28), edx: 0 });
+register_file().write_extended_control_register(0, (XCR0Flags::X87 | XCR0Flags::SSE).bits());
+assert!(!avx_usable());
+
+let state = XCR0Flags::X87 | XCR0Flags::SSE | XCR0Flags::AVX;
+register_file().write_extended_control_register(0, state.bits());
+assert!(avx_usable());
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 1](https://cdrdv2-public.intel.com/782158/253665-sdm-vol-1.pdf)
+/// Chapter 14.3 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[must_use]
+pub fn avx_usable() -> bool {
+    CPUFeature::AVX.is_supported()
+        && xsave_feature_mask().map_or(false, |mask| {
+            XCR0Flags::from_bits_retain(mask).contains(XCR0Flags::SSE | XCR0Flags::AVX)
+        })
+}
+
+/// This function returns whether AVX-512 instructions can be executed. Like [avx_usable], the
+/// processor must support the [CPUFeature::AVX512F] feature and the operating system must have
+/// enabled the SSE, AVX, opmask and ZMM state in the XCR0 register.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::{register_file, MockBackend}, xsave::{avx512_usable, avx_usable, XCR0Flags}};
+
+// AVX-512 is present, but the operating system didn't enable the ZMM state
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: (1 << 27) | (1 << 28), edx: 0 });
+register_file().set_cpuid(7, 0, CpuidResult { eax: 0, ebx: 1 << 16, ecx: 0, edx: 0 });
+let state = XCR0Flags::X87 | XCR0Flags::SSE | XCR0Flags::AVX;
+register_file().write_extended_control_register(0, state.bits());
+assert!(avx_usable());
+assert!(!avx512_usable());
+
+register_file().write_extended_control_register(0, XCR0Flags::all().bits());
+assert!(avx512_usable());
+```
+"#)]
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 1](https://cdrdv2-public.intel.com/782158/253665-sdm-vol-1.pdf)
+/// Chapter 15.2 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[must_use]
+pub fn avx512_usable() -> bool {
+    const ZMM_STATE: XCR0Flags = XCR0Flags::SSE
+        .union(XCR0Flags::AVX)
+        .union(XCR0Flags::OPMASK)
+        .union(XCR0Flags::ZMM_HI256)
+        .union(XCR0Flags::HI16_ZMM);
+
+    CPUFeature::AVX512F.is_supported()
+        && xsave_feature_mask().map_or(false, |mask| {
+            XCR0Flags::from_bits_retain(mask).contains(ZMM_STATE)
+        })
+}
+
+/// This structure represents the layout of a single state component in the XSAVE area, which is
+/// reported by CPUID leaf 0xD with the number of the component as sub-leaf.
+///
+/// - `size` - The size of the component in bytes
+/// - `offset` - The offset of the component in the standard (non-compacted) format of the XSAVE
+/// area. This offset is always zero for supervisor components, which are only saved in the
+/// compacted format by `xsaves`.
+/// - `supervisor` - Whether the component is a supervisor state component (enabled in the
+/// IA32_XSS MSR instead of the XCR0 register)
+/// - `aligned` - Whether the component is aligned to 64 bytes in the compacted format
+///
+/// # See also
+/// - [Intel 64 and IA-32 Architectures Software Developer's Manual Volume 1](https://cdrdv2-public.intel.com/782158/253665-sdm-vol-1.pdf)
+/// Chapter 13.2 by [Intel Corporation](https://www.intel.de/content/www/us/en/homepage.html)
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
+pub struct XsaveComponent {
+    pub size: u32,
+    pub offset: u32,
+    pub supervisor: bool,
+    pub aligned: bool,
+}
+
+/// This function returns the size and offset of the state component with the specified number
+/// from CPUID leaf 0xD. The components 0 (x87) and 1 (SSE) are part of the legacy region of the
+/// XSAVE area and have no sub-leaf, so this function returns [None] for them. [None] is also
+/// returned for components, that are not supported by the processor, and if the processor doesn't
+/// support the XSAVE feature.
+#[cfg_attr(feature = "mock", doc = r#"
+```rust
+use core::arch::x86_64::CpuidResult;
+use libcpu::{mock::register_file, xsave::{xsave_component, XsaveComponent}};
+
+// Captured from an Intel Core i7-8700 (AVX state)
+register_file().set_cpuid(1, 0, CpuidResult { eax: 0, ebx: 0, ecx: 1 << 26, edx: 0 });
+register_file().set_cpuid(0xD, 2, CpuidResult { eax: 0x100, ebx: 0x240, ecx: 0, edx: 0 });
+
+let avx = xsave_component(2).unwrap();
+assert_eq!(avx, XsaveComponent { size: 256, offset: 576, supervisor: false, aligned: false });
+assert_eq!(xsave_component(1), None);
+assert_eq!(xsave_component(5), None);
+```
+"#)]
+///
+/// # See also
+/// - [CPUID](https://www.felixcloutier.com/x86/cpuid) by
+/// [Felix Clountier](https://www.felixcloutier.com)
+#[must_use]
+pub fn xsave_component(component: u32) -> Option<XsaveComponent> {
+    if !(2..63).contains(&component)
+        || !CPUFeature::XSAVE.is_supported() {
+        return None;
+    }
+
+    let result = cpuid(leaves::EXTENDED_STATE, Some(component));
+    if result.eax == 0 {
+        return None;
+    }
+
+    Some(XsaveComponent {
+        size: result.eax,
+        offset: result.ebx,
+        supervisor: result.ecx.get_bit(0),
+        aligned: result.ecx.get_bit(1),
+    })
+}